@@ -0,0 +1,75 @@
+//! Runs every ROM in `tests/test_roms.txt` and prints the README result tables.
+//!
+//! Usage: conformance <roms dir> [--readme README.md]
+//!
+//! With `--readme`, the tables between the conformance markers in the given
+//! file are replaced instead of printed.
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use zetaboy::conformance::{self, README_BEGIN, README_END, Status};
+
+const MANIFEST: &str = include_str!("../tests/test_roms.txt");
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (roms_dir, readme) = match args.as_slice() {
+        [dir] => (PathBuf::from(dir), None),
+        [dir, flag, readme] if flag == "--readme" => {
+            (PathBuf::from(dir), Some(PathBuf::from(readme)))
+        }
+        _ => return Err("Usage: conformance <roms dir> [--readme README.md]".into()),
+    };
+
+    let roms = conformance::parse_manifest(MANIFEST)?;
+    let mut results = Vec::new();
+
+    for rom in roms {
+        let status = match conformance::run_test(&rom, &roms_dir) {
+            Ok(result) => {
+                if result.status != rom.expected {
+                    eprintln!(
+                        "{}: {:?} (expected {:?}) {}",
+                        rom.name,
+                        result.status,
+                        rom.expected,
+                        result.detail.trim()
+                    );
+                }
+                result.status
+            }
+            Err(e) => {
+                eprintln!("{}: {}", rom.name, e);
+                Status::Fail
+            }
+        };
+        results.push((rom, status));
+    }
+
+    let tables = conformance::markdown_tables(&results);
+
+    match readme {
+        None => print!("{}", tables),
+        Some(path) => {
+            let contents = std::fs::read_to_string(&path)?;
+            let begin = contents
+                .find(README_BEGIN)
+                .ok_or("Missing conformance begin marker")?;
+            let end = contents
+                .find(README_END)
+                .ok_or("Missing conformance end marker")?;
+            let updated = format!(
+                "{}{}\n\n{}\n{}",
+                &contents[..begin],
+                README_BEGIN,
+                tables,
+                &contents[end..]
+            );
+            std::fs::write(&path, updated)?;
+            println!("Updated {}", path.display());
+        }
+    }
+
+    Ok(())
+}
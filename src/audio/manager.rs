@@ -1,15 +1,16 @@
-use std::{
-    collections::VecDeque,
-    sync::mpsc::{Receiver, SyncSender},
-};
+#[cfg(feature = "audio")]
+use std::{collections::VecDeque, sync::mpsc::Receiver};
+use std::sync::mpsc::SyncSender;
 
+#[cfg(feature = "audio")]
 use super::AudioOutput;
-use crate::emulator::CPU_FREQUENCY;
 
+#[cfg(feature = "audio")]
 pub struct AudioManager {
     _output: AudioOutput,
 }
 
+#[cfg(feature = "audio")]
 impl AudioManager {
     pub fn new() -> Result<(Self, AudioSampleSender), Box<dyn std::error::Error>> {
         let (tx, rx) = std::sync::mpsc::sync_channel::<(f32, f32)>(16384);
@@ -45,42 +46,25 @@ impl AudioManager {
 
 pub struct AudioSampleSender {
     sender: SyncSender<(f32, f32)>,
-    sample_accumulator: f32,
-    ticks_per_sample: f32,
-    prev_sample: (f32, f32),
-    current_sample: (f32, f32),
+    sample_rate: f32,
 }
 
 impl AudioSampleSender {
+    #[cfg(feature = "audio")]
     fn new(sender: SyncSender<(f32, f32)>, sample_rate: f32) -> Self {
         Self {
             sender,
-            sample_accumulator: 0.0,
-            ticks_per_sample: CPU_FREQUENCY as f32 / sample_rate,
-            prev_sample: (0.0, 0.0),
-            current_sample: (0.0, 0.0),
+            sample_rate,
         }
     }
 
-    pub fn process_cpu_cycles(&mut self, t_cycles: u32, mut get_sample: impl FnMut() -> (f32, f32)) {
-        for _ in 0..t_cycles {
-            self.prev_sample = self.current_sample;
-            self.current_sample = get_sample();
-            
-            self.sample_accumulator += 1.0;
+    /// Output sample rate of the audio device, in Hz.
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
 
-            if self.sample_accumulator >= self.ticks_per_sample {
-                self.sample_accumulator -= self.ticks_per_sample;
-                
-                // Calculate interpolation factor (0.0 to 1.0)
-                let t = self.sample_accumulator / self.ticks_per_sample;
-                
-                // Linear interpolation: prev + t * (current - prev)
-                let interp_l = self.prev_sample.0 + t * (self.current_sample.0 - self.prev_sample.0);
-                let interp_r = self.prev_sample.1 + t * (self.current_sample.1 - self.prev_sample.1);
-                
-                let _ = self.sender.try_send((interp_l, interp_r));
-            }
-        }
+    /// Queues a stereo sample for playback. Samples are dropped if the queue is full.
+    pub fn send(&mut self, sample: (f32, f32)) {
+        let _ = self.sender.try_send(sample);
     }
 }
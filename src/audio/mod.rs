@@ -1,5 +1,9 @@
+#[cfg(feature = "audio")]
 mod output;
 mod manager;
 
+#[cfg(feature = "audio")]
 pub use output::AudioOutput;
-pub use manager::{AudioManager, AudioSampleSender};
\ No newline at end of file
+#[cfg(feature = "audio")]
+pub use manager::AudioManager;
+pub use manager::AudioSampleSender;
@@ -0,0 +1,152 @@
+//! Command-line options.
+
+use std::path::PathBuf;
+
+use crate::emulator::EmulatorConfig;
+use crate::emulator::model::Model;
+
+pub const USAGE: &str = "\
+ZetaBoy - Game Boy (Color) emulator
+
+Usage: zetaboy [OPTIONS] [ROM]
+
+Options:
+  --model <MODEL>       Hardware model: DMG0, DMG, MGB, SGB, CGB or AGB
+                        (default: CGB for CGB-compatible cartridges, DMG otherwise)
+  --dmg                 Same as --model DMG
+  --cgb                 Same as --model CGB
+  --boot-rom <FILE>     Boot through the given boot ROM
+  --cgb-palette <KEYS>  Colours for DMG games on CGB, as if KEYS were held during
+                        the boot logo: Up, Up+A, Up+B, Left, Left+A, Left+B, Down,
+                        Down+A, Down+B, Right, Right+A or Right+B
+                        (default: picked from the game title)
+  --load-state <FILE>   Load a save state after loading the ROM
+  --link-listen <ADDR>  Wait for another emulator to connect a link cable, on
+                        a TCP HOST:PORT or a Unix socket given as unix:PATH
+  --link-connect <ADDR> Connect a link cable to an emulator started with
+                        --link-listen. During a transfer, the emulator that
+                        is ahead waits for the other
+  --printer             Connect a Game Boy Printer, saving pages next to the ROM
+  --headless            Run without a window (requires a ROM and --frames)
+  --frames <N>          Number of frames to run in headless mode
+  --screenshot <FILE>   Save the last frame as a PNG (headless mode)
+  --scale <N>           Initial window scale (1-10)
+  --mute                Disable audio output
+  --trace               Print the CPU state after each instruction
+  --print-serial        Print bytes sent through the serial port (test ROM output)
+  --cart-info           Print the cartridge header information
+  -h, --help            Print this help
+";
+
+const MAX_SCALE: u32 = 10;
+
+/// Which end of a link cable socket this emulator is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LinkOption {
+    Listen(String),
+    Connect(String),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub rom_path: Option<String>,
+    pub config: EmulatorConfig,
+    pub load_state: Option<String>,
+    pub link: Option<LinkOption>,
+    pub printer: bool,
+    pub headless: bool,
+    pub frames: Option<u32>,
+    pub screenshot: Option<PathBuf>,
+    pub scale: Option<u32>,
+    pub mute: bool,
+}
+
+impl Options {
+    /// Parses the command-line arguments (without the program name).
+    ///
+    /// Returns `Ok(None)` when help was requested.
+    pub fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("Missing value for {}", name))
+            };
+
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--model" => options.config.model = Some(value(arg)?.parse()?),
+                "--dmg" => options.config.model = Some(Model::Dmg),
+                "--cgb" => options.config.model = Some(Model::Cgb),
+                "--boot-rom" => options.config.boot_rom = Some(PathBuf::from(value(arg)?)),
+                "--cgb-palette" => options.config.compat_palette = Some(value(arg)?.parse()?),
+                "--load-state" => options.load_state = Some(value(arg)?),
+                "--link-listen" => options.link = Some(LinkOption::Listen(value(arg)?)),
+                "--link-connect" => options.link = Some(LinkOption::Connect(value(arg)?)),
+                "--printer" => options.printer = true,
+                "--headless" => options.headless = true,
+                "--frames" => options.frames = Some(parse_number(arg, &value(arg)?)?),
+                "--screenshot" => options.screenshot = Some(PathBuf::from(value(arg)?)),
+                "--scale" => {
+                    let scale = parse_number(arg, &value(arg)?)?;
+                    if !(1..=MAX_SCALE).contains(&scale) {
+                        return Err(format!("--scale must be between 1 and {}", MAX_SCALE));
+                    }
+                    options.scale = Some(scale);
+                }
+                "--mute" => options.mute = true,
+                "--trace" => options.config.trace = true,
+                "--print-serial" => options.config.print_serial = true,
+                "--cart-info" => options.config.print_cart_info = true,
+                _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+                _ if options.rom_path.is_none() => options.rom_path = Some(arg.clone()),
+                _ => return Err(format!("Unexpected argument: {}", arg)),
+            }
+        }
+
+        options.validate()?;
+        Ok(Some(options))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.headless {
+            if self.rom_path.is_none() {
+                return Err("--headless requires a ROM".to_string());
+            }
+            if self.frames.is_none() {
+                return Err("--headless requires --frames".to_string());
+            }
+        } else {
+            if self.frames.is_some() {
+                return Err("--frames is only valid with --headless".to_string());
+            }
+            if self.screenshot.is_some() {
+                return Err("--screenshot is only valid with --headless".to_string());
+            }
+        }
+
+        if self.load_state.is_some() && self.rom_path.is_none() {
+            return Err("--load-state requires a ROM".to_string());
+        }
+        if self.link.is_some() && self.rom_path.is_none() {
+            return Err("--link-listen and --link-connect require a ROM".to_string());
+        }
+        if self.printer && self.link.is_some() {
+            return Err("--printer can't be used with a link cable".to_string());
+        }
+        if self.printer && self.rom_path.is_none() {
+            return Err("--printer requires a ROM".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_number(option: &str, value: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", option, value))
+}
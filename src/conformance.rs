@@ -0,0 +1,417 @@
+//! Headless test-ROM conformance harness.
+//!
+//! Boots test ROMs without a frontend and judges them the way each suite
+//! reports its result:
+//! - Blargg: text sent through the serial port (or the $A000 memory protocol)
+//! - Mooneye: the Fibonacci register signature present when `LD B,B` runs
+//! - Screenshot: a hash of the screen after a number of frames, DMG shades in greys
+//!
+//! The ROMs to run and their expected results are listed in a manifest
+//! (see `tests/test_roms.txt`), which is also used to generate the README tables.
+
+use std::error::Error;
+use std::path::Path;
+
+use crate::emulator::cpu::CpuMode;
+use crate::emulator::model::Model;
+use crate::emulator::{CYCLES_PER_FRAME, Emulator};
+use crate::screenshot;
+
+/// Opcode of `LD B,B`, used by Mooneye tests as a software breakpoint.
+const LD_B_B: u8 = 0x40;
+/// B, C, D, E, H and L after a passing Mooneye test.
+const MOONEYE_PASS: [u8; 6] = [3, 5, 8, 13, 21, 34];
+/// B, C, D, E, H and L after a failing Mooneye test.
+const MOONEYE_FAIL: [u8; 6] = [0x42; 6];
+/// Signature written to $A001-$A003 by Blargg tests using the memory protocol.
+const BLARGG_SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+/// Value of $A000 while a Blargg test is still running.
+const BLARGG_RUNNING: u8 = 0x80;
+/// Greys of DMG reference screenshots, lightest first.
+const GREYS: [u32; 4] = [0xFFFFFFFF, 0xFFAAAAAA, 0xFF555555, 0xFF000000];
+/// Markers around the generated tables in the README.
+pub const README_BEGIN: &str = "<!-- conformance:begin -->";
+pub const README_END: &str = "<!-- conformance:end -->";
+
+/// How a test ROM reports its result.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Check {
+    Blargg,
+    Mooneye,
+    /// Framebuffer hash, either given directly or computed from a reference PNG
+    /// (relative to the ROM directory).
+    Screenshot(ScreenshotReference),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScreenshotReference {
+    Hash(u64),
+    Image(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail,
+    Unsupported,
+    /// Runs, but no result has been recorded yet.
+    Untested,
+}
+
+impl Status {
+    /// Symbol used for this status in the README tables.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Status::Pass => "✅",
+            Status::Fail => "❌",
+            Status::Unsupported => "⚠️",
+            Status::Untested => "❔",
+        }
+    }
+}
+
+/// A manifest entry.
+#[derive(Clone, Debug)]
+pub struct TestRom {
+    pub section: String,
+    pub name: String,
+    pub path: String,
+    pub check: Check,
+    pub frames: u32,
+    pub expected: Status,
+    pub note: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct TestResult {
+    pub status: Status,
+    /// Human-readable explanation (serial output, registers, hash...)
+    pub detail: String,
+}
+
+/// Parses a manifest.
+///
+/// Each non-empty line that doesn't start with `#` has the form
+/// `section | name | path | check | frames | expected | note`, where `check` is
+/// `blargg`, `mooneye`, `screenshot:<hex hash>` or `screenshot:<reference.png>`
+/// and `expected` is `pass`, `fail`, `unsupported` or `untested`.
+pub fn parse_manifest(manifest: &str) -> Result<Vec<TestRom>, String> {
+    let mut roms = Vec::new();
+
+    for (index, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+        if fields.len() != 7 {
+            return Err(format!(
+                "Line {}: expected 7 fields, found {}",
+                index + 1,
+                fields.len()
+            ));
+        }
+
+        let check = parse_check(fields[3]).map_err(|e| format!("Line {}: {}", index + 1, e))?;
+        let frames = fields[4]
+            .parse()
+            .map_err(|_| format!("Line {}: invalid frame count '{}'", index + 1, fields[4]))?;
+        let expected = match fields[5] {
+            "pass" => Status::Pass,
+            "fail" => Status::Fail,
+            "unsupported" => Status::Unsupported,
+            "untested" => Status::Untested,
+            other => return Err(format!("Line {}: invalid status '{}'", index + 1, other)),
+        };
+
+        roms.push(TestRom {
+            section: fields[0].to_string(),
+            name: fields[1].to_string(),
+            path: fields[2].to_string(),
+            check,
+            frames,
+            expected,
+            note: fields[6].to_string(),
+        });
+    }
+
+    Ok(roms)
+}
+
+fn parse_check(check: &str) -> Result<Check, String> {
+    match check.split_once(':') {
+        None if check == "blargg" => Ok(Check::Blargg),
+        None if check == "mooneye" => Ok(Check::Mooneye),
+        Some(("screenshot", reference)) if reference.ends_with(".png") => Ok(Check::Screenshot(
+            ScreenshotReference::Image(reference.to_string()),
+        )),
+        Some(("screenshot", hash)) => u64::from_str_radix(hash, 16)
+            .map(|hash| Check::Screenshot(ScreenshotReference::Hash(hash)))
+            .map_err(|_| format!("invalid screenshot hash '{}'", hash)),
+        _ => Err(format!("invalid check '{}'", check)),
+    }
+}
+
+/// Runs a single test ROM from `roms_dir`.
+///
+/// Entries expected to be unsupported are not run.
+pub fn run_test(rom: &TestRom, roms_dir: &Path) -> Result<TestResult, Box<dyn Error>> {
+    if rom.expected == Status::Unsupported {
+        return Ok(TestResult {
+            status: Status::Unsupported,
+            detail: String::new(),
+        });
+    }
+
+    let rom_path = roms_dir.join(&rom.path);
+    let rom_path = rom_path.to_str().ok_or("ROM path is not valid UTF-8")?;
+    let mut emulator = Emulator::new(rom_path, model_for_test(&rom.name))?;
+
+    match &rom.check {
+        Check::Blargg => Ok(run_blargg(&mut emulator, rom.frames)),
+        Check::Mooneye => Ok(run_mooneye(&mut emulator, rom.frames)),
+        Check::Screenshot(reference) => {
+            let expected_hash = match reference {
+                ScreenshotReference::Hash(hash) => *hash,
+                ScreenshotReference::Image(image) => {
+                    let (pixels, _, _) = screenshot::load_png(roms_dir.join(image))?;
+                    if emulator.cpu.bus.ppu.cgb_mode {
+                        framebuffer_hash(&pixels)
+                    } else {
+                        framebuffer_hash(&quantise_greys(&pixels))
+                    }
+                }
+            };
+            Ok(run_screenshot(&mut emulator, rom.frames, expected_hash))
+        }
+    }
+}
+
+/// Picks the model a test targets from its Mooneye-style name suffix
+/// (`boot_regs-mgb`, `unused_hwio-GS`...), or `None` to use the default.
+pub fn model_for_test(name: &str) -> Option<Model> {
+    let (_, suffix) = name.rsplit_once('-')?;
+    match suffix {
+        s if s.starts_with("dmg0") => Some(Model::Dmg0),
+        s if s.starts_with("dmg") || s.starts_with('G') => Some(Model::Dmg),
+        s if s.starts_with("mgb") => Some(Model::Mgb),
+        s if s.starts_with("sgb") || s.starts_with('S') => Some(Model::Sgb),
+        s if s.starts_with("cgb") || s.starts_with('C') => Some(Model::Cgb),
+        s if s.starts_with('A') => Some(Model::Agb),
+        _ => None,
+    }
+}
+
+/// Runs until the test prints "Passed" or "Failed" (or reports through memory).
+pub fn run_blargg(emulator: &mut Emulator, frames: u32) -> TestResult {
+    let mut output = String::new();
+
+    for _ in 0..frames {
+        emulator.run_frame();
+        output.extend(emulator.take_serial_output().into_iter().map(char::from));
+
+        if output.contains("Passed") {
+            return TestResult {
+                status: Status::Pass,
+                detail: output,
+            };
+        }
+        if output.contains("Failed") {
+            return TestResult {
+                status: Status::Fail,
+                detail: output,
+            };
+        }
+        if let Some(result) = blargg_memory_result(emulator) {
+            return result;
+        }
+    }
+
+    TestResult {
+        status: Status::Fail,
+        detail: format!("Timed out after {} frames: {}", frames, output),
+    }
+}
+
+/// Checks the $A000 memory protocol used by Blargg tests without serial output.
+fn blargg_memory_result(emulator: &Emulator) -> Option<TestResult> {
+    let bus = &emulator.cpu.bus;
+    let signature = [
+        bus.read_byte(0xA001),
+        bus.read_byte(0xA002),
+        bus.read_byte(0xA003),
+    ];
+    let code = bus.read_byte(0xA000);
+    if signature != BLARGG_SIGNATURE || code == BLARGG_RUNNING {
+        return None;
+    }
+
+    // Zero-terminated text follows the signature
+    let detail = (0xA004..0xC000)
+        .map(|address| bus.read_byte(address))
+        .take_while(|&byte| byte != 0)
+        .map(char::from)
+        .collect();
+
+    Some(TestResult {
+        status: if code == 0 {
+            Status::Pass
+        } else {
+            Status::Fail
+        },
+        detail,
+    })
+}
+
+/// Runs until `LD B,B` executes, then checks for the Fibonacci signature.
+pub fn run_mooneye(emulator: &mut Emulator, frames: u32) -> TestResult {
+    let max_cycles = frames as u64 * CYCLES_PER_FRAME;
+    let mut cycles = 0;
+
+    while cycles < max_cycles {
+        let cpu = &emulator.cpu;
+        let breakpoint = cpu.mode == CpuMode::Normal && cpu.bus.read_byte(cpu.reg.pc) == LD_B_B;
+
+        cycles += emulator.step();
+
+        if breakpoint {
+            let reg = &emulator.cpu.reg;
+            let registers = [reg.b, reg.c, reg.d, reg.e, reg.h, reg.l];
+            let detail = format!(
+                "B={} C={} D={} E={} H={} L={}",
+                reg.b, reg.c, reg.d, reg.e, reg.h, reg.l
+            );
+
+            if registers == MOONEYE_PASS {
+                return TestResult {
+                    status: Status::Pass,
+                    detail,
+                };
+            }
+            if registers == MOONEYE_FAIL {
+                return TestResult {
+                    status: Status::Fail,
+                    detail,
+                };
+            }
+        }
+    }
+
+    TestResult {
+        status: Status::Fail,
+        detail: format!("Timed out after {} frames", frames),
+    }
+}
+
+/// Runs for `frames` frames and compares the framebuffer hash.
+pub fn run_screenshot(emulator: &mut Emulator, frames: u32, expected_hash: u64) -> TestResult {
+    for _ in 0..frames {
+        emulator.run_frame();
+    }
+
+    let hash = framebuffer_hash(&screen_pixels(emulator));
+    TestResult {
+        status: if hash == expected_hash {
+            Status::Pass
+        } else {
+            Status::Fail
+        },
+        detail: format!("Framebuffer hash {:016x}", hash),
+    }
+}
+
+/// The screen as compared with reference screenshots: in CGB mode the colours
+/// shown, otherwise the DMG shades through `GREYS` whatever palette is selected.
+pub fn screen_pixels(emulator: &Emulator) -> Vec<u32> {
+    let ppu = &emulator.cpu.bus.ppu;
+    if ppu.cgb_mode {
+        ppu.buffer.clone()
+    } else {
+        ppu.shades()
+            .iter()
+            .map(|&shade| GREYS[shade as usize])
+            .collect()
+    }
+}
+
+/// Maps each pixel of a DMG reference screenshot to the nearest of `GREYS`.
+fn quantise_greys(pixels: &[u32]) -> Vec<u32> {
+    pixels
+        .iter()
+        .map(|&pixel| {
+            let [b, g, r, _] = pixel.to_le_bytes();
+            let level = (r as u32 + g as u32 + b as u32) / 3;
+            GREYS[3 - ((level * 3 + 127) / 255) as usize]
+        })
+        .collect()
+}
+
+/// Stable 64-bit FNV-1a hash of the RGB components of a framebuffer.
+pub fn framebuffer_hash(pixels: &[u32]) -> u64 {
+    const FNV_OFFSET: u64 = 0xCBF29CE484222325;
+    const FNV_PRIME: u64 = 0x100000001B3;
+
+    let mut hash = FNV_OFFSET;
+    for &pixel in pixels {
+        for byte in (pixel & 0x00FFFFFF).to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+/// Renders results as the Markdown tables used in the README, one per section.
+pub fn markdown_tables(results: &[(TestRom, Status)]) -> String {
+    let mut sections: Vec<&str> = Vec::new();
+    for (rom, _) in results {
+        if !sections.contains(&rom.section.as_str()) {
+            sections.push(&rom.section);
+        }
+    }
+
+    let mut markdown = String::new();
+    for section in sections {
+        let rows: Vec<&(TestRom, Status)> = results
+            .iter()
+            .filter(|(rom, _)| rom.section == section)
+            .collect();
+        let name_width = rows
+            .iter()
+            .map(|(rom, _)| rom.name.len())
+            .max()
+            .unwrap_or(0)
+            .max(8);
+        let note_width = rows
+            .iter()
+            .map(|(rom, _)| rom.note.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+
+        if !markdown.is_empty() {
+            markdown.push('\n');
+        }
+        markdown.push_str(&format!("### {}\n\n", section));
+        markdown.push_str(&format!(
+            "| {:name_width$} |    | {:note_width$} |\n",
+            "Test ROM", "Note"
+        ));
+        markdown.push_str(&format!(
+            "|{}|----|{}|\n",
+            "-".repeat(name_width + 2),
+            "-".repeat(note_width + 2)
+        ));
+        for (rom, status) in rows {
+            markdown.push_str(&format!(
+                "| {:name_width$} | {} | {:note_width$} |\n",
+                rom.name,
+                status.symbol(),
+                rom.note
+            ));
+        }
+    }
+
+    markdown
+}
@@ -1,4 +1,5 @@
 mod channel;
+mod resampler;
 mod utils;
 
 use crate::utils::bits::*;
@@ -11,6 +12,7 @@ mod pulse_phase_timer;
 mod sweep;
 
 use channel::{NoiseChannel, PulseChannel, WaveChannel};
+pub use resampler::Resampler;
 use serde::{Deserialize, Serialize};
 use utils::{
     apply_low_pass_filter, apply_volume_reduction, get_panned_output, mix_samples,
@@ -34,7 +36,7 @@ pub struct Apu {
 
 impl Apu {
     pub fn new() -> Self {
-        let mut apu = Self {
+        Self {
             enabled: false,
             frame_sequencer_step: 0,
             frame_sequencer_counter: 0,
@@ -46,25 +48,7 @@ impl Apu {
             channel_4: NoiseChannel::new(),
             last_sample: (0.0, 0.0),
             tick_divider: false,
-        };
-        
-        apu.write(0xFF10, 0x80);
-        apu.write(0xFF11, 0xBF);
-        apu.write(0xFF12, 0xF3);
-        apu.write(0xFF14, 0xBF);
-        apu.write(0xFF16, 0x3F);
-        apu.write(0xFF19, 0xBF);
-        apu.write(0xFF1A, 0x7F);
-        apu.write(0xFF1B, 0xFF);
-        apu.write(0xFF1C, 0x9F);
-        apu.write(0xFF1E, 0xBF);
-        apu.write(0xFF20, 0xFF);
-        apu.write(0xFF23, 0xBF);
-        apu.write(0xFF24, 0x77);
-        apu.write(0xFF25, 0xF3);
-        apu.write(0xFF26, 0xF1);
-        
-        apu
+        }
     }
 
     pub fn sample_stereo(&mut self) -> (f32, f32) {
@@ -94,12 +78,6 @@ impl Apu {
         self.last_sample
     }
 
-    pub fn sample_mono(&mut self) -> (f32, f32) {
-        let (left, right) = self.sample_stereo();
-        let mono = (left + right) / 2.0;
-        (mono, mono)
-    }
-
     fn generate_left_sample(
         &self,
         ch1_dac_out: f32,
@@ -237,7 +215,8 @@ impl Apu {
     }
 
     fn get_master_control(&self) -> u8 {
-        let mut value = 0;
+        // Bits 4-6 are unused and read as 1
+        let mut value = 0x70;
         if self.enabled {
             value |= BIT_7;
         }
@@ -0,0 +1,58 @@
+use crate::emulator::CPU_FREQUENCY;
+
+/// Converts the per-T-cycle APU output into samples at a host sample rate.
+///
+/// Samples are accumulated in an internal buffer until drained by the frontend.
+pub struct Resampler {
+    sample_rate: f32,
+    sample_accumulator: f32,
+    ticks_per_sample: f32,
+    prev_sample: (f32, f32),
+    current_sample: (f32, f32),
+    samples: Vec<(f32, f32)>,
+}
+
+impl Resampler {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            sample_accumulator: 0.0,
+            ticks_per_sample: CPU_FREQUENCY as f32 / sample_rate,
+            prev_sample: (0.0, 0.0),
+            current_sample: (0.0, 0.0),
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Feeds `t_cycles` worth of APU output, calling `get_sample` once per T-cycle.
+    pub fn process_cpu_cycles(&mut self, t_cycles: u32, mut get_sample: impl FnMut() -> (f32, f32)) {
+        for _ in 0..t_cycles {
+            self.prev_sample = self.current_sample;
+            self.current_sample = get_sample();
+
+            self.sample_accumulator += 1.0;
+
+            if self.sample_accumulator >= self.ticks_per_sample {
+                self.sample_accumulator -= self.ticks_per_sample;
+
+                // Calculate interpolation factor (0.0 to 1.0)
+                let t = self.sample_accumulator / self.ticks_per_sample;
+
+                // Linear interpolation: prev + t * (current - prev)
+                let interp_l = self.prev_sample.0 + t * (self.current_sample.0 - self.prev_sample.0);
+                let interp_r = self.prev_sample.1 + t * (self.current_sample.1 - self.prev_sample.1);
+
+                self.samples.push((interp_l, interp_r));
+            }
+        }
+    }
+
+    /// Takes every sample produced since the last call.
+    pub fn drain(&mut self) -> Vec<(f32, f32)> {
+        std::mem::take(&mut self.samples)
+    }
+}
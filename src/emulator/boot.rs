@@ -0,0 +1,191 @@
+//! Boot ROMs and the hardware state they leave behind.
+//!
+//! With a boot ROM the emulator starts from the power-on state and runs the
+//! real boot sequence (logo scroll included). Without one, the state the boot
+//! ROM would have left is applied directly from the tables below.
+
+mod compat;
+
+use std::error::Error;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::emulator::model::Model;
+
+pub use compat::{PaletteCombo, palettes_for_cart, palettes_for_title};
+
+/// Size of a DMG, MGB or SGB boot ROM.
+const DMG_BOOT_ROM_SIZE: usize = 0x100;
+/// Size of a CGB boot ROM, including the unmapped 0x0100-0x01FF gap.
+const CGB_BOOT_ROM_SIZE: usize = 0x900;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct BootRom {
+    data: Vec<u8>,
+}
+
+impl BootRom {
+    /// Loads a boot ROM dump, detecting its type from the size.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| format!("Failed to read boot ROM {}: {}", path.display(), e))?;
+        Self::from_bytes(data)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, Box<dyn Error>> {
+        match data.len() {
+            DMG_BOOT_ROM_SIZE | CGB_BOOT_ROM_SIZE => Ok(Self { data }),
+            size => Err(format!(
+                "Invalid boot ROM size: {} bytes (expected {} or {})",
+                size, DMG_BOOT_ROM_SIZE, CGB_BOOT_ROM_SIZE
+            )
+            .into()),
+        }
+    }
+
+    pub fn is_cgb(&self) -> bool {
+        self.data.len() == CGB_BOOT_ROM_SIZE
+    }
+
+    /// Returns the byte at `address` if the boot ROM covers it.
+    ///
+    /// The CGB boot ROM leaves 0x0100-0x01FF mapped to the cartridge header.
+    pub fn read(&self, address: u16) -> Option<u8> {
+        match address {
+            0x0100..=0x01FF => None,
+            _ => self.data.get(address as usize).copied(),
+        }
+    }
+}
+
+/// CPU and I/O register values at the moment the boot ROM hands over to the cartridge.
+pub struct PostBootState {
+    pub af: u16,
+    pub bc: u16,
+    pub de: u16,
+    pub hl: u16,
+    /// Internal 16-bit divider counter (DIV is the upper byte)
+    pub div: u16,
+    /// I/O registers written in order through the memory bus
+    pub io: &'static [(u16, u8)],
+}
+
+/// Registers the boot ROMs leave set up. The APU is enabled first so the sound
+/// registers aren't ignored; NR14 is written last as the logo sound triggers channel 1.
+const POST_BOOT_IO: &[(u16, u8)] = &[
+    (0xFF26, 0x80), // NR52
+    (0xFF10, 0x80), // NR10
+    (0xFF11, 0xBF), // NR11
+    (0xFF12, 0xF3), // NR12
+    (0xFF13, 0xFF), // NR13
+    (0xFF16, 0x3F), // NR21
+    (0xFF19, 0xBF), // NR24
+    (0xFF1A, 0x7F), // NR30
+    (0xFF1B, 0xFF), // NR31
+    (0xFF1C, 0x9F), // NR32
+    (0xFF1E, 0xBF), // NR34
+    (0xFF20, 0xFF), // NR41
+    (0xFF23, 0xBF), // NR44
+    (0xFF24, 0x77), // NR50
+    (0xFF25, 0xF3), // NR51
+    (0xFF40, 0x91), // LCDC
+    (0xFF47, 0xFC), // BGP
+    (0xFF0F, 0xE1), // IF (V-Blank pending)
+    (0xFF14, 0xBF), // NR14 (channel 1 trigger)
+];
+
+/// The SGB boot ROM plays no sound, leaving channel 1 off.
+const SGB_POST_BOOT_IO: &[(u16, u8)] = POST_BOOT_IO.split_last().unwrap().1;
+
+const DMG0_POST_BOOT: PostBootState = PostBootState {
+    af: 0x0100,
+    bc: 0xFF13,
+    de: 0x00C1,
+    hl: 0x8403,
+    div: 0x1830,
+    io: POST_BOOT_IO,
+};
+
+const DMG_POST_BOOT: PostBootState = PostBootState {
+    af: 0x01B0,
+    bc: 0x0013,
+    de: 0x00D8,
+    hl: 0x014D,
+    div: 0xABCC,
+    io: POST_BOOT_IO,
+};
+
+const MGB_POST_BOOT: PostBootState = PostBootState {
+    af: 0xFFB0,
+    ..DMG_POST_BOOT
+};
+
+const SGB_POST_BOOT: PostBootState = PostBootState {
+    af: 0x0100,
+    bc: 0x0014,
+    de: 0x0000,
+    hl: 0xC060,
+    div: 0xD85C,
+    io: SGB_POST_BOOT_IO,
+};
+
+const CGB_POST_BOOT: PostBootState = PostBootState {
+    af: 0x1180,
+    bc: 0x0000,
+    de: 0xFF56,
+    hl: 0x000D,
+    div: 0x1EA0,
+    io: POST_BOOT_IO,
+};
+
+/// CGB running a DMG cartridge; setting up the compatibility palettes takes longer.
+const CGB_DMG_POST_BOOT: PostBootState = PostBootState {
+    de: 0x0008,
+    hl: 0x007C,
+    div: 0x267C,
+    ..CGB_POST_BOOT
+};
+
+/// The AGB boot ROM ends with `INC B`, which games use to detect a Game Boy Advance.
+const AGB_POST_BOOT: PostBootState = PostBootState {
+    af: 0x1100,
+    bc: 0x0100,
+    ..CGB_POST_BOOT
+};
+
+const AGB_DMG_POST_BOOT: PostBootState = PostBootState {
+    af: 0x1100,
+    bc: 0x0100,
+    ..CGB_DMG_POST_BOOT
+};
+
+/// Returns the post-boot state of `model`, running in CGB mode or not.
+pub fn post_boot_state(model: Model, cgb_mode: bool) -> &'static PostBootState {
+    match (model, cgb_mode) {
+        (Model::Dmg0, _) => &DMG0_POST_BOOT,
+        (Model::Dmg, _) => &DMG_POST_BOOT,
+        (Model::Mgb, _) => &MGB_POST_BOOT,
+        (Model::Sgb, _) => &SGB_POST_BOOT,
+        (Model::Cgb, true) => &CGB_POST_BOOT,
+        (Model::Cgb, false) => &CGB_DMG_POST_BOOT,
+        (Model::Agb, true) => &AGB_POST_BOOT,
+        (Model::Agb, false) => &AGB_DMG_POST_BOOT,
+    }
+}
+
+/// RGB555 colours a CGB uses for DMG cartridges, indexed by BGP/OBP0/OBP1 shade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompatPalettes {
+    pub bg: [u16; 4],
+    pub obj0: [u16; 4],
+    pub obj1: [u16; 4],
+}
+
+/// Palettes the CGB boot ROM picks for DMG cartridges it doesn't recognise.
+pub const DEFAULT_COMPAT_PALETTES: CompatPalettes = CompatPalettes {
+    bg: [0x7FFF, 0x1BEF, 0x6180, 0x0000],
+    obj0: [0x7FFF, 0x421F, 0x1CF2, 0x0000],
+    obj1: [0x7FFF, 0x421F, 0x1CF2, 0x0000],
+};
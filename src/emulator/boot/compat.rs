@@ -0,0 +1,305 @@
+//! Colourisation of DMG cartridges, as done by the CGB boot ROM.
+//!
+//! Nintendo titles are identified by a checksum of the header title (plus its
+//! 4th letter when checksums collide) and get a hand-picked combination of BG,
+//! OBJ0 and OBJ1 palettes. Holding a direction (and A or B) during the boot
+//! logo overrides the choice.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::CompatPalettes;
+use crate::emulator::cart::Cart;
+
+/// Palettes of the boot ROM, as RGB555.
+const PALETTES: [[u16; 4]; 30] = [
+    [0x7FFF, 0x32BF, 0x00D0, 0x0000],
+    [0x639F, 0x4279, 0x15B0, 0x04CB],
+    [0x7FFF, 0x6E31, 0x454A, 0x0000],
+    [0x7FFF, 0x1BEF, 0x0200, 0x0000],
+    [0x7FFF, 0x421F, 0x1CF2, 0x0000],
+    [0x7FFF, 0x5294, 0x294A, 0x0000],
+    [0x7FFF, 0x03FF, 0x012F, 0x0000],
+    [0x7FFF, 0x03EF, 0x01D6, 0x0000],
+    [0x7FFF, 0x42B5, 0x3DC8, 0x0000],
+    [0x7E74, 0x03FF, 0x0180, 0x0000],
+    [0x67FF, 0x77AC, 0x1A13, 0x2D6B],
+    [0x7ED6, 0x4BFF, 0x2175, 0x0000],
+    [0x53FF, 0x4A5F, 0x7E52, 0x0000],
+    [0x4FFF, 0x7ED2, 0x3A4C, 0x1CE0],
+    [0x03ED, 0x7FFF, 0x255F, 0x0000],
+    [0x036A, 0x021F, 0x03FF, 0x7FFF],
+    [0x7FFF, 0x01DF, 0x0112, 0x0000],
+    [0x231F, 0x035F, 0x00F2, 0x0009],
+    [0x7FFF, 0x03EA, 0x011F, 0x0000],
+    [0x299F, 0x001A, 0x000C, 0x0000],
+    [0x7FFF, 0x027F, 0x001F, 0x0000],
+    [0x7FFF, 0x03E0, 0x0206, 0x0120],
+    [0x7FFF, 0x7EEB, 0x001F, 0x7C00],
+    [0x7FFF, 0x3FFF, 0x7E00, 0x001F],
+    [0x7FFF, 0x03FF, 0x001F, 0x0000],
+    [0x03FF, 0x001F, 0x000C, 0x0000],
+    [0x7FFF, 0x033F, 0x0193, 0x0000],
+    [0x0000, 0x4200, 0x037F, 0x7FFF],
+    [0x7FFF, 0x7E8C, 0x7C00, 0x0000],
+    [0x7FFF, 0x1BEF, 0x6180, 0x0000],
+];
+
+/// OBJ0, OBJ1 and BG palettes of a combination, from `PALETTES`.
+const fn combination(obj0: usize, obj1: usize, bg: usize) -> [usize; 3] {
+    [obj0 * 4, obj1 * 4, bg * 4]
+}
+
+/// Palette combinations as offsets of their first colour in `PALETTES`.
+///
+/// A few start one colour early, overlapping the end of the previous palette,
+/// the same way the boot ROM reads them.
+const COMBINATIONS: [[usize; 3]; 51] = [
+    combination(4, 4, 29),
+    combination(18, 18, 18),
+    combination(20, 20, 20),
+    combination(24, 24, 24),
+    combination(9, 9, 9),
+    combination(0, 0, 0),
+    combination(27, 27, 27),
+    combination(5, 5, 5),
+    combination(12, 12, 12),
+    combination(26, 26, 26),
+    combination(16, 8, 8),
+    combination(4, 28, 28),
+    combination(4, 2, 2),
+    combination(3, 4, 4),
+    combination(4, 29, 29),
+    combination(28, 4, 28),
+    combination(2, 17, 2),
+    combination(16, 16, 8),
+    combination(4, 4, 7),
+    combination(4, 4, 18),
+    combination(4, 4, 20),
+    combination(19, 19, 9),
+    [4 * 4 - 1, 4 * 4 - 1, 11 * 4],
+    combination(17, 17, 2),
+    combination(4, 4, 2),
+    combination(4, 4, 3),
+    combination(28, 28, 0),
+    combination(3, 3, 0),
+    combination(0, 0, 1),
+    combination(18, 22, 18),
+    combination(20, 22, 20),
+    combination(24, 22, 24),
+    combination(16, 22, 8),
+    combination(17, 4, 13),
+    [28 * 4 - 1, 0, 14 * 4],
+    [28 * 4 - 1, 4 * 4, 15 * 4],
+    [19 * 4, 23 * 4 - 1, 9 * 4],
+    combination(16, 28, 10),
+    combination(4, 23, 28),
+    combination(17, 22, 2),
+    combination(4, 0, 2),
+    combination(4, 28, 3),
+    combination(28, 3, 0),
+    combination(3, 28, 4),
+    combination(21, 28, 4),
+    combination(3, 28, 0),
+    combination(25, 3, 28),
+    combination(0, 28, 8),
+    combination(4, 3, 28),
+    combination(28, 3, 6),
+    combination(4, 28, 29),
+];
+
+/// Title checksums of the recognised games.
+const TITLE_CHECKSUMS: [u8; 64] = [
+    0x88, 0x16, 0x36, 0xD1, 0xDB, 0xF2, 0x3C, 0x8C, 0x92, 0x3D, 0x5C, 0x58, 0xC9, 0x3E, 0x70, 0x1D,
+    0x59, 0x69, 0x19, 0x35, 0xA8, 0x14, 0xAA, 0x75, 0x95, 0x99, 0x34, 0x6F, 0x15, 0xFF, 0x97, 0x4B,
+    0x90, 0x17, 0x10, 0x39, 0xF7, 0xF6, 0xA2, 0x49, 0x4E, 0x43, 0x68, 0xE0, 0x8B, 0xF0, 0xCE, 0x0C,
+    0x29, 0xE8, 0xB7, 0x86, 0x9A, 0x52, 0x01, 0x9D, 0x71, 0x9C, 0xBD, 0x5D, 0x6D, 0x67, 0x3F, 0x6B,
+];
+
+/// Combination used by each game in `TITLE_CHECKSUMS`.
+const TITLE_COMBINATIONS: [u8; 64] = [
+    4, 5, 35, 34, 3, 31, 15, 10, 5, 19, 36, 7, 37, 30, 44, 21, 32, 31, 20, 5, 33, 13, 14, 5, 29, 5,
+    18, 9, 3, 2, 26, 25, 25, 41, 42, 26, 45, 42, 45, 36, 38, 26, 42, 30, 41, 34, 34, 5, 42, 6, 5,
+    33, 25, 42, 42, 40, 14, 16, 25, 42, 42, 5, 0, 39,
+];
+
+/// Title checksums shared by several games, told apart by the 4th letter of the title.
+const SHARED_CHECKSUMS: [(u8, u8); 29] = [
+    (0xB3, b'B'),
+    (0x46, b'E'),
+    (0x28, b'F'),
+    (0xA5, b'A'),
+    (0xC6, b'A'),
+    (0xD3, b'R'),
+    (0x27, b'B'),
+    (0x61, b'E'),
+    (0x18, b'K'),
+    (0x66, b'E'),
+    (0x6A, b'K'),
+    (0xBF, b' '),
+    (0x0D, b'R'),
+    (0xF4, b'-'),
+    (0xB3, b'U'),
+    (0x46, b'R'),
+    (0x28, b'A'),
+    (0xA5, b'R'),
+    (0xC6, b' '),
+    (0xD3, b'I'),
+    (0x27, b'N'),
+    (0x61, b'A'),
+    (0x18, b'I'),
+    (0x66, b'L'),
+    (0x6A, b'I'),
+    (0xBF, b'C'),
+    (0x0D, b'E'),
+    (0xF4, b' '),
+    (0xB3, b'R'),
+];
+
+/// Combination used by each game in `SHARED_CHECKSUMS`.
+const SHARED_COMBINATIONS: [u8; 29] = [
+    36, 22, 25, 6, 32, 12, 36, 11, 39, 18, 39, 24, 31, 50, 17, 46, 6, 27, 0, 47, 41, 41, 0, 0, 19,
+    34, 23, 18, 29,
+];
+
+/// Button combinations held during the CGB boot logo to pick the palettes of a DMG game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteCombo {
+    Up,
+    UpA,
+    UpB,
+    Left,
+    LeftA,
+    LeftB,
+    Down,
+    DownA,
+    DownB,
+    Right,
+    RightA,
+    RightB,
+}
+
+impl PaletteCombo {
+    pub const ALL: [PaletteCombo; 12] = [
+        PaletteCombo::Up,
+        PaletteCombo::UpA,
+        PaletteCombo::UpB,
+        PaletteCombo::Left,
+        PaletteCombo::LeftA,
+        PaletteCombo::LeftB,
+        PaletteCombo::Down,
+        PaletteCombo::DownA,
+        PaletteCombo::DownB,
+        PaletteCombo::Right,
+        PaletteCombo::RightA,
+        PaletteCombo::RightB,
+    ];
+
+    /// Buttons to hold, as accepted by `--cgb-palette`.
+    pub fn name(self) -> &'static str {
+        match self {
+            PaletteCombo::Up => "Up",
+            PaletteCombo::UpA => "Up+A",
+            PaletteCombo::UpB => "Up+B",
+            PaletteCombo::Left => "Left",
+            PaletteCombo::LeftA => "Left+A",
+            PaletteCombo::LeftB => "Left+B",
+            PaletteCombo::Down => "Down",
+            PaletteCombo::DownA => "Down+A",
+            PaletteCombo::DownB => "Down+B",
+            PaletteCombo::Right => "Right",
+            PaletteCombo::RightA => "Right+A",
+            PaletteCombo::RightB => "Right+B",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            PaletteCombo::Up => "Brown",
+            PaletteCombo::UpA => "Red",
+            PaletteCombo::UpB => "Dark Brown",
+            PaletteCombo::Left => "Blue",
+            PaletteCombo::LeftA => "Dark Blue",
+            PaletteCombo::LeftB => "Grayscale",
+            PaletteCombo::Down => "Pale Yellow",
+            PaletteCombo::DownA => "Orange",
+            PaletteCombo::DownB => "Yellow",
+            PaletteCombo::Right => "Green",
+            PaletteCombo::RightA => "Dark Green",
+            PaletteCombo::RightB => "Inverted",
+        }
+    }
+
+    pub fn palettes(self) -> CompatPalettes {
+        let index = match self {
+            PaletteCombo::Up => 5,
+            PaletteCombo::UpA => 43,
+            PaletteCombo::UpB => 28,
+            PaletteCombo::Left => 48,
+            PaletteCombo::LeftA => 40,
+            PaletteCombo::LeftB => 7,
+            PaletteCombo::Down => 8,
+            PaletteCombo::DownA => 3,
+            PaletteCombo::DownB => 49,
+            PaletteCombo::Right => 1,
+            PaletteCombo::RightA => 0,
+            PaletteCombo::RightB => 6,
+        };
+        combination_palettes(index)
+    }
+}
+
+impl fmt::Display for PaletteCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name(), self.description())
+    }
+}
+
+impl FromStr for PaletteCombo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PaletteCombo::ALL
+            .into_iter()
+            .find(|combo| combo.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown palette combination: {}", s))
+    }
+}
+
+/// Palettes the CGB boot ROM picks for a DMG cartridge.
+pub fn palettes_for_cart(cart: &Cart) -> CompatPalettes {
+    if cart.is_nintendo_licensee() {
+        palettes_for_title(&cart.title_bytes())
+    } else {
+        combination_palettes(0)
+    }
+}
+
+/// Palettes for a Nintendo game with the raw header `title` (0x0134-0x0143).
+pub fn palettes_for_title(title: &[u8; 16]) -> CompatPalettes {
+    let checksum = title.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+    let unique = TITLE_CHECKSUMS
+        .iter()
+        .position(|&game| game == checksum)
+        .map(|i| TITLE_COMBINATIONS[i]);
+    let shared = || {
+        SHARED_CHECKSUMS
+            .iter()
+            .position(|&game| game == (checksum, title[3]))
+            .map(|i| SHARED_COMBINATIONS[i])
+    };
+
+    let index = unique.or_else(shared).unwrap_or(0);
+    combination_palettes(index as usize)
+}
+
+fn combination_palettes(index: usize) -> CompatPalettes {
+    let colors = PALETTES.as_flattened();
+    let palette = |offset: usize| -> [u16; 4] { colors[offset..offset + 4].try_into().unwrap() };
+    let [obj0, obj1, bg] = COMBINATIONS[index];
+    CompatPalettes {
+        bg: palette(bg),
+        obj0: palette(obj0),
+        obj1: palette(obj1),
+    }
+}
@@ -16,9 +16,13 @@ use std::path::Path;
 
 trait Mbc {
     fn read_rom(&self, rom_data: &[u8], address: u16) -> u8;
+    /// ROM bank mapped at `address` (0x0000-0x7FFF).
+    fn rom_bank(&self, address: u16) -> usize;
     fn write_rom(&mut self, address: u16, value: u8);
     fn read_ram(&self, ram_data: &[u8], address: u16) -> u8;
     fn write_ram(&mut self, ram_data: &mut [u8], address: u16, value: u8);
+    /// Returns the registers to their power-on state, keeping the RTC and EEPROM contents.
+    fn reset(&mut self);
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
@@ -57,6 +61,17 @@ impl MbcType {
         }
     }
 
+    pub fn rom_bank(&self, address: u16) -> usize {
+        match self {
+            MbcType::Mbc0(mbc) => mbc.rom_bank(address),
+            MbcType::Mbc1(mbc) => mbc.rom_bank(address),
+            MbcType::Mbc2(mbc) => mbc.rom_bank(address),
+            MbcType::Mbc3(mbc) => mbc.rom_bank(address),
+            MbcType::Mbc5(mbc) => mbc.rom_bank(address),
+            MbcType::Mbc7(mbc) => mbc.rom_bank(address),
+        }
+    }
+
     pub fn write_rom(&mut self, address: u16, value: u8) {
         match self {
             MbcType::Mbc0(mbc) => mbc.write_rom(address, value),
@@ -90,6 +105,17 @@ impl MbcType {
         }
     }
 
+    pub fn reset(&mut self) {
+        match self {
+            MbcType::Mbc0(mbc) => mbc.reset(),
+            MbcType::Mbc1(mbc) => mbc.reset(),
+            MbcType::Mbc2(mbc) => mbc.reset(),
+            MbcType::Mbc3(mbc) => mbc.reset(),
+            MbcType::Mbc5(mbc) => mbc.reset(),
+            MbcType::Mbc7(mbc) => mbc.reset(),
+        }
+    }
+
     pub fn name(code: u8) -> &'static str {
         match code {
             0x00 => "ROM ONLY",
@@ -14,6 +14,10 @@ impl Mbc for Mbc0 {
         }
     }
 
+    fn rom_bank(&self, address: u16) -> usize {
+        (address >= 0x4000) as usize
+    }
+
     fn write_rom(&mut self, _address: u16, _value: u8) {
         // No-op for ROM-only cartridges
     }
@@ -26,4 +30,6 @@ impl Mbc for Mbc0 {
     fn write_ram(&mut self, _ram_data: &mut [u8], _address: u16, _value: u8) {
         // ROM-only cartridges do not have RAM, do nothing
     }
+
+    fn reset(&mut self) {}
 }
@@ -53,6 +53,14 @@ impl Mbc for Mbc1 {
         }
     }
 
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF if self.banking_mode == 0 => 0,
+            0x0000..=0x3FFF => self.rom_bank as usize & 0x60,
+            _ => self.rom_bank as usize % self.rom_banks_number.max(1),
+        }
+    }
+
     fn write_rom(&mut self, address: u16, value: u8) {
         match address {
             0x0000..=0x1FFF => self.ram_enabled = (value & 0x0F) == 0x0A,
@@ -133,4 +141,8 @@ impl Mbc for Mbc1 {
             ram_data[real_address] = value;
         }
     }
+
+    fn reset(&mut self) {
+        *self = Mbc1::new(self.rom_banks_number);
+    }
 }
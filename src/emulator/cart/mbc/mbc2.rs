@@ -31,6 +31,13 @@ impl Mbc for Mbc2 {
         }
     }
 
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF => 0,
+            _ => (self.rom_bank_select as usize) % self.rom_bank_count,
+        }
+    }
+
     fn write_rom(&mut self, address: u16, value: u8) {
         match address {
             0x0000..=0x3FFF => {
@@ -59,4 +66,8 @@ impl Mbc for Mbc2 {
             ram_data[ram_address] = value & 0x0F;
         }
     }
+
+    fn reset(&mut self) {
+        *self = Mbc2::new(self.rom_bank_count);
+    }
 }
@@ -158,6 +158,14 @@ impl Mbc for Mbc3 {
         }
     }
 
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF if self.banking_mode == 0 => 0,
+            0x0000..=0x3FFF => self.rom_bank as usize & 0x60,
+            _ => self.rom_bank as usize % self.rom_banks_number.max(1),
+        }
+    }
+
     fn write_rom(&mut self, address: u16, value: u8) {
         match address {
             0x0000..=0x1FFF => self.ram_rtc_enabled = (value & 0x0F) == 0x0A,
@@ -271,6 +279,15 @@ impl Mbc for Mbc3 {
             }
         }
     }
+
+    fn reset(&mut self) {
+        // The RTC keeps counting across resets
+        self.rom_bank = 1;
+        self.ram_rtc_selection = 0;
+        self.ram_rtc_enabled = false;
+        self.banking_mode = 0;
+        self.rtc_latch = 0xFF;
+    }
 }
 
 fn system_time_to_u64_millis(time: SystemTime) -> u64 {
@@ -36,6 +36,13 @@ impl super::Mbc for Mbc5 {
         }
     }
 
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize % self.rom_banks,
+        }
+    }
+
     fn write_rom(&mut self, address: u16, value: u8) {
         match address {
             // 0x0000-0x1FFF: RAM Enable
@@ -85,4 +92,8 @@ impl super::Mbc for Mbc5 {
             ram_data[offset] = value;
         }
     }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.rom_banks, self.ram_banks);
+    }
 }
@@ -331,6 +331,13 @@ impl Mbc for Mbc7 {
         }
     }
 
+    fn rom_bank(&self, address: u16) -> usize {
+        match address {
+            0x0000..=0x3FFF => 0,
+            _ => (self.rom_bank as usize) % self.rom_banks.max(1),
+        }
+    }
+
     fn write_rom(&mut self, address: u16, value: u8) {
         match address {
             // 0x0000-0x1FFF: RAM Enable 1
@@ -438,4 +445,16 @@ impl Mbc for Mbc7 {
             }
         }
     }
+
+    fn reset(&mut self) {
+        // Keep the EEPROM contents, but abort any command in progress
+        let data = std::mem::take(&mut self.eeprom.data);
+        self.eeprom = Eeprom { data, ..Eeprom::new() };
+        self.rom_bank = 1;
+        self.ram_enabled_1 = false;
+        self.ram_enabled_2 = false;
+        self.accel_x = 0x8000;
+        self.accel_y = 0x8000;
+        self.accel_latched = false;
+    }
 }
@@ -20,7 +20,7 @@ struct Header {
     // _logo: [u8; 0x30],
     title: [u8; 16],
     new_lic_code: u16,
-    _sgb_flag: u8,
+    sgb_flag: u8,
     cart_type: u8,
     rom_size: u8,
     ram_size: u8,
@@ -108,16 +108,66 @@ impl Cart {
         MbcType::has_battery(self.header.cart_type)
     }
 
+    /// Puts the mapper back in its power-on state. RAM, RTC and EEPROM contents are kept.
+    pub fn reset(&mut self) {
+        self.mbc_type.reset();
+    }
+
     /// Reload ROM data from a file (used after deserializing save states)
     pub fn reload_rom<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Box<dyn Error>> {
         self.rom_data = fs::read(path)?;
         Ok(())
     }
 
+    /// Title from the header, without the CGB flag some cartridges store in its last byte.
+    pub fn title(&self) -> String {
+        let title: String = self
+            .header
+            .title
+            .iter()
+            .take_while(|&&byte| byte != 0 && byte.is_ascii())
+            .map(|&byte| byte as char)
+            .collect();
+        title.trim_end().to_string()
+    }
+
+    /// Raw title area (0x0134-0x0143), including the CGB flag in its last byte.
+    pub fn title_bytes(&self) -> [u8; 16] {
+        self.header.title
+    }
+
+    /// Whether Nintendo is the licensee, in the old or the new (ASCII "01") licensee code.
+    pub fn is_nintendo_licensee(&self) -> bool {
+        match self.header.lic_code {
+            0x01 => true,
+            0x33 => self.header.new_lic_code == u16::from_be_bytes(*b"01"),
+            _ => false,
+        }
+    }
+
+    /// Header checksum stored at 0x014D.
+    pub fn header_checksum(&self) -> u8 {
+        self.header.checksum
+    }
+
+    /// Global checksum stored at 0x014E-0x014F (not verified by the hardware).
+    pub fn global_checksum(&self) -> u16 {
+        match self.rom_data.get(0x14E..=0x14F) {
+            Some(&[high, low]) => u16::from_be_bytes([high, low]),
+            _ => 0,
+        }
+    }
+
     pub fn is_cgb(&self) -> bool {
         self.header.cgb_flag == 0x80 || self.header.cgb_flag == 0xC0
     }
 
+    /// Whether the cartridge enables SGB functions, which also requires the old licensee
+    /// code to be 0x33.
+    pub fn supports_sgb(&self) -> bool {
+        self.header.sgb_flag == 0x03 && self.header.lic_code == 0x33
+    }
+
     pub fn is_cgb_only(&self) -> bool {
         self.header.cgb_flag == 0xC0
     }
@@ -153,6 +203,11 @@ impl Cart {
         self.mbc_type.read_rom(&self.rom_data, address)
     }
 
+    /// ROM bank currently mapped at `address` (0x0000-0x7FFF).
+    pub fn rom_bank(&self, address: u16) -> usize {
+        self.mbc_type.rom_bank(address)
+    }
+
     /// Write to ROM area (triggers MBC operations)
     pub fn write_rom(&mut self, address: u16, value: u8) {
         self.mbc_type.write_rom(address, value);
@@ -184,7 +239,7 @@ impl Header {
             // _logo: rom[0x104..=0x133].try_into().unwrap(),
             title: rom[0x134..=0x143].try_into().unwrap(),
             new_lic_code: ((rom[0x144] as u16) << 8) | rom[0x145] as u16,
-            _sgb_flag: rom[0x146],
+            sgb_flag: rom[0x146],
             cart_type: rom[0x147],
             rom_size: rom[0x148],
             ram_size: rom[0x149],
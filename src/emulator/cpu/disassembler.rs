@@ -0,0 +1,86 @@
+//! Disassembly of SM83 machine code, for the debugger and offline tooling.
+
+use super::instructions::{OpcodeInfo, Operands};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisassembledInstruction {
+    pub address: u16,
+    /// Opcode, prefix and immediate bytes
+    pub bytes: Vec<u8>,
+    /// Assembly with operands resolved, e.g. `JR NZ,$C012`
+    pub text: String,
+}
+
+impl DisassembledInstruction {
+    pub fn size(&self) -> u16 {
+        self.bytes.len() as u16
+    }
+}
+
+/// Decodes the instruction at `address`, reading memory through `read`.
+///
+/// Bytes that aren't a valid opcode are shown as `DB $XX`.
+pub fn disassemble(read: impl Fn(u16) -> u8, address: u16) -> DisassembledInstruction {
+    let opcode = read(address);
+    let prefixed = opcode == 0xCB;
+    let (info, opcode_size) = if prefixed {
+        (OpcodeInfo::from_byte(read(address.wrapping_add(1)), true), 2)
+    } else {
+        (OpcodeInfo::from_byte(opcode, false), 1)
+    };
+
+    let Some(info) = info else {
+        return DisassembledInstruction {
+            address,
+            bytes: vec![opcode],
+            text: format!("DB ${:02X}", opcode),
+        };
+    };
+
+    let size = opcode_size + info.instruction.immediate_size() as u16;
+    let bytes: Vec<u8> = (0..size).map(|i| read(address.wrapping_add(i))).collect();
+    let immediate = bytes[opcode_size as usize..]
+        .iter()
+        .rev()
+        .fold(0, |value, &byte| (value << 8) | byte as u16);
+    let operands = Operands {
+        immediate,
+        next_pc: address.wrapping_add(size),
+    };
+
+    DisassembledInstruction {
+        address,
+        text: info.instruction.resolve(operands).to_string(),
+        bytes,
+    }
+}
+
+/// Disassembles `code` as if it was loaded at `base`.
+///
+/// A trailing instruction cut short by the end of `code` is shown as `DB` bytes.
+pub fn disassemble_bytes(code: &[u8], base: u16) -> Vec<DisassembledInstruction> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+
+    while offset < code.len() {
+        let address = base.wrapping_add(offset as u16);
+        let read = |a: u16| {
+            let index = a.wrapping_sub(base) as usize;
+            code.get(index).copied().unwrap_or(0)
+        };
+        let mut instruction = disassemble(read, address);
+
+        if offset + instruction.bytes.len() > code.len() {
+            let opcode = code[offset];
+            instruction = DisassembledInstruction {
+                address,
+                bytes: vec![opcode],
+                text: format!("DB ${:02X}", opcode),
+            };
+        }
+        offset += instruction.bytes.len();
+        instructions.push(instruction);
+    }
+
+    instructions
+}
@@ -64,4 +64,258 @@ pub enum Instruction {
     INC(IncDecSource),
     DEC(IncDecSource),
     LD(LoadType),
-}
\ No newline at end of file
+}
+
+/// Immediate operand of an instruction, used to resolve it in the disassembly.
+#[derive(Debug, Clone, Copy)]
+pub struct Operands {
+    /// The bytes following the opcode, little endian
+    pub immediate: u16,
+    /// Address of the next instruction, the base of relative jumps
+    pub next_pc: u16,
+}
+
+impl Instruction {
+    /// Number of immediate bytes following the opcode (and the CB prefix).
+    pub fn immediate_size(&self) -> u8 {
+        match self {
+            I::STOP | I::JR | I::JRIF(_) | I::ADDSP => 1,
+            I::JP(_) | I::CALL(_) => 2,
+            I::ADD(source)
+            | I::ADC(source)
+            | I::SUB(source)
+            | I::SBC(source)
+            | I::AND(source)
+            | I::XOR(source)
+            | I::OR(source)
+            | I::CP(source) => matches!(source, AS8::D8) as u8,
+            I::LD(load) => match load {
+                LT::Byte(_, LBS::D8) => 1,
+                LT::Byte(..) => 0,
+                LT::Word(_, LWS::D16) | LT::Word(LWT::A16, _) => 2,
+                LT::Word(LWT::HL, LWS::SP) => 1,
+                LT::Word(..) => 0,
+                LT::AFromIndirect(LI::A8) | LT::IndirectFromA(LI::A8) => 1,
+                LT::AFromIndirect(LI::A16) | LT::IndirectFromA(LI::A16) => 2,
+                LT::AFromIndirect(_) | LT::IndirectFromA(_) => 0,
+            },
+            _ => 0,
+        }
+    }
+
+    /// Formats the instruction with its immediate operand resolved, e.g. `JR NZ,$C012`.
+    ///
+    /// `Display` shows placeholders (`d8`, `a16`, `r8`...) instead.
+    pub fn resolve(&self, operands: Operands) -> impl std::fmt::Display + '_ {
+        Asm {
+            instruction: self,
+            operands: Some(operands),
+        }
+    }
+
+    fn write_asm(&self, f: &mut std::fmt::Formatter<'_>, operands: Option<Operands>) -> std::fmt::Result {
+        let d8 = || match operands {
+            Some(operands) => format!("${:02X}", operands.immediate as u8),
+            None => "d8".to_string(),
+        };
+        let d16 = || match operands {
+            Some(operands) => format!("${:04X}", operands.immediate),
+            None => "d16".to_string(),
+        };
+        // Signed offset, as in `ADD SP,-$02`
+        let r8 = |prefix: &str| match operands {
+            Some(operands) if (operands.immediate as u8 as i8) < 0 => {
+                format!("-${:02X}", (operands.immediate as u8 as i8).unsigned_abs())
+            }
+            Some(operands) => format!("{}${:02X}", prefix, operands.immediate as u8),
+            None => format!("{}r8", prefix),
+        };
+        let jr_target = || match operands {
+            Some(operands) => {
+                let offset = operands.immediate as u8 as i8 as i16;
+                format!("${:04X}", operands.next_pc.wrapping_add_signed(offset))
+            }
+            None => "r8".to_string(),
+        };
+        let indirect = |source: &LI| match source {
+            LI::BC => "(BC)".to_string(),
+            LI::DE => "(DE)".to_string(),
+            LI::HL => "(HL)".to_string(),
+            LI::HLinc => "(HL+)".to_string(),
+            LI::HLdec => "(HL-)".to_string(),
+            LI::C => "(C)".to_string(),
+            LI::A8 => match operands {
+                Some(operands) => format!("($FF{:02X})", operands.immediate as u8),
+                None => "(a8)".to_string(),
+            },
+            LI::A16 => match operands {
+                Some(operands) => format!("(${:04X})", operands.immediate),
+                None => "(a16)".to_string(),
+            },
+        };
+        let source8 = |source: &AS8| match source {
+            AS8::D8 => d8(),
+            source => format!("{}", Reg8(source)),
+        };
+
+        match self {
+            I::NOP => write!(f, "NOP"),
+            I::STOP => write!(f, "STOP"),
+            I::HALT => write!(f, "HALT"),
+            I::DI => write!(f, "DI"),
+            I::EI => write!(f, "EI"),
+            I::JP(None) => write!(f, "JP {}", d16()),
+            I::JP(Some(condition)) => write!(f, "JP {},{}", Cond(condition), d16()),
+            I::JPHL => write!(f, "JP HL"),
+            I::JR => write!(f, "JR {}", jr_target()),
+            I::JRIF(condition) => write!(f, "JR {},{}", Cond(condition), jr_target()),
+            I::CALL(None) => write!(f, "CALL {}", d16()),
+            I::CALL(Some(condition)) => write!(f, "CALL {},{}", Cond(condition), d16()),
+            I::RET(None) => write!(f, "RET"),
+            I::RET(Some(condition)) => write!(f, "RET {}", Cond(condition)),
+            I::RETI => write!(f, "RETI"),
+            I::RST(vector) => write!(f, "RST ${:02X}", vector),
+            I::ADD(source) => write!(f, "ADD A,{}", source8(source)),
+            I::ADC(source) => write!(f, "ADC A,{}", source8(source)),
+            I::SUB(source) => write!(f, "SUB {}", source8(source)),
+            I::SBC(source) => write!(f, "SBC A,{}", source8(source)),
+            I::AND(source) => write!(f, "AND {}", source8(source)),
+            I::XOR(source) => write!(f, "XOR {}", source8(source)),
+            I::OR(source) => write!(f, "OR {}", source8(source)),
+            I::CP(source) => write!(f, "CP {}", source8(source)),
+            I::DAA => write!(f, "DAA"),
+            I::SCF => write!(f, "SCF"),
+            I::CPL => write!(f, "CPL"),
+            I::CCF => write!(f, "CCF"),
+            I::ADDHL(source) => {
+                let source = match source {
+                    AS16::BC => "BC",
+                    AS16::DE => "DE",
+                    AS16::HL => "HL",
+                    AS16::SP => "SP",
+                };
+                write!(f, "ADD HL,{}", source)
+            }
+            I::ADDSP => write!(f, "ADD SP,{}", r8("")),
+            I::POP(operand) => write!(f, "POP {:?}", operand),
+            I::PUSH(operand) => write!(f, "PUSH {:?}", operand),
+            I::RLCA => write!(f, "RLCA"),
+            I::RLA => write!(f, "RLA"),
+            I::RRCA => write!(f, "RRCA"),
+            I::RRA => write!(f, "RRA"),
+            I::BIT(bit, target) => write!(f, "BIT {},{}", bit, Reg8(target)),
+            I::RES(bit, target) => write!(f, "RES {},{}", bit, Reg8(target)),
+            I::SET(bit, target) => write!(f, "SET {},{}", bit, Reg8(target)),
+            I::RLC(target) => write!(f, "RLC {}", Reg8(target)),
+            I::RRC(target) => write!(f, "RRC {}", Reg8(target)),
+            I::RL(target) => write!(f, "RL {}", Reg8(target)),
+            I::RR(target) => write!(f, "RR {}", Reg8(target)),
+            I::SLA(target) => write!(f, "SLA {}", Reg8(target)),
+            I::SRA(target) => write!(f, "SRA {}", Reg8(target)),
+            I::SRL(target) => write!(f, "SRL {}", Reg8(target)),
+            I::SWAP(target) => write!(f, "SWAP {}", Reg8(target)),
+            I::INC(target) => write!(f, "INC {}", IncDec(target)),
+            I::DEC(target) => write!(f, "DEC {}", IncDec(target)),
+            I::LD(load) => match load {
+                LT::Byte(target, LBS::D8) => write!(f, "LD {},{}", ByteTarget(target), d8()),
+                LT::Byte(target, source) => {
+                    write!(f, "LD {},{}", ByteTarget(target), ByteSource(source))
+                }
+                LT::Word(LWT::HL, LWS::SP) => write!(f, "LD HL,SP{}", r8("+")),
+                LT::Word(LWT::A16, source) => {
+                    let address = match operands {
+                        Some(operands) => format!("(${:04X})", operands.immediate),
+                        None => "(a16)".to_string(),
+                    };
+                    write!(f, "LD {},{:?}", address, source)
+                }
+                LT::Word(target, LWS::D16) => write!(f, "LD {:?},{}", target, d16()),
+                LT::Word(target, source) => write!(f, "LD {:?},{:?}", target, source),
+                LT::AFromIndirect(source @ (LI::A8 | LI::C)) => {
+                    write!(f, "LDH A,{}", indirect(source))
+                }
+                LT::AFromIndirect(source) => write!(f, "LD A,{}", indirect(source)),
+                LT::IndirectFromA(target @ (LI::A8 | LI::C)) => {
+                    write!(f, "LDH {},A", indirect(target))
+                }
+                LT::IndirectFromA(target) => write!(f, "LD {},A", indirect(target)),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_asm(f, None)
+    }
+}
+
+struct Asm<'a> {
+    instruction: &'a Instruction,
+    operands: Option<Operands>,
+}
+
+impl std::fmt::Display for Asm<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.instruction.write_asm(f, self.operands)
+    }
+}
+
+struct Reg8<'a>(&'a AS8);
+
+impl std::fmt::Display for Reg8<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            AS8::HLI => write!(f, "(HL)"),
+            AS8::D8 => write!(f, "d8"),
+            register => write!(f, "{:?}", register),
+        }
+    }
+}
+
+struct ByteTarget<'a>(&'a LBT);
+
+impl std::fmt::Display for ByteTarget<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            LBT::HLI => write!(f, "(HL)"),
+            register => write!(f, "{:?}", register),
+        }
+    }
+}
+
+struct ByteSource<'a>(&'a LBS);
+
+impl std::fmt::Display for ByteSource<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            LBS::HLI => write!(f, "(HL)"),
+            LBS::D8 => write!(f, "d8"),
+            register => write!(f, "{:?}", register),
+        }
+    }
+}
+
+struct IncDec<'a>(&'a IDS);
+
+impl std::fmt::Display for IncDec<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            IDS::HLI => write!(f, "(HL)"),
+            register => write!(f, "{:?}", register),
+        }
+    }
+}
+
+struct Cond<'a>(&'a FC);
+
+impl std::fmt::Display for Cond<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            FC::Zero => write!(f, "Z"),
+            FC::NotZero => write!(f, "NZ"),
+            FC::Carry => write!(f, "C"),
+            FC::NotCarry => write!(f, "NC"),
+        }
+    }
+}
@@ -1,17 +1,24 @@
 mod dma;
 mod hdma;
+mod region;
 
 use crate::emulator::apu::Apu;
+use crate::emulator::boot::{self, BootRom, PaletteCombo, PostBootState};
 use crate::emulator::cart::Cart;
+use crate::emulator::debugger::{Access, Debugger};
 use crate::emulator::joypad::Joypad;
+use crate::emulator::model::Model;
 use crate::emulator::ppu::*;
 use crate::emulator::serial::Serial;
+use crate::emulator::sgb::Sgb;
 use crate::emulator::timer::Timer;
 use crate::utils::bits::*;
 use dma::Dma;
 use hdma::Hdma;
 use serde::{Deserialize, Serialize};
 
+pub use region::MemoryRegion;
+
 const HRAM_SIZE: usize = 0x7F;
 const WRAM_SIZE: usize = 0x8000;
 
@@ -31,6 +38,16 @@ pub struct MemoryBus {
     pub timer: Timer,
     pub joypad: Joypad,
     pub serial: Serial,
+    /// Present when an SGB runs a cartridge with SGB support. Save states store
+    /// it after the CPU, see `save_state`.
+    #[serde(skip)]
+    pub sgb: Option<Sgb>,
+    #[serde(skip)]
+    pub debugger: Debugger,
+    model: Model,
+    /// Mapped over the cartridge ROM until FF50 is written
+    boot_rom: Option<BootRom>,
+    cgb_mode_after_boot: bool,
     #[serde(with = "serde_arrays")]
     hram: [u8; HRAM_SIZE],
     #[serde(with = "serde_arrays")]
@@ -44,10 +61,13 @@ pub struct MemoryBus {
 }
 
 impl MemoryBus {
-    pub fn new(cart: Cart, force_dmg: bool) -> Self {
-        let cgb_mode = cart.is_cgb() && !force_dmg;
+    /// Creates the bus of a `model` console. A `boot_rom` must match the model.
+    pub fn new(cart: Cart, model: Model, boot_rom: Option<BootRom>) -> Self {
+        let cgb_mode = model.is_cgb() && cart.is_cgb();
+        // The CGB boot ROM itself always runs in CGB mode
         let mut ppu = PPU::new();
-        ppu.set_cgb_mode(cgb_mode);
+        ppu.set_cgb_mode(if boot_rom.is_some() { model.is_cgb() } else { cgb_mode });
+        let sgb = (model == Model::Sgb && cart.supports_sgb()).then(Sgb::new);
 
         Self {
             cart,
@@ -55,7 +75,12 @@ impl MemoryBus {
             apu: Apu::new(),
             timer: Timer::new(),
             joypad: Joypad::new(),
-            serial: Serial::new(true),
+            serial: Serial::new(),
+            sgb,
+            debugger: Debugger::default(),
+            model,
+            boot_rom,
+            cgb_mode_after_boot: cgb_mode,
             hram: [0; HRAM_SIZE],
             wram: [0; WRAM_SIZE],
             wram_bank: 1,
@@ -69,8 +94,21 @@ impl MemoryBus {
 
     /// Returns a byte from the `address`.
     pub fn read_byte(&self, address: u16) -> u8 {
+        let value = self.peek_byte(address);
+        if self.debugger.is_watching() {
+            self.debugger.check_access(address, Access::Read, value);
+        }
+        value
+    }
+
+    /// Returns a byte from the `address` without triggering watchpoints.
+    pub fn peek_byte(&self, address: u16) -> u8 {
         let address_usize = address as usize;
         match address {
+            0x0000..=0x08FF if self.boot_rom.is_some() => {
+                let boot_rom = self.boot_rom.as_ref().unwrap();
+                boot_rom.read(address).unwrap_or_else(|| self.cart.read_rom(address))
+            }
             0x0000..=0x7FFF => self.cart.read_rom(address),
             0x8000..=0x9FFF => self.ppu.read_vram(address),
             0xA000..=0xBFFF => self.cart.read_ram(address),
@@ -93,7 +131,10 @@ impl MemoryBus {
             }
             0xFEA0..=0xFEFF => 0x00, // Unused OAM area
             0xFF00..=0xFF7F => match address {
-                0xFF00 => self.joypad.read_p1(),
+                0xFF00 => match &self.sgb {
+                    Some(sgb) => sgb.read_p1(self.joypad.read_p1()),
+                    None => self.joypad.read_p1(),
+                },
                 0xFF01 => self.serial.read_sb(),
                 0xFF02 => self.serial.read_sc(),
                 0xFF04..=0xFF07 => self.timer.read(address),
@@ -135,6 +176,9 @@ impl MemoryBus {
 
     /// Writes a byte of `value` to the `address`.
     pub fn write_byte(&mut self, address: u16, value: u8) {
+        if self.debugger.is_watching() {
+            self.debugger.check_access(address, Access::Write, value);
+        }
         let address_usize = address as usize;
         match address {
             0x0000..=0x7FFF => self.cart.write_rom(address, value),
@@ -155,7 +199,12 @@ impl MemoryBus {
             }
             0xFEA0..=0xFEFF => {} // Unused OAM area
             0xFF00..=0xFF7F => match address {
-                0xFF00 => self.joypad.write_p1(value),
+                0xFF00 => {
+                    self.joypad.write_p1(value);
+                    if let Some(sgb) = &mut self.sgb {
+                        sgb.write_p1(value);
+                    }
+                }
                 0xFF01 => self.serial.write_sb(value),
                 0xFF02 => self.serial.write_sc(value),
                 0xFF04..=0xFF07 => self.timer.write(address, value, &mut self.interrupt_flag),
@@ -209,6 +258,7 @@ impl MemoryBus {
                     }
                 }
                 0xFF4F => self.ppu.write_vram_bank(value),
+                0xFF50 if value != 0 => self.unmap_boot_rom(),
                 0xFF51..=0xFF55 => {
                     self.hdma.write(address, value);
                     // If General Purpose DMA (bit 7 = 0), transfer immediately
@@ -235,6 +285,64 @@ impl MemoryBus {
         };
     }
 
+    /// Bank mapped at `address`, for the ROM and switchable WRAM areas.
+    pub fn bank_at(&self, address: u16) -> Option<usize> {
+        match address {
+            0x0000..=0x7FFF => Some(self.cart.rom_bank(address)),
+            0xD000..=0xDFFF => Some(self.wram_bank),
+            _ => None,
+        }
+    }
+
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    /// Writes the I/O registers and DIV the boot ROM would have left behind.
+    pub fn apply_post_boot_state(&mut self, state: &PostBootState) {
+        for &(address, value) in state.io {
+            self.write_byte(address, value);
+        }
+        self.timer.div = state.div;
+
+        if self.model.is_cgb() && !self.ppu.cgb_mode {
+            self.set_compat_palette(None);
+            self.ppu.dmg_compat = true;
+        }
+    }
+
+    /// Loads the palettes a DMG cartridge is coloured with on CGB hardware: the ones
+    /// for `combo` if given, otherwise those the boot ROM picks for the cartridge.
+    pub fn set_compat_palette(&mut self, combo: Option<PaletteCombo>) {
+        let palettes = match combo {
+            Some(combo) => combo.palettes(),
+            None => boot::palettes_for_cart(&self.cart),
+        };
+        self.ppu.set_compat_palettes(&palettes);
+    }
+
+    /// Reinitializes the frame buffers after deserialization, as they aren't saved.
+    pub fn reinit_buffers(&mut self) {
+        self.ppu.reinit_buffers();
+        if let Some(sgb) = &mut self.sgb {
+            sgb.reinit_buffers();
+        }
+    }
+
+    /// Whether the boot ROM is still mapped over the cartridge ROM.
+    pub fn is_boot_rom_mapped(&self) -> bool {
+        self.boot_rom.is_some()
+    }
+
+    /// Hands the address space over to the cartridge once the boot ROM is done.
+    fn unmap_boot_rom(&mut self) {
+        if self.boot_rom.take().is_some() && self.ppu.cgb_mode != self.cgb_mode_after_boot {
+            // The CGB boot ROM has loaded the compatibility palettes for a DMG cartridge
+            self.ppu.cgb_mode = false;
+            self.ppu.dmg_compat = true;
+        }
+    }
+
     pub fn tick(&mut self) {
         let previous_mode = self.ppu.mode;
 
@@ -251,6 +359,13 @@ impl MemoryBus {
             self.request_interrupt(InterruptBit::Serial);
         }
 
+        if previous_mode != PPUMode::VBlank
+            && self.ppu.mode == PPUMode::VBlank
+            && let Some(sgb) = &mut self.sgb
+        {
+            sgb.end_frame(&self.ppu);
+        }
+
         // Trigger HBlank DMA if we just entered HBlank
         if previous_mode != PPUMode::HBlank && self.ppu.mode == PPUMode::HBlank {
             self.hdma_hblank_transfer();
@@ -261,7 +376,7 @@ impl MemoryBus {
     pub fn dma_tick(&mut self) {
         if let Some((source, destination)) = self.dma.tick() {
             // Perform the DMA transfer
-            let value = self.read_byte(source);
+            let value = self.peek_byte(source);
             self.ppu.write_oam(destination, value);
         }
     }
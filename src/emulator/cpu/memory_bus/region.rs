@@ -0,0 +1,114 @@
+//! Direct views of the memory areas behind the bus, bypassing banking.
+
+use std::fmt;
+
+use super::{HRAM_SIZE, MemoryBus};
+
+const ROM_BANK_SIZE: usize = 0x4000;
+const SRAM_BANK_SIZE: usize = 0x2000;
+const WRAM_BANK_SIZE: usize = 0x1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryRegion {
+    /// The 64 KiB address space, as the CPU sees it
+    Bus,
+    Rom(usize),
+    Sram(usize),
+    Wram(usize),
+    Vram(usize),
+    Oam,
+    Hram,
+}
+
+impl MemoryRegion {
+    /// Address the first byte of the region is mapped at.
+    pub fn base_address(self) -> u16 {
+        match self {
+            MemoryRegion::Bus | MemoryRegion::Rom(0) => 0x0000,
+            MemoryRegion::Rom(_) => 0x4000,
+            MemoryRegion::Vram(_) => 0x8000,
+            MemoryRegion::Sram(_) => 0xA000,
+            MemoryRegion::Wram(0) => 0xC000,
+            MemoryRegion::Wram(_) => 0xD000,
+            MemoryRegion::Oam => 0xFE00,
+            MemoryRegion::Hram => 0xFF80,
+        }
+    }
+}
+
+impl fmt::Display for MemoryRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryRegion::Bus => write!(f, "Bus"),
+            MemoryRegion::Rom(bank) => write!(f, "ROM {:02X}", bank),
+            MemoryRegion::Sram(bank) => write!(f, "SRAM {:02X}", bank),
+            MemoryRegion::Wram(bank) => write!(f, "WRAM {}", bank),
+            MemoryRegion::Vram(bank) => write!(f, "VRAM {}", bank),
+            MemoryRegion::Oam => write!(f, "OAM"),
+            MemoryRegion::Hram => write!(f, "HRAM"),
+        }
+    }
+}
+
+impl MemoryBus {
+    /// Every region of this console and cartridge.
+    pub fn memory_regions(&self) -> Vec<MemoryRegion> {
+        let rom_banks = self.cart.rom_data.len().div_ceil(ROM_BANK_SIZE);
+        let sram_banks = self.cart.ram_data.len().div_ceil(SRAM_BANK_SIZE);
+        let (wram_banks, vram_banks) = if self.ppu.cgb_mode { (8, 2) } else { (2, 1) };
+
+        let mut regions = vec![MemoryRegion::Bus];
+        regions.extend((0..rom_banks).map(MemoryRegion::Rom));
+        regions.extend((0..sram_banks).map(MemoryRegion::Sram));
+        regions.extend((0..wram_banks).map(MemoryRegion::Wram));
+        regions.extend((0..vram_banks).map(MemoryRegion::Vram));
+        regions.extend([MemoryRegion::Oam, MemoryRegion::Hram]);
+        regions
+    }
+
+    /// Size of `region` in bytes.
+    pub fn region_len(&self, region: MemoryRegion) -> usize {
+        match region {
+            MemoryRegion::Bus => 0x10000,
+            MemoryRegion::Rom(bank) => banked_len(self.cart.rom_data.len(), bank, ROM_BANK_SIZE),
+            MemoryRegion::Sram(bank) => banked_len(self.cart.ram_data.len(), bank, SRAM_BANK_SIZE),
+            MemoryRegion::Wram(bank) => banked_len(self.wram.len(), bank, WRAM_BANK_SIZE),
+            MemoryRegion::Vram(bank) => self.ppu.vram_bank(bank).len(),
+            MemoryRegion::Oam => self.ppu.oam().len(),
+            MemoryRegion::Hram => HRAM_SIZE,
+        }
+    }
+
+    /// Reads the byte at `offset` in `region`, without side effects or watchpoints.
+    pub fn read_region(&self, region: MemoryRegion, offset: usize) -> u8 {
+        match region {
+            MemoryRegion::Bus => self.peek_byte(offset as u16),
+            MemoryRegion::Rom(bank) => self.cart.rom_data[bank * ROM_BANK_SIZE + offset],
+            MemoryRegion::Sram(bank) => self.cart.ram_data[bank * SRAM_BANK_SIZE + offset],
+            MemoryRegion::Wram(bank) => self.wram[bank * WRAM_BANK_SIZE + offset],
+            MemoryRegion::Vram(bank) => self.ppu.vram_bank(bank)[offset],
+            MemoryRegion::Oam => self.ppu.oam()[offset],
+            MemoryRegion::Hram => self.hram[offset],
+        }
+    }
+
+    /// Writes the byte at `offset` in `region`.
+    ///
+    /// Bus writes behave like CPU writes, so they also reach MBC and I/O registers.
+    pub fn write_region(&mut self, region: MemoryRegion, offset: usize, value: u8) {
+        match region {
+            MemoryRegion::Bus => self.write_byte(offset as u16, value),
+            MemoryRegion::Rom(bank) => self.cart.rom_data[bank * ROM_BANK_SIZE + offset] = value,
+            MemoryRegion::Sram(bank) => self.cart.ram_data[bank * SRAM_BANK_SIZE + offset] = value,
+            MemoryRegion::Wram(bank) => self.wram[bank * WRAM_BANK_SIZE + offset] = value,
+            MemoryRegion::Vram(bank) => self.ppu.vram_bank_mut(bank)[offset] = value,
+            MemoryRegion::Oam => self.ppu.oam_mut()[offset] = value,
+            MemoryRegion::Hram => self.hram[offset] = value,
+        }
+    }
+}
+
+/// Length of `bank` in a banked area of `total` bytes; the last bank may be partial.
+fn banked_len(total: usize, bank: usize, bank_size: usize) -> usize {
+    total.saturating_sub(bank * bank_size).min(bank_size)
+}
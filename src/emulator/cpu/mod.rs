@@ -1,10 +1,12 @@
 mod control_unit;
+pub mod disassembler;
 mod instructions;
 pub mod memory_bus;
 mod registers;
 
-use crate::PRINT_STATE;
+use crate::emulator::boot::{self, BootRom, PostBootState};
 use crate::emulator::cart::Cart;
+use crate::emulator::model::Model;
 use instructions::*;
 use memory_bus::*;
 use registers::*;
@@ -30,21 +32,42 @@ pub struct CPU {
 }
 
 impl CPU {
-    pub fn new(cart: Cart, force_dmg: bool) -> Self {
-        let is_cgb = cart.is_cgb() && !force_dmg;
+    /// Creates a CPU that starts executing the `boot_rom`, or the cartridge
+    /// directly (in the post-boot state of `model`) if there is none.
+    pub fn new(cart: Cart, model: Model, boot_rom: Option<BootRom>) -> Self {
+        let has_boot_rom = boot_rom.is_some();
+        let mut cpu = Self {
+            reg: Registers::new(),
+            bus: MemoryBus::new(cart, model, boot_rom),
+            ime: false,
+            mode: CpuMode::Normal,
+            total_cycles: 0,
+        };
 
+        let is_cgb = cpu.bus.ppu.cgb_mode;
         println!(
-            "Initializing CPU in {} mode",
-            if is_cgb { "CGB" } else { "DMG" }
+            "Initializing {} CPU in {} mode{}",
+            model,
+            if is_cgb { "CGB" } else { "DMG" },
+            if has_boot_rom { " with boot ROM" } else { "" }
         );
 
-        Self {
-            reg: Registers::new_with_mode(is_cgb),
-            bus: MemoryBus::new(cart, force_dmg),
-            ime: false,
-            mode: CpuMode::Normal,
-            total_cycles: 0,
+        if !has_boot_rom {
+            cpu.apply_post_boot_state(boot::post_boot_state(model, is_cgb));
         }
+
+        cpu
+    }
+
+    /// Puts the CPU and I/O registers in the state the boot ROM would leave them in.
+    fn apply_post_boot_state(&mut self, state: &PostBootState) {
+        self.reg.set_af(state.af);
+        self.reg.set_bc(state.bc);
+        self.reg.set_de(state.de);
+        self.reg.set_hl(state.hl);
+        self.reg.sp = 0xFFFE;
+        self.reg.pc = 0x0100;
+        self.bus.apply_post_boot_state(state);
     }
 
     /// Emulates a CPU step. Returns the number of cycles taken.
@@ -54,6 +77,16 @@ impl CPU {
         let cycles_before = self.total_cycles;
         let mut pending: u8 = 0;
 
+        // Stop before the instruction on breakpoints and execute watchpoints
+        if matches!(self.mode, Normal | HaltBug | EnableIME) && self.bus.debugger.is_active() {
+            let pc = self.reg.pc;
+            let bank = self.bus.bank_at(pc);
+            let opcode = self.bus.peek_byte(pc);
+            if self.bus.debugger.check_execute(pc, bank, opcode) {
+                return 0;
+            }
+        }
+
         // Handle delayed EI: enable interrupts after the instruction following EI
         match self.mode {
             Normal => {
@@ -103,7 +136,6 @@ impl CPU {
             self.execute_interrupts(pending);
         }
 
-        self.print_state();
         self.total_cycles - cycles_before
     }
 
@@ -130,23 +162,21 @@ impl CPU {
         self.total_cycles += 4;
     }
 
+    /// Prints the registers and the bytes at PC, in the Gameboy Doctor log format.
     pub fn print_state(&self) {
-        if !PRINT_STATE {
-            return;
-        }
         println!(
             "{} PCMEM:{:02X},{:02X},{:02X},{:02X}",
             self.reg,
-            self.bus.read_byte(self.reg.pc),
-            self.bus.read_byte(self.reg.pc.wrapping_add(1)),
-            self.bus.read_byte(self.reg.pc.wrapping_add(2)),
-            self.bus.read_byte(self.reg.pc.wrapping_add(3))
+            self.bus.peek_byte(self.reg.pc),
+            self.bus.peek_byte(self.reg.pc.wrapping_add(1)),
+            self.bus.peek_byte(self.reg.pc.wrapping_add(2)),
+            self.bus.peek_byte(self.reg.pc.wrapping_add(3))
         );
     }
 
     /// Returns the currently pending interrupts (IF & IE).
     fn get_pending_interrupts(&self) -> u8 {
-        self.bus.read_byte(0xFF0F) & self.bus.read_byte(0xFFFF)
+        self.bus.peek_byte(0xFF0F) & self.bus.peek_byte(0xFFFF)
     }
 
     fn execute_interrupts(&mut self, pending: u8) {
@@ -167,7 +197,7 @@ impl CPU {
 
                     // If the interrupt is not enabled, cancel it's dispatch
                     if self.reg.sp == 0xFFFF {
-                        if self.bus.read_byte(0xFFFF) & mask == 0 {
+                        if self.bus.peek_byte(0xFFFF) & mask == 0 {
                             self.reg.pc = 0x0000;
                             continue;
                         }
@@ -178,7 +208,7 @@ impl CPU {
                     self.write_byte(self.reg.sp, (self.reg.pc & 0xFF) as u8);
 
                     // Clear the interrupt flag
-                    let int_f = self.bus.read_byte(0xFF0F);
+                    let int_f = self.bus.peek_byte(0xFF0F);
                     self.bus.write_byte(0xFF0F, int_f & !mask);
 
                     // Jump to the interrupt vector
@@ -216,29 +246,26 @@ impl CPU {
         self.bus.write_byte(address, value);
     }
 
-    fn read_word(&mut self, address: u16) -> u16 {
-        let a = self.read_byte(address) as u16;
-        let b = self.read_byte(address + 1) as u16;
-        (b << 8) | a
-    }
-
     fn write_word(&mut self, address: u16, value: u16) {
         self.write_byte(address, (value & 0xFF) as u8);
         self.write_byte(address + 1, (value >> 8) as u8);
     }
 
     /// Returns the byte pointed by PC and increments PC.
+    ///
+    /// Instruction fetches don't trigger read watchpoints.
     fn read_byte_at_pc(&mut self) -> u8 {
-        let result = self.read_byte(self.reg.pc);
+        self.tick4();
+        let result = self.bus.peek_byte(self.reg.pc);
         self.reg.pc = self.reg.pc.wrapping_add(1);
         result
     }
 
     /// Returns the next 2 bytes.
     fn read_word_at_pc(&mut self) -> u16 {
-        let result = self.read_word(self.reg.pc);
-        self.reg.pc = self.reg.pc.wrapping_add(2);
-        result
+        let low = self.read_byte_at_pc() as u16;
+        let high = self.read_byte_at_pc() as u16;
+        (high << 8) | low
     }
 
     /// Returns the byte pointed by the `HL` register
@@ -0,0 +1,263 @@
+//! Breakpoints and watchpoints.
+//!
+//! The debugger lives in the `MemoryBus`, which checks watchpoints on every CPU
+//! access. `CPU::step` checks breakpoints and execute watchpoints before each
+//! instruction and doesn't run it on a hit. The first hit is kept until
+//! `resume` so the frontend can pause and show it.
+
+use std::cell::Cell;
+use std::fmt;
+use std::str::FromStr;
+
+/// Stops before the instruction at `address` is executed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Breakpoint {
+    /// Only break when this bank is mapped at `address`
+    pub bank: Option<usize>,
+    pub address: u16,
+    pub enabled: bool,
+}
+
+impl Breakpoint {
+    /// Whether this breakpoint is on `address` while `bank` is mapped there.
+    pub fn is_at(&self, address: u16, bank: Option<usize>) -> bool {
+        self.address == address && (self.bank.is_none() || self.bank == bank)
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bank {
+            Some(bank) => write!(f, "{:02X}:{:04X}", bank, self.address),
+            None => write!(f, "{:04X}", self.address),
+        }
+    }
+}
+
+impl FromStr for Breakpoint {
+    type Err = String;
+
+    /// Parses `4A10`, `$4A10` or a bank-qualified `03:4A10` (all hexadecimal).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (bank, address) = match s.trim().split_once(':') {
+            Some((bank, address)) => (Some(parse_hex(bank)? as usize), address),
+            None => (None, s),
+        };
+        Ok(Self {
+            bank,
+            address: parse_hex(address)?,
+            enabled: true,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Access::Read => write!(f, "read"),
+            Access::Write => write!(f, "write"),
+            Access::Execute => write!(f, "execute"),
+        }
+    }
+}
+
+/// Stops on accesses to the addresses `start..=end`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub start: u16,
+    pub end: u16,
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+    pub enabled: bool,
+}
+
+impl Watchpoint {
+    fn matches(&self, address: u16, access: Access) -> bool {
+        let watched = match access {
+            Access::Read => self.read,
+            Access::Write => self.write,
+            Access::Execute => self.execute,
+        };
+        self.enabled && watched && (self.start..=self.end).contains(&address)
+    }
+}
+
+impl fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{:04X}", self.start)
+        } else {
+            write!(f, "{:04X}-{:04X}", self.start, self.end)
+        }
+    }
+}
+
+impl FromStr for Watchpoint {
+    type Err = String;
+
+    /// Parses an address or range (`C000`, `C000-C0FF`), watching every kind of access.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = match s.trim().split_once('-') {
+            Some((start, end)) => (parse_hex(start)?, parse_hex(end)?),
+            None => (parse_hex(s)?, parse_hex(s)?),
+        };
+        if start > end {
+            return Err(format!("Invalid range: {}", s.trim()));
+        }
+        Ok(Self {
+            start,
+            end,
+            read: true,
+            write: true,
+            execute: true,
+            enabled: true,
+        })
+    }
+}
+
+/// Why the debugger stopped emulation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugHit {
+    Breakpoint {
+        index: usize,
+        pc: u16,
+    },
+    Watchpoint {
+        index: usize,
+        address: u16,
+        access: Access,
+        /// Value read or written, or the opcode for execute accesses
+        value: u8,
+        /// Address of the instruction that made the access
+        pc: u16,
+    },
+}
+
+impl fmt::Display for DebugHit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DebugHit::Breakpoint { pc, .. } => write!(f, "Breakpoint at {:04X}", pc),
+            DebugHit::Watchpoint {
+                address,
+                access,
+                value,
+                pc,
+                ..
+            } => write!(
+                f,
+                "Watchpoint: {} {:02X} at {:04X} (PC {:04X})",
+                access, value, address, pc
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Debugger {
+    pub breakpoints: Vec<Breakpoint>,
+    pub watchpoints: Vec<Watchpoint>,
+    hit: Cell<Option<DebugHit>>,
+    /// Address of the instruction being executed, for watchpoint hits
+    pc: u16,
+    /// Execute this address once without breaking, to continue past a hit
+    skip_pc: Option<u16>,
+}
+
+impl Debugger {
+    /// The hit that stopped emulation, if any.
+    pub fn hit(&self) -> Option<DebugHit> {
+        self.hit.get()
+    }
+
+    /// Clears the hit so emulation can continue past it.
+    pub fn resume(&mut self) {
+        self.skip_pc = match self.hit.take() {
+            Some(DebugHit::Breakpoint { pc, .. }) => Some(pc),
+            Some(DebugHit::Watchpoint {
+                access: Access::Execute,
+                pc,
+                ..
+            }) => Some(pc),
+            _ => None,
+        };
+    }
+
+    /// Removes the breakpoints on `address` in `bank`, or adds one if there are none.
+    pub fn toggle_breakpoint(&mut self, address: u16, bank: Option<usize>) {
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|breakpoint| !breakpoint.is_at(address, bank));
+        if self.breakpoints.len() == count {
+            self.breakpoints.push(Breakpoint {
+                bank,
+                address,
+                enabled: true,
+            });
+        }
+    }
+
+    /// Checks breakpoints and execute watchpoints before running the instruction at `pc`.
+    ///
+    /// Returns true if the instruction must not be executed.
+    pub(crate) fn check_execute(&mut self, pc: u16, bank: Option<usize>, opcode: u8) -> bool {
+        self.pc = pc;
+        if self.skip_pc.take() == Some(pc) || self.hit.get().is_some() {
+            return false;
+        }
+
+        let breakpoint = self
+            .breakpoints
+            .iter()
+            .position(|breakpoint| breakpoint.enabled && breakpoint.is_at(pc, bank));
+        if let Some(index) = breakpoint {
+            self.hit.set(Some(DebugHit::Breakpoint { index, pc }));
+            return true;
+        }
+
+        self.check_access(pc, Access::Execute, opcode);
+        self.hit.get().is_some()
+    }
+
+    /// Checks the watchpoints for an access to `address`.
+    pub(crate) fn check_access(&self, address: u16, access: Access, value: u8) {
+        if self.hit.get().is_some() {
+            return;
+        }
+        let watchpoint = self
+            .watchpoints
+            .iter()
+            .position(|watchpoint| watchpoint.matches(address, access));
+        if let Some(index) = watchpoint {
+            self.hit.set(Some(DebugHit::Watchpoint {
+                index,
+                address,
+                access,
+                value,
+                pc: self.pc,
+            }));
+        }
+    }
+
+    /// Whether any watchpoint is enabled, so the bus can skip the checks otherwise.
+    pub(crate) fn is_watching(&self) -> bool {
+        self.watchpoints.iter().any(|watchpoint| watchpoint.enabled)
+    }
+
+    /// Whether any breakpoint or execute watchpoint is enabled.
+    pub(crate) fn is_active(&self) -> bool {
+        self.breakpoints.iter().any(|breakpoint| breakpoint.enabled) || self.is_watching()
+    }
+}
+
+fn parse_hex(s: &str) -> Result<u16, String> {
+    let s = s.trim();
+    let digits = s.strip_prefix('$').or_else(|| s.strip_prefix("0x")).unwrap_or(s);
+    u16::from_str_radix(digits, 16).map_err(|_| format!("Invalid address: {}", s))
+}
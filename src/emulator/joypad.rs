@@ -27,6 +27,19 @@ impl JoypadButton {
     }
 }
 
+/// State of all eight Game Boy buttons (true = pressed).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Buttons {
+    pub right: bool,
+    pub left: bool,
+    pub up: bool,
+    pub down: bool,
+    pub a: bool,
+    pub b: bool,
+    pub select: bool,
+    pub start: bool,
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct Joypad {
     button_state: u8, // 8 bits for 8 buttons (0 = pressed, 1 = unpressed)
@@ -1,37 +1,49 @@
 pub mod apu;
+pub mod boot;
 pub mod cart;
 pub mod cpu;
+pub mod debugger;
 pub mod joypad;
+pub mod model;
 pub mod ppu;
+pub mod rewind;
+pub mod save_state;
 pub mod serial;
+pub mod sgb;
 pub mod timer;
 
 use std::error::Error;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::PathBuf;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
 
-use crate::PRINT_CART_INFO;
-use crate::emulator::joypad::JoypadButton;
+use crate::emulator::joypad::{Buttons, JoypadButton};
+use apu::Resampler;
+use boot::{BootRom, PaletteCombo};
 use cart::Cart;
 use cpu::CPU;
+use model::Model;
+use ppu::dmg_palette::DmgPalettes;
+use rewind::{Rewind, RewindConfig};
+use ppu::{HEIGHT, WIDTH};
+use save_state::{SaveSlot, SaveStateHeader};
+use serial::{LinkCable, Printer, SerialTransfer, SharedDevice};
+use sgb::{SGB_HEIGHT, SGB_WIDTH};
 
 pub const CPU_FREQUENCY: u32 = 4194304;
+pub const CYCLES_PER_FRAME: u64 = 70224;
 
 pub struct InputState {
     // Gameboy buttons
-    pub right: bool,
-    pub left: bool,
-    pub up: bool,
-    pub down: bool,
-    pub a: bool,
-    pub b: bool,
-    pub select: bool,
-    pub start: bool,
+    pub buttons: Buttons,
     // Emulator controls
     pub save: bool,
     pub load: bool,
+    pub quick_save: bool,
+    pub quick_load: bool,
+    /// Numbered slot used by `save` and `load`
+    pub slot: u8,
     // Internal state for save/load handling
     can_change_state: bool,
 }
@@ -39,66 +51,409 @@ pub struct InputState {
 impl InputState {
     fn new() -> Self {
         Self {
-            right: false,
-            left: false,
-            up: false,
-            down: false,
-            a: false,
-            b: false,
-            select: false,
-            start: false,
+            buttons: Buttons::default(),
             save: false,
             load: false,
+            quick_save: false,
+            quick_load: false,
+            slot: 0,
             can_change_state: true,
         }
     }
 }
 
+/// Settings used when creating an `Emulator`.
+#[derive(Clone, Debug, Default)]
+pub struct EmulatorConfig {
+    /// Hardware model, chosen from the cartridge (or boot ROM) if `None`
+    pub model: Option<Model>,
+    /// Boot ROM to run before the cartridge (DMG/MGB/SGB or CGB dump)
+    pub boot_rom: Option<PathBuf>,
+    /// Colours for DMG cartridges on CGB hardware, as if the buttons were held
+    /// during the boot logo. Picked from the cartridge title if `None`.
+    pub compat_palette: Option<PaletteCombo>,
+    /// Print the CPU state after each instruction
+    pub trace: bool,
+    /// Print bytes sent through the serial port to stdout, as test ROMs report
+    /// their results this way
+    pub print_serial: bool,
+    /// Print the cartridge header information when loading
+    pub print_cart_info: bool,
+    /// Keep snapshots to rewind to, disabled if `None`
+    pub rewind: Option<RewindConfig>,
+}
+
 pub struct Emulator {
     pub running: bool,
     pub cpu: CPU,
     pub input_state: InputState,
     pub rom_path: PathBuf,
-    pub next_step: Instant,
+    pub config: EmulatorConfig,
+    resampler: Option<Resampler>,
+    rewind: Option<Rewind>,
+    frame_count: u64,
+    /// Last printer plugged in, see `printer`
+    printer: Option<Arc<Mutex<Printer>>>,
 }
 
 impl Emulator {
-    pub fn new(filename: &str, force_dmg: bool) -> Result<Self, Box<dyn Error>> {
-        let cart = Cart::new(filename)?;
+    pub fn new(filename: &str, model: Option<Model>) -> Result<Self, Box<dyn Error>> {
+        let config = EmulatorConfig {
+            model,
+            ..Default::default()
+        };
+        Self::with_config(filename, &config)
+    }
 
-        if PRINT_CART_INFO {
+    pub fn with_config(filename: &str, config: &EmulatorConfig) -> Result<Self, Box<dyn Error>> {
+        let cart = Cart::new(filename)?;
+        if config.print_cart_info {
             cart.print_info();
         }
 
         let mut emulator = Self {
             running: true,
-            cpu: CPU::new(cart, force_dmg),
+            cpu: power_on(cart, config)?,
             input_state: InputState::new(),
             rom_path: PathBuf::from(filename),
-            next_step: Instant::now(),
+            config: config.clone(),
+            resampler: None,
+            rewind: config.rewind.map(Rewind::new),
+            frame_count: 0,
+            printer: None,
         };
 
         if let Err(e) = emulator.load_sram() {
             eprintln!("Failed to load SRAM: {}", e);
         }
+        if let Err(e) = emulator.load_dmg_palettes() {
+            eprintln!("Failed to load palettes: {}", e);
+        }
 
         Ok(emulator)
     }
 
-    pub fn handle_input(&mut self) {
+    /// Changes the colours of a DMG cartridge running on CGB hardware, see
+    /// [`EmulatorConfig::compat_palette`].
+    pub fn set_compat_palette(&mut self, combo: Option<PaletteCombo>) {
+        self.config.compat_palette = combo;
+        if self.cpu.bus.ppu.dmg_compat {
+            self.cpu.bus.set_compat_palette(combo);
+        }
+    }
+
+    /// Soft reset: power-cycles the console with the same cartridge.
+    ///
+    /// Battery RAM, the RTC and the EEPROM are kept; the model and boot ROM
+    /// are taken from `config`, so changes to them apply on reset.
+    pub fn reset(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut cart = self.cpu.bus.cart.clone();
+        cart.reset();
+        self.restart(cart)?;
+        println!("Reset");
+        Ok(())
+    }
+
+    /// Hard reset: like `reset`, but reloads the ROM from disk first.
+    ///
+    /// Battery RAM goes through the SRAM file, so it survives the reload.
+    pub fn hard_reset(&mut self) -> Result<(), Box<dyn Error>> {
+        let cart = Cart::new(&self.rom_path)?;
+        self.save_sram()?;
+        self.restart(cart)?;
+        if let Err(e) = self.load_sram() {
+            eprintln!("Failed to load SRAM: {}", e);
+        }
+        println!("Reloaded {}", self.rom_path.display());
+        Ok(())
+    }
+
+    fn restart(&mut self, cart: Cart) -> Result<(), Box<dyn Error>> {
+        let cpu = power_on(cart, &self.config)?;
+        self.replace_cpu(cpu);
+        if let Some(rewind) = &mut self.rewind {
+            rewind.clear();
+        }
+        Ok(())
+    }
+
+    /// Switches to `cpu`, keeping what isn't part of the emulated state: the
+    /// debugger, display colours and the serial device and settings.
+    fn replace_cpu(&mut self, mut cpu: CPU) {
+        cpu.bus.debugger = std::mem::take(&mut self.cpu.bus.debugger);
+        cpu.bus.ppu.dmg_palettes = self.cpu.bus.ppu.dmg_palettes;
+        cpu.bus.serial.set_device(self.cpu.bus.serial.device().cloned());
+        cpu.bus.serial.set_print_output(self.config.print_serial);
+        self.cpu = cpu;
+    }
+
+    /// Executes a single CPU instruction (or interrupt dispatch).
+    ///
+    /// Returns the number of T-cycles taken.
+    pub fn step(&mut self) -> u64 {
+        let t_cycles = self.cpu.step();
+
+        if self.config.trace {
+            self.cpu.print_state();
+        }
+
+        if let Some(resampler) = &mut self.resampler {
+            let apu = &mut self.cpu.bus.apu;
+            resampler.process_cpu_cycles(t_cycles as u32, || apu.sample_stereo());
+        }
+
+        if self.cpu.bus.ppu.take_frame_ready() {
+            self.frame_count += 1;
+            if let Some(rewind) = &mut self.rewind
+                && let Err(e) = rewind.on_frame(&self.cpu)
+            {
+                eprintln!("Failed to take rewind snapshot: {}", e);
+            }
+        }
+
+        t_cycles
+    }
+
+    /// Number of frames completed (V-Blank entries) since the emulator was created.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Runs until at least `cycles` T-cycles have elapsed, or the debugger stops.
+    ///
+    /// Returns the number of T-cycles actually executed, which may overshoot
+    /// by the length of the last instruction.
+    pub fn run_cycles(&mut self, cycles: u64) -> u64 {
+        let mut elapsed = 0;
+        while elapsed < cycles && self.cpu.bus.debugger.hit().is_none() {
+            elapsed += self.step();
+        }
+        elapsed
+    }
+
+    /// Runs until the PPU enters V-Blank, i.e. a full frame is in the framebuffer.
+    ///
+    /// While the LCD is off no V-Blank occurs, so one frame's worth of cycles
+    /// is run instead. Stops early if the debugger hits a breakpoint or
+    /// watchpoint. Returns the number of T-cycles executed.
+    pub fn run_frame(&mut self) -> u64 {
+        let start_frame = self.frame_count;
+        let mut elapsed = 0;
+        loop {
+            elapsed += self.step();
+            if self.frame_count != start_frame || self.cpu.bus.debugger.hit().is_some() {
+                break;
+            }
+            if !self.cpu.bus.ppu.is_lcd_enabled() && elapsed >= CYCLES_PER_FRAME {
+                break;
+            }
+        }
+        elapsed
+    }
+
+    /// Like `run_frame`, with `peer` run alongside cycle for cycle, so that link
+    /// cable transfers between the two happen at the same time on both sides.
+    pub fn run_frame_linked(&mut self, peer: &mut Emulator) -> u64 {
+        let start_frame = self.frame_count;
+        let mut elapsed = 0;
+        // T-cycles this emulator is ahead of `peer`
+        let mut ahead = 0i64;
+        loop {
+            if ahead > 0 {
+                ahead -= peer.step() as i64;
+                continue;
+            }
+            let t_cycles = self.step();
+            elapsed += t_cycles;
+            ahead += t_cycles as i64;
+            if self.frame_count != start_frame || self.cpu.bus.debugger.hit().is_some() {
+                break;
+            }
+            if !self.cpu.bus.ppu.is_lcd_enabled() && elapsed >= CYCLES_PER_FRAME {
+                break;
+            }
+        }
+        elapsed
+    }
+
+    /// Returns the last rendered frame as `frame_size` pixels in 0xAARRGGBB format.
+    ///
+    /// In SGB mode this is the whole 256x224 picture, border included.
+    pub fn framebuffer(&self) -> &[u32] {
+        match &self.cpu.bus.sgb {
+            Some(sgb) => sgb.frame(),
+            None => &self.cpu.bus.ppu.buffer,
+        }
+    }
+
+    /// Width and height of `framebuffer`.
+    pub fn frame_size(&self) -> (usize, usize) {
+        match self.cpu.bus.sgb {
+            Some(_) => (SGB_WIDTH, SGB_HEIGHT),
+            None => (WIDTH, HEIGHT),
+        }
+    }
+
+    /// Plugs `device` into the serial port, or unplugs it if `None`.
+    pub fn set_serial_device(&mut self, device: Option<SharedDevice>) {
+        self.cpu.bus.serial.set_device(device);
+    }
+
+    /// Connects this emulator and `other` with a link cable. Run them with
+    /// `run_frame_linked`: run one after the other, a transfer would wait
+    /// forever for the emulator not running.
+    pub fn link(&mut self, other: &mut Emulator) {
+        let (first, second) = LinkCable::pair();
+        self.set_serial_device(Some(Arc::new(Mutex::new(first))));
+        other.set_serial_device(Some(Arc::new(Mutex::new(second))));
+    }
+
+    /// Plugs in a new Game Boy Printer saving its pages next to the ROM, see
+    /// `printout_base`. The returned handle gives access to what it printed.
+    pub fn connect_printer(&mut self) -> Arc<Mutex<Printer>> {
+        let printer = Arc::new(Mutex::new(Printer::new()));
+        self.plug_printer(printer.clone());
+        printer
+    }
+
+    /// Plugs in `printer`, keeping the pages it already printed. New pages are
+    /// saved next to this ROM.
+    pub fn plug_printer(&mut self, printer: Arc<Mutex<Printer>>) {
+        printer
+            .lock()
+            .unwrap()
+            .save_to(Some(self.printout_base()));
+        self.set_serial_device(Some(printer.clone()));
+        self.printer = Some(printer);
+    }
+
+    /// The printer plugged into the serial port, if one is.
+    pub fn printer(&self) -> Option<&Arc<Mutex<Printer>>> {
+        let printer = self.printer.as_ref()?;
+        let device = self.cpu.bus.serial.device()?;
+        std::ptr::addr_eq(Arc::as_ptr(printer), Arc::as_ptr(device)).then_some(printer)
+    }
+
+    /// Printer pages are saved as this path followed by `-<n>.png`.
+    pub fn printout_base(&self) -> PathBuf {
+        let stem = self.rom_path.file_stem().unwrap_or_default().to_string_lossy();
+        self.rom_path.with_file_name(format!("{}-print", stem))
+    }
+
+    /// Takes every byte sent through the serial port since the last call.
+    pub fn take_serial_output(&mut self) -> Vec<u8> {
+        self.cpu.bus.serial.take_output()
+    }
+
+    /// Takes every serial transfer, in both directions, since the last call.
+    ///
+    /// Kept apart from `take_serial_output`: taking one leaves the other untouched.
+    pub fn take_serial_transfers(&mut self) -> Vec<SerialTransfer> {
+        self.cpu.bus.serial.take_transfers()
+    }
+
+    /// See [`EmulatorConfig::print_serial`].
+    pub fn set_print_serial(&mut self, print: bool) {
+        self.config.print_serial = print;
+        self.cpu.bus.serial.set_print_output(print);
+    }
+
+    /// Enables audio sample generation at `sample_rate` Hz, or disables it with `None`.
+    ///
+    /// Audio is disabled by default so headless runs don't pay for resampling.
+    pub fn set_audio_sample_rate(&mut self, sample_rate: Option<f32>) {
+        let current = self.resampler.as_ref().map(|r| r.sample_rate());
+        if current != sample_rate {
+            self.resampler = sample_rate.map(Resampler::new);
+        }
+    }
+
+    /// Takes all stereo samples generated since the last call.
+    pub fn drain_audio(&mut self) -> Vec<(f32, f32)> {
+        self.resampler
+            .as_mut()
+            .map(|r| r.drain())
+            .unwrap_or_default()
+    }
+
+    /// Enables the rewind buffer with `config`, or disables it with `None`.
+    ///
+    /// Snapshots already taken are kept unless the configuration changes.
+    pub fn set_rewind(&mut self, config: Option<RewindConfig>) {
+        if self.rewind_config() != config {
+            self.rewind = config.map(Rewind::new);
+            self.config.rewind = config;
+        }
+    }
+
+    pub fn rewind_config(&self) -> Option<RewindConfig> {
+        self.rewind.as_ref().map(Rewind::config)
+    }
+
+    /// Memory used by rewind snapshots, in bytes.
+    pub fn rewind_memory_usage(&self) -> usize {
+        self.rewind.as_ref().map_or(0, Rewind::used_bytes)
+    }
+
+    /// Goes back to the most recent rewind snapshot, removing it from the buffer.
+    ///
+    /// Returns false if there is nothing left to rewind to.
+    pub fn rewind(&mut self) -> bool {
+        let Some(snapshot) = self.rewind.as_mut().and_then(Rewind::pop) else {
+            return false;
+        };
+
+        match snapshot {
+            Ok(mut state) => {
+                state.bus.cart.rom_data = std::mem::take(&mut self.cpu.bus.cart.rom_data);
+                self.replace_cpu(state);
+                true
+            }
+            Err(e) => {
+                eprintln!("Failed to restore rewind snapshot: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Sets the pressed state of all Game Boy buttons at once.
+    pub fn set_buttons(&mut self, buttons: Buttons) {
         use JoypadButton::*;
 
+        self.set_button_state(Right, buttons.right);
+        self.set_button_state(Left, buttons.left);
+        self.set_button_state(Up, buttons.up);
+        self.set_button_state(Down, buttons.down);
+        self.set_button_state(A, buttons.a);
+        self.set_button_state(B, buttons.b);
+        self.set_button_state(Select, buttons.select);
+        self.set_button_state(Start, buttons.start);
+    }
+
+    pub fn handle_input(&mut self) {
+        let input = &self.input_state;
+        let save_slot = match (input.save, input.quick_save) {
+            (true, _) => Some(SaveSlot::Numbered(input.slot)),
+            (false, true) => Some(SaveSlot::Quick),
+            (false, false) => None,
+        };
+        let load_slot = match (input.load, input.quick_load) {
+            (true, _) => Some(SaveSlot::Numbered(input.slot)),
+            (false, true) => Some(SaveSlot::Quick),
+            (false, false) => None,
+        };
+
         // Handle save/load state
         if self.input_state.can_change_state {
-            if self.input_state.save {
-                match self.save_state() {
+            if let Some(slot) = save_slot {
+                match self.save_state(slot) {
                     Ok(path) => println!("Saved state to {}", path),
                     Err(e) => eprintln!("Failed to save state: {}", e),
                 }
                 self.input_state.can_change_state = false;
                 return;
-            } else if self.input_state.load {
-                match self.load_state() {
+            } else if let Some(slot) = load_slot {
+                match self.load_state(slot) {
                     Ok(path) => println!("Loaded state from {}", path),
                     Err(e) => eprintln!("Failed to load state: {}", e),
                 }
@@ -106,62 +461,72 @@ impl Emulator {
                 return;
             }
         } else {
-            if !self.input_state.save && !self.input_state.load {
+            if save_slot.is_none() && load_slot.is_none() {
                 self.input_state.can_change_state = true;
                 return;
             }
         }
 
         // Apply GameBoy input states directly
-        self.set_button_state(Right, self.input_state.right);
-        self.set_button_state(Left, self.input_state.left);
-        self.set_button_state(Up, self.input_state.up);
-        self.set_button_state(Down, self.input_state.down);
-        self.set_button_state(A, self.input_state.a);
-        self.set_button_state(B, self.input_state.b);
-        self.set_button_state(Select, self.input_state.select);
-        self.set_button_state(Start, self.input_state.start);
+        self.set_buttons(self.input_state.buttons);
     }
 
     fn set_button_state(&mut self, button: JoypadButton, state: bool) {
         self.cpu.bus.set_button_state(button, state);
     }
 
-    /// Save the current emulator state to a file.
+    /// Save the current emulator state to `slot`.
     ///
     /// If the state was successfully saved, returns the path to the save file.
     /// Otherwise, returns an error.
-    pub fn save_state(&self) -> Result<String, Box<dyn std::error::Error>> {
-        let path = &self.get_state_path();
-        let state = self.cpu.clone();
+    pub fn save_state(&self, slot: SaveSlot) -> Result<String, Box<dyn std::error::Error>> {
+        self.save_state_to(&self.get_state_path(slot))
+    }
 
+    /// Save the current emulator state to the file at `path`.
+    pub fn save_state_to(&self, path: &str) -> Result<String, Box<dyn std::error::Error>> {
         let file = File::create(path)?;
-        let writer = BufWriter::new(file);
-        bincode::serialize_into(writer, &state)?;
+        let mut writer = BufWriter::new(file);
+        save_state::write_state(&mut writer, &self.cpu)?;
+        writer.flush()?;
 
         Ok(path.to_string())
     }
 
-    /// Load the emulator state from a file.
+    /// Load the emulator state from `slot`.
     ///
-    /// If the state was successfully saved, returns the path to the save file.
+    /// If the state was successfully loaded, returns the path to the save file.
     /// Otherwise, returns an error.
-    pub fn load_state(&mut self) -> Result<String, Box<dyn std::error::Error>> {
-        let path = &self.get_state_path();
-        let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
-        let reader = BufReader::new(file);
+    pub fn load_state(&mut self, slot: SaveSlot) -> Result<String, Box<dyn std::error::Error>> {
+        self.load_state_from(&self.get_state_path(slot))
+    }
 
-        let mut state: CPU = bincode::deserialize_from(reader)
-            .map_err(|e| format!("Failed to deserialize {}: {}", path, e))?;
+    /// Load the emulator state from the file at `path`.
+    ///
+    /// States made with a different ROM are rejected.
+    pub fn load_state_from(&mut self, path: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+        let (header, mut state) = save_state::read_state(&bytes, &self.cpu.bus.cart)
+            .map_err(|e| format!("Failed to load {}: {}", path, e))?;
 
-        state.bus.cart.reload_rom(&self.rom_path)?;
-        state.bus.ppu.reinit_buffers();
+        state.bus.cart.rom_data = self.cpu.bus.cart.rom_data.clone();
+        // Show the saved frame until the next one is rendered
+        state.bus.ppu.buffer = header.thumbnail;
+        state.bus.reinit_buffers();
 
-        self.cpu = state;
+        self.replace_cpu(state);
+        if let Some(rewind) = &mut self.rewind {
+            rewind.clear();
+        }
 
         Ok(path.to_string())
     }
 
+    /// Reads the header (title, timestamp, thumbnail...) of the state in `slot`.
+    pub fn state_header(&self, slot: SaveSlot) -> Result<SaveStateHeader, Box<dyn Error>> {
+        SaveStateHeader::read(self.get_state_path(slot))
+    }
+
     pub fn save_sram(&self) -> Result<(), Box<dyn std::error::Error>> {
         let cart = &self.cpu.bus.cart;
         if cart.has_battery() {
@@ -190,13 +555,37 @@ impl Emulator {
         Ok(())
     }
 
-    /// Get the save state file path based on the ROM path.
-    fn get_state_path(&self) -> String {
+    /// Get the save state file path of `slot` based on the ROM path.
+    pub fn get_state_path(&self, slot: SaveSlot) -> String {
         let mut path = self.rom_path.clone();
-        path.set_extension("sav");
+        path.set_extension(slot.extension());
         path.to_string_lossy().to_string()
     }
 
+    pub fn dmg_palettes(&self) -> DmgPalettes {
+        self.cpu.bus.ppu.dmg_palettes
+    }
+
+    /// Changes the DMG display colours and remembers them for this ROM.
+    pub fn set_dmg_palettes(&mut self, palettes: DmgPalettes) -> Result<(), Box<dyn Error>> {
+        self.cpu.bus.ppu.dmg_palettes = palettes;
+        palettes.save(self.get_palette_path())
+    }
+
+    /// Loads the DMG display colours saved for this ROM, if any.
+    fn load_dmg_palettes(&mut self) -> Result<(), Box<dyn Error>> {
+        let path = self.get_palette_path();
+        if path.exists() {
+            self.cpu.bus.ppu.dmg_palettes = DmgPalettes::load(path)?;
+        }
+        Ok(())
+    }
+
+    /// Get the palette file path based on the ROM path.
+    fn get_palette_path(&self) -> PathBuf {
+        self.rom_path.with_extension("pal")
+    }
+
     /// Get the SRAM file path based on the ROM path.
     fn get_sram_path(&self) -> String {
         let mut path = self.rom_path.clone();
@@ -204,3 +593,25 @@ impl Emulator {
         path.to_string_lossy().to_string()
     }
 }
+
+/// Creates the CPU of the console selected by `config`, with `cart` inserted.
+fn power_on(cart: Cart, config: &EmulatorConfig) -> Result<CPU, Box<dyn Error>> {
+    let boot_rom = config.boot_rom.as_ref().map(BootRom::load).transpose()?;
+    let model = match (config.model, &boot_rom) {
+        (Some(model), Some(boot_rom)) if model.is_cgb() != boot_rom.is_cgb() => {
+            return Err(format!("The boot ROM doesn't match the {} model", model).into());
+        }
+        (Some(model), _) => model,
+        (None, Some(boot_rom)) if boot_rom.is_cgb() => Model::Cgb,
+        (None, Some(_)) => Model::Dmg,
+        (None, None) => Model::for_cart(&cart),
+    };
+
+    let mut cpu = CPU::new(cart, model, boot_rom);
+    cpu.bus.serial.set_print_output(config.print_serial);
+    // A boot ROM reads the buttons itself, otherwise compatibility mode is already set up
+    if config.compat_palette.is_some() && cpu.bus.ppu.dmg_compat {
+        cpu.bus.set_compat_palette(config.compat_palette);
+    }
+    Ok(cpu)
+}
@@ -0,0 +1,87 @@
+//! Game Boy hardware revisions.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::emulator::cart::Cart;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Model {
+    /// Early original Game Boy (different boot ROM and DIV phase)
+    Dmg0,
+    /// Original Game Boy
+    Dmg,
+    /// Game Boy Pocket/Light
+    Mgb,
+    /// Super Game Boy
+    Sgb,
+    /// Game Boy Color
+    Cgb,
+    /// Game Boy Advance running in Game Boy Color mode
+    Agb,
+}
+
+impl Model {
+    pub const ALL: [Model; 6] = [
+        Model::Dmg0,
+        Model::Dmg,
+        Model::Mgb,
+        Model::Sgb,
+        Model::Cgb,
+        Model::Agb,
+    ];
+
+    /// The model used when none is selected: CGB for CGB-compatible cartridges, DMG otherwise.
+    pub fn for_cart(cart: &Cart) -> Self {
+        if cart.is_cgb() { Model::Cgb } else { Model::Dmg }
+    }
+
+    /// Whether this is CGB hardware, able to run CGB cartridges in CGB mode.
+    ///
+    /// DMG cartridges run in compatibility mode on these, colourised through the CGB palettes.
+    pub fn is_cgb(self) -> bool {
+        matches!(self, Model::Cgb | Model::Agb)
+    }
+
+    /// Short name, as accepted by `--model`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Model::Dmg0 => "DMG0",
+            Model::Dmg => "DMG",
+            Model::Mgb => "MGB",
+            Model::Sgb => "SGB",
+            Model::Cgb => "CGB",
+            Model::Agb => "AGB",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Model::Dmg0 => "Game Boy (DMG0)",
+            Model::Dmg => "Game Boy (DMG)",
+            Model::Mgb => "Game Boy Pocket (MGB)",
+            Model::Sgb => "Super Game Boy (SGB)",
+            Model::Cgb => "Game Boy Color (CGB)",
+            Model::Agb => "Game Boy Advance (AGB)",
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Model {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Model::ALL
+            .into_iter()
+            .find(|model| model.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown model: {}", s))
+    }
+}
@@ -0,0 +1,299 @@
+//! Rendering of VRAM and OAM contents for the debug viewers.
+
+use std::fmt;
+
+use super::lcdc::LcdcData;
+use super::{
+    HEIGHT, MAX_SPRITES_PER_SCANLINE, OAM_SPRITES, OAMSprite, PPU, RenderSprite, TileAttributes,
+    get_sprite_height, get_sprite_tile_address, get_tile_address,
+};
+
+/// Tiles in one VRAM bank (0x8000-0x97FF)
+pub const TILES_PER_BANK: usize = 384;
+/// Tile sheet layout: 16 tiles per row, 24 rows
+pub const TILE_SHEET_COLUMNS: usize = 16;
+pub const TILE_SHEET_WIDTH: usize = TILE_SHEET_COLUMNS * 8;
+pub const TILE_SHEET_HEIGHT: usize = TILES_PER_BANK / TILE_SHEET_COLUMNS * 8;
+
+const TILE_SIZE_BYTES: usize = 16;
+
+/// Width and height of a BG map in pixels
+pub const TILE_MAP_SIZE: usize = 256;
+
+/// Palette a viewer colours tiles with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewerPalette {
+    Bgp,
+    Obp0,
+    Obp1,
+    /// CGB background palette 0-7
+    CgbBg(u8),
+    /// CGB object palette 0-7
+    CgbObj(u8),
+}
+
+impl ViewerPalette {
+    /// Palettes available in the current mode.
+    pub fn all(cgb_mode: bool) -> Vec<ViewerPalette> {
+        let mut palettes = vec![ViewerPalette::Bgp, ViewerPalette::Obp0, ViewerPalette::Obp1];
+        if cgb_mode {
+            palettes.extend((0..8).map(ViewerPalette::CgbBg));
+            palettes.extend((0..8).map(ViewerPalette::CgbObj));
+        }
+        palettes
+    }
+}
+
+impl fmt::Display for ViewerPalette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViewerPalette::Bgp => write!(f, "BGP"),
+            ViewerPalette::Obp0 => write!(f, "OBP0"),
+            ViewerPalette::Obp1 => write!(f, "OBP1"),
+            ViewerPalette::CgbBg(palette) => write!(f, "BG {}", palette),
+            ViewerPalette::CgbObj(palette) => write!(f, "OBJ {}", palette),
+        }
+    }
+}
+
+/// One of the two 32x32 BG maps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileMap {
+    Map9800,
+    Map9C00,
+}
+
+impl TileMap {
+    pub const ALL: [TileMap; 2] = [TileMap::Map9800, TileMap::Map9C00];
+
+    pub fn base_address(self) -> u16 {
+        match self {
+            TileMap::Map9800 => 0x9800,
+            TileMap::Map9C00 => 0x9C00,
+        }
+    }
+
+    /// Map selected by an LCDC map bit (3 for BG, 6 for window).
+    fn from_lcdc_bit(bit: bool) -> Self {
+        if bit {
+            TileMap::Map9C00
+        } else {
+            TileMap::Map9800
+        }
+    }
+}
+
+impl fmt::Display for TileMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${:04X}", self.base_address())
+    }
+}
+
+/// A BG map entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileMapEntry {
+    /// Address of the entry in the map
+    pub address: u16,
+    pub tile_id: u8,
+    /// VRAM address of the tile data, following LCDC bit 4
+    pub tile_address: u16,
+    pub attributes: TileAttributes,
+}
+
+/// Which maps the BG and window use, and where they are on screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MapLayout {
+    pub bg_map: TileMap,
+    pub window_map: TileMap,
+    pub window_enabled: bool,
+    pub scx: u8,
+    pub scy: u8,
+    pub wx: u8,
+    pub wy: u8,
+}
+
+/// VRAM address of tile `index` (0-383).
+pub fn tile_address(index: usize) -> u16 {
+    0x8000 + (index * TILE_SIZE_BYTES) as u16
+}
+
+impl PPU {
+    /// Colour of `color_index` through `palette`, as the renderer outputs it.
+    pub fn palette_color(&self, palette: ViewerPalette, color_index: u8) -> u32 {
+        match palette {
+            ViewerPalette::Bgp => self.get_dmg_color(self.bgp, color_index, None),
+            ViewerPalette::Obp0 => self.get_dmg_color(self.obp0, color_index, Some(0)),
+            ViewerPalette::Obp1 => self.get_dmg_color(self.obp1, color_index, Some(1)),
+            ViewerPalette::CgbBg(palette) => self.get_cgb_color(palette, color_index, false),
+            ViewerPalette::CgbObj(palette) => self.get_cgb_color(palette, color_index, true),
+        }
+    }
+
+    /// Renders every tile of VRAM `bank` as a [`TILE_SHEET_WIDTH`] x [`TILE_SHEET_HEIGHT`] sheet.
+    pub fn render_tile_sheet(&self, bank: usize, palette: ViewerPalette) -> Vec<u32> {
+        let colors: Vec<u32> = (0..4).map(|i| self.palette_color(palette, i)).collect();
+        let mut pixels = vec![0; TILE_SHEET_WIDTH * TILE_SHEET_HEIGHT];
+
+        for tile in 0..TILES_PER_BANK {
+            let tile_addr = tile_address(tile);
+            let left = (tile % TILE_SHEET_COLUMNS) * 8;
+            let top = (tile / TILE_SHEET_COLUMNS) * 8;
+            for y in 0..8 {
+                for x in 0..8 {
+                    let color_index = self.tile_color_index(bank, tile_addr, x, y);
+                    pixels[(top + y) * TILE_SHEET_WIDTH + left + x] = colors[color_index as usize];
+                }
+            }
+        }
+
+        pixels
+    }
+
+    /// Current map selection, scroll and window position.
+    pub fn map_layout(&self) -> MapLayout {
+        let lcdc = LcdcData::from(self.lcdc);
+        MapLayout {
+            bg_map: TileMap::from_lcdc_bit(lcdc.bg_tile_map),
+            window_map: TileMap::from_lcdc_bit(lcdc.window_tile_map),
+            window_enabled: lcdc.window_enable,
+            scx: self.scx,
+            scy: self.scy,
+            wx: self.wx,
+            wy: self.wy,
+        }
+    }
+
+    /// Entry at `column`, `row` (0-31) of `map`.
+    pub fn tile_map_entry(&self, map: TileMap, column: usize, row: usize) -> TileMapEntry {
+        let address = map.base_address() + (row * 32 + column) as u16;
+        let index = (address - 0x8000) as usize;
+        let tile_id = self.vram[0][index];
+        let attributes = if self.cgb_mode {
+            TileAttributes::from(self.vram[1][index])
+        } else {
+            TileAttributes::default()
+        };
+
+        TileMapEntry {
+            address,
+            tile_id,
+            tile_address: get_tile_address(tile_id, LcdcData::from(self.lcdc).bg_window_tile_data),
+            attributes,
+        }
+    }
+
+    /// Renders the whole of `map` as a [`TILE_MAP_SIZE`] square, as the BG would be drawn.
+    pub fn render_tile_map(&self, map: TileMap) -> Vec<u32> {
+        let bg_window_tile_data = LcdcData::from(self.lcdc).bg_window_tile_data;
+        let mut pixels = vec![0; TILE_MAP_SIZE * TILE_MAP_SIZE];
+
+        for y in 0..TILE_MAP_SIZE {
+            for x in 0..TILE_MAP_SIZE {
+                let (color_index, attributes) =
+                    self.bg_map_pixel(map.base_address(), x, y, bg_window_tile_data);
+                pixels[y * TILE_MAP_SIZE + x] = if self.cgb_mode {
+                    self.get_cgb_color(attributes.palette, color_index, false)
+                } else {
+                    self.get_dmg_color(self.bgp, color_index, None)
+                };
+            }
+        }
+
+        pixels
+    }
+
+    /// Decoded OAM entry `index` (0-39).
+    pub fn oam_sprite(&self, index: usize) -> RenderSprite {
+        RenderSprite::from(OAMSprite::at_oam(&self.oam, index))
+    }
+
+    /// Sprite height (8 or 16) selected by LCDC bit 2.
+    pub fn sprite_height(&self) -> u8 {
+        get_sprite_height(LcdcData::from(self.lcdc).obj_size)
+    }
+
+    /// For each sprite, the visible scanlines it was dropped from by the
+    /// 10 sprites per line limit.
+    pub fn dropped_sprite_lines(&self) -> Vec<Vec<u8>> {
+        let sprite_height = self.sprite_height();
+        let mut dropped = vec![Vec::new(); OAM_SPRITES];
+
+        for ly in 0..HEIGHT as u8 {
+            for sprite in self
+                .sprites_on_line(ly, sprite_height)
+                .skip(MAX_SPRITES_PER_SCANLINE)
+            {
+                dropped[sprite.oam_index].push(ly);
+            }
+        }
+
+        dropped
+    }
+
+    /// Renders sprite `index` as drawn on screen, 8 pixels wide by [`PPU::sprite_height`].
+    ///
+    /// Colour 0 is transparent (alpha 0).
+    pub fn render_sprite(&self, index: usize) -> Vec<u32> {
+        let sprite = self.oam_sprite(index);
+        let sprite_height = self.sprite_height() as usize;
+        // For 8x16 sprites, lower bit of tile ignored
+        let tile_index = if sprite_height == 16 {
+            sprite.tile_index & 0xFE
+        } else {
+            sprite.tile_index
+        };
+        let bank = if self.cgb_mode {
+            sprite.cgb_vram_bank as usize
+        } else {
+            0
+        };
+
+        let mut pixels = vec![0; 8 * sprite_height];
+        for y in 0..sprite_height {
+            let line = if sprite.y_flip {
+                sprite_height - 1 - y
+            } else {
+                y
+            };
+            let tile_addr = get_sprite_tile_address(tile_index) + (line / 8 * 16) as u16;
+            for x in 0..8 {
+                let column = if sprite.x_flip { 7 - x } else { x };
+                let color_index = self.tile_color_index(bank, tile_addr, column, line % 8);
+                if color_index == 0 {
+                    continue;
+                }
+                pixels[y * 8 + x] = if self.cgb_mode {
+                    self.get_cgb_color(sprite.cgb_palette, color_index, true)
+                } else {
+                    let obp = if sprite.palette_index {
+                        self.obp1
+                    } else {
+                        self.obp0
+                    };
+                    self.get_dmg_color(obp, color_index, Some(sprite.palette_index as u8))
+                };
+            }
+        }
+
+        pixels
+    }
+
+    /// Overwrites `color_index` in CGB palette `palette_num`, as if written through BCPD/OCPD.
+    pub fn set_cgb_palette_rgb555(
+        &mut self,
+        palette_num: u8,
+        color_index: u8,
+        is_obj: bool,
+        rgb555: u16,
+    ) {
+        let palette_data = if is_obj {
+            &mut self.obj_palette_data
+        } else {
+            &mut self.bg_palette_data
+        };
+
+        let base_index = (palette_num * 8 + color_index * 2) as usize;
+        palette_data[base_index] = rgb555 as u8;
+        palette_data[base_index + 1] = (rgb555 >> 8) as u8;
+    }
+}
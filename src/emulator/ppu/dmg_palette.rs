@@ -0,0 +1,167 @@
+//! Colours the four DMG shades are displayed with.
+
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Four colours as 0xAARRGGBB, lightest shade first
+pub type Shades = [u32; 4];
+
+pub struct PalettePreset {
+    pub name: &'static str,
+    pub shades: Shades,
+}
+
+pub const DMG_GREEN: Shades = [0xFF9A9E3F, 0xFF496B22, 0xFF0E450B, 0xFF1B2A09];
+
+pub const PRESETS: [PalettePreset; 5] = [
+    PalettePreset {
+        name: "DMG Green",
+        shades: DMG_GREEN,
+    },
+    PalettePreset {
+        name: "Pocket Grey",
+        shades: [0xFFC4CFA1, 0xFF8B956D, 0xFF4D533C, 0xFF1F1F1F],
+    },
+    PalettePreset {
+        name: "Light",
+        shades: [0xFF00B581, 0xFF009A71, 0xFF00694A, 0xFF004F3B],
+    },
+    PalettePreset {
+        name: "BGB",
+        shades: [0xFFE0F8D0, 0xFF88C070, 0xFF346856, 0xFF081820],
+    },
+    PalettePreset {
+        name: "High Contrast",
+        shades: [0xFFFFFFFF, 0xFFAAAAAA, 0xFF555555, 0xFF000000],
+    },
+];
+
+/// Layer with its own display colours: background/window, or one of the OBJ palettes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DmgLayer {
+    Bg,
+    Obj0,
+    Obj1,
+}
+
+impl DmgLayer {
+    pub const ALL: [DmgLayer; 3] = [DmgLayer::Bg, DmgLayer::Obj0, DmgLayer::Obj1];
+}
+
+impl fmt::Display for DmgLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DmgLayer::Bg => write!(f, "BG"),
+            DmgLayer::Obj0 => write!(f, "OBJ 0"),
+            DmgLayer::Obj1 => write!(f, "OBJ 1"),
+        }
+    }
+}
+
+/// Display colours for the background/window and both OBJ palettes in DMG mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DmgPalettes {
+    pub bg: Shades,
+    pub obj0: Shades,
+    pub obj1: Shades,
+}
+
+impl Default for DmgPalettes {
+    fn default() -> Self {
+        Self::uniform(DMG_GREEN)
+    }
+}
+
+impl DmgPalettes {
+    /// The same colours for every layer.
+    pub fn uniform(shades: Shades) -> Self {
+        Self {
+            bg: shades,
+            obj0: shades,
+            obj1: shades,
+        }
+    }
+
+    pub fn layer(&self, layer: DmgLayer) -> Shades {
+        match layer {
+            DmgLayer::Bg => self.bg,
+            DmgLayer::Obj0 => self.obj0,
+            DmgLayer::Obj1 => self.obj1,
+        }
+    }
+
+    pub fn set_layer(&mut self, layer: DmgLayer, shades: Shades) {
+        match layer {
+            DmgLayer::Bg => self.bg = shades,
+            DmgLayer::Obj0 => self.obj0 = shades,
+            DmgLayer::Obj1 => self.obj1 = shades,
+        }
+    }
+
+    /// Parses palette file contents: 4 colours for every layer, or 12 for BG, OBJ0
+    /// and OBJ1 in that order.
+    ///
+    /// Colours are `RRGGBB` hex values, optionally prefixed with `#` or `0x`,
+    /// separated by whitespace or commas. Text after `;` is a comment.
+    pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let colors = text
+            .lines()
+            .map(|line| line.split(';').next().unwrap_or_default())
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|token| !token.is_empty())
+            .map(parse_color)
+            .collect::<Result<Vec<u32>, _>>()?;
+
+        let shades = |i: usize| -> Shades { colors[i * 4..i * 4 + 4].try_into().unwrap() };
+        match colors.len() {
+            4 => Ok(Self::uniform(shades(0))),
+            12 => Ok(Self {
+                bg: shades(0),
+                obj0: shades(1),
+                obj1: shades(2),
+            }),
+            count => Err(format!("Expected 4 or 12 colours, found {}", count).into()),
+        }
+    }
+
+    /// Formats the palettes as a file that [`DmgPalettes::parse`] reads back.
+    pub fn to_file_string(&self) -> String {
+        let mut text = String::from("; BG, OBJ0, OBJ1, lightest shade first\n");
+        for layer in DmgLayer::ALL {
+            let shades = self.layer(layer);
+            let line: Vec<String> = shades
+                .iter()
+                .map(|color| format!("{:06X}", color & 0xFFFFFF))
+                .collect();
+            let _ = writeln!(text, "{}", line.join(" "));
+        }
+        text
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("Invalid palette {}: {}", path.display(), e).into())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, self.to_file_string())?;
+        Ok(())
+    }
+}
+
+fn parse_color(token: &str) -> Result<u32, Box<dyn Error>> {
+    let hex = token
+        .strip_prefix('#')
+        .or_else(|| token.strip_prefix("0x"))
+        .unwrap_or(token);
+    if hex.len() != 6 {
+        return Err(format!("Invalid colour: {}", token).into());
+    }
+    let rgb = u32::from_str_radix(hex, 16).map_err(|_| format!("Invalid colour: {}", token))?;
+    Ok(0xFF000000 | rgb)
+}
@@ -1,10 +1,24 @@
+mod debug_view;
+pub mod dmg_palette;
 mod lcdc;
 mod sprite;
+mod tile_attributes;
 
-use crate::{emulator::cpu::memory_bus::InterruptBit, utils::bits::*};
+use crate::{
+    emulator::{boot::CompatPalettes, cpu::memory_bus::InterruptBit},
+    utils::bits::*,
+};
+use dmg_palette::DmgPalettes;
 use lcdc::LcdcData;
 use serde::{Deserialize, Serialize};
-use sprite::{OAMSprite, RenderSprite, SPRITE_WIDTH};
+use sprite::SPRITE_WIDTH;
+
+pub use debug_view::{
+    MapLayout, TILE_MAP_SIZE, TILE_SHEET_COLUMNS, TILE_SHEET_HEIGHT, TILE_SHEET_WIDTH,
+    TILES_PER_BANK, TileMap, TileMapEntry, ViewerPalette, tile_address,
+};
+pub use sprite::{OAM_SPRITES, OAMSprite, RenderSprite};
+pub use tile_attributes::TileAttributes;
 
 pub const WIDTH: usize = 160;
 pub const HEIGHT: usize = 144;
@@ -49,8 +63,18 @@ pub struct PPU {
     window_line: u8,
     #[serde(skip)]
     bg_color_indices: Vec<u8>,
+    /// DMG shade (0-3) of each pixel after BGP/OBP0/OBP1, which the SGB colourises
+    #[serde(skip)]
+    shades: Vec<u8>,
+    #[serde(skip)]
+    frame_ready: bool,
     // CGB-specific fields
     pub cgb_mode: bool,
+    /// CGB hardware running a DMG cartridge: DMG shades index the CGB palettes
+    pub dmg_compat: bool,
+    /// Display colours of the DMG shades, a frontend setting rather than console state
+    #[serde(skip)]
+    pub dmg_palettes: DmgPalettes,
     vram_bank: usize,
     bg_palette_data: Vec<u8>,
     bg_palette_index: u8,
@@ -82,7 +106,11 @@ impl PPU {
             dot_counter: 0,
             window_line: 0,
             bg_color_indices: vec![0; WIDTH * HEIGHT],
+            shades: vec![0; WIDTH * HEIGHT],
+            frame_ready: false,
             cgb_mode: false,
+            dmg_compat: false,
+            dmg_palettes: DmgPalettes::default(),
             vram_bank: 0,
             bg_palette_data: vec![0; BG_PALETTE_SIZE],
             bg_palette_index: 0,
@@ -90,14 +118,14 @@ impl PPU {
             obj_palette_data: vec![0; OBJ_PALETTE_SIZE],
             obj_palette_index: 0,
             obj_palette_auto_increment: false,
-            // Initialize PPU registers with boot values
-            lcdc: 0x91,
+            // Power-on values, the boot ROM (or its post-boot state) sets the rest
+            lcdc: 0,
             stat: 0,
             scy: 0,
             scx: 0,
             ly: 0,
             lyc: 0,
-            bgp: 0xFC,
+            bgp: 0,
             obp0: 0xFF,
             obp1: 0xFF,
             wy: 0,
@@ -116,12 +144,15 @@ impl PPU {
 
     /// Reinitialize transient buffers after deserialization (since they're skipped in save states)
     pub fn reinit_buffers(&mut self) {
-        if self.buffer.is_empty() {
+        if self.buffer.len() != WIDTH * HEIGHT {
             self.buffer = vec![0; WIDTH * HEIGHT];
         }
         if self.bg_color_indices.is_empty() {
             self.bg_color_indices = vec![0; WIDTH * HEIGHT];
         }
+        if self.shades.is_empty() {
+            self.shades = vec![0; WIDTH * HEIGHT];
+        }
     }
     
     // PPU register read/write methods
@@ -179,6 +210,21 @@ impl PPU {
         }
     }
     
+    /// Loads the palettes used in DMG compatibility mode (BG palette 0, OBJ palettes 0 and 1).
+    pub fn set_compat_palettes(&mut self, palettes: &CompatPalettes) {
+        for (color_idx, &rgb555) in palettes.bg.iter().enumerate() {
+            self.bg_palette_data[color_idx * 2] = rgb555 as u8;
+            self.bg_palette_data[color_idx * 2 + 1] = (rgb555 >> 8) as u8;
+        }
+        for (palette_num, colors) in [palettes.obj0, palettes.obj1].iter().enumerate() {
+            for (color_idx, &rgb555) in colors.iter().enumerate() {
+                let base_index = palette_num * 8 + color_idx * 2;
+                self.obj_palette_data[base_index] = rgb555 as u8;
+                self.obj_palette_data[base_index + 1] = (rgb555 >> 8) as u8;
+            }
+        }
+    }
+
     fn init_default_cgb_palettes(&mut self) {
         // Initialize with a simple visible grayscale palette so we can see if anything is rendering
         // Games will overwrite these with their own palettes
@@ -317,12 +363,39 @@ impl PPU {
         self.oam[(address - 0xFE00) as usize] = value;
     }
 
+    /// Contents of VRAM `bank` (0-1), regardless of the selected bank or PPU mode.
+    pub fn vram_bank(&self, bank: usize) -> &[u8] {
+        &self.vram[bank]
+    }
+
+    pub fn vram_bank_mut(&mut self, bank: usize) -> &mut [u8] {
+        &mut self.vram[bank]
+    }
+
+    pub fn oam(&self) -> &[u8] {
+        &self.oam
+    }
+
+    pub fn oam_mut(&mut self) -> &mut [u8] {
+        &mut self.oam
+    }
+
     /// Reads two consecutive bytes from VRAM
     fn read_vram_pair(&self, addr: u16) -> (u8, u8) {
         let index = vram_index(addr);
         (self.vram[self.vram_bank][index], self.vram[self.vram_bank][index + 1])
     }
 
+    /// DMG shades of the last frame, `WIDTH * HEIGHT` values from 0 (lightest) to 3.
+    pub fn shades(&self) -> &[u8] {
+        &self.shades
+    }
+
+    /// Returns true once per frame, after the PPU has entered V-Blank.
+    pub fn take_frame_ready(&mut self) -> bool {
+        std::mem::take(&mut self.frame_ready)
+    }
+
     pub fn is_lcd_enabled(&self) -> bool {
         (self.lcdc & BIT_7) != 0
     }
@@ -379,6 +452,7 @@ impl PPU {
             // V-Blank interrupt is requested ONCE, when line transitions to 144
             if previous_line == 143 && ly == 144 {
                 *interrupt_flag |= InterruptBit::VBlank as u8;
+                self.frame_ready = true;
             }
             PPUMode::VBlank // VBlank for ALL dots during lines 144-153
         } else {
@@ -443,7 +517,8 @@ impl PPU {
         // IF BG is disabled, fill with 0th color
         if !lcdc_data.bg_enable {
             for x in 0..WIDTH {
-                self.buffer[self.ly as usize * WIDTH + x] = self.get_dmg_color(self.bgp, 0);
+                self.buffer[self.ly as usize * WIDTH + x] = self.get_dmg_color(self.bgp, 0, None);
+                self.shades[self.ly as usize * WIDTH + x] = dmg_shade(self.bgp, 0);
             }
             return;
         }
@@ -479,61 +554,25 @@ impl PPU {
                 let y_coord = self.ly.wrapping_add(self.scy) as usize;
                 (bg_tile_map_addr, x_coord, y_coord)
             };
-            let tile_map_x = (x_coord / 8) % 32;
-            let tile_map_y = (y_coord / 8) % 32;
-            let tile_x = x_coord % 8;
-            let tile_y = y_coord % 8;
-
-            let tile_map_index = tile_map_y * 32 + tile_map_x;
-            let tile_id_addr = tile_map_addr + tile_map_index as u16;
-            let tile_id = self.vram[0][vram_index(tile_id_addr)];
-            
-            // CGB: Read tile attributes from VRAM bank 1
-            let (palette_num, vram_bank, x_flip, y_flip, _bg_priority) = if self.cgb_mode {
-                let attr = self.vram[1][vram_index(tile_id_addr)];
-                let palette_num = attr & 0x07;
-                let vram_bank = ((attr & 0x08) >> 3) as usize;
-                let x_flip = (attr & 0x20) != 0;
-                let y_flip = (attr & 0x40) != 0;
-                let bg_priority = (attr & 0x80) != 0;
-                (palette_num, vram_bank, x_flip, y_flip, bg_priority)
-            } else {
-                (0, 0, false, false, false)
-            };
-            
-            // Apply Y flip
-            let flipped_tile_y = if y_flip { 7 - tile_y } else { tile_y };
-            
-            // Get tile address (use correct VRAM bank for CGB)
-            let tile_addr = get_tile_address(tile_id, lcdc_data.bg_window_tile_data);
-            let tile_data_addr = tile_addr + (flipped_tile_y as u16) * 2;
-            
-            // Read tile data from the correct VRAM bank
-            let (byte1, byte2) = if self.cgb_mode {
-                let index = vram_index(tile_data_addr);
-                (self.vram[vram_bank][index], self.vram[vram_bank][index + 1])
-            } else {
-                self.read_vram_pair(tile_data_addr)
-            };
-            
-            // Apply X flip
-            let flipped_tile_x = if x_flip { 7 - tile_x } else { tile_x };
-            
-            let bit1 = (byte1 >> (7 - flipped_tile_x)) & BIT_0;
-            let bit2 = (byte2 >> (7 - flipped_tile_x)) & BIT_0;
-            let color_index = (bit2 << 1) | bit1;
+            let (color_index, attributes) = self.bg_map_pixel(
+                tile_map_addr,
+                x_coord,
+                y_coord,
+                lcdc_data.bg_window_tile_data,
+            );
 
             // Get the final color
             let out_color = if self.cgb_mode {
-                self.get_cgb_color(palette_num, color_index, false)
+                self.get_cgb_color(attributes.palette, color_index, false)
             } else {
-                self.get_dmg_color(self.bgp, color_index)
+                self.get_dmg_color(self.bgp, color_index, None)
             };
 
             let buffer_index = self.ly as usize * WIDTH + x;
 
             self.buffer[buffer_index] = out_color;
             self.bg_color_indices[buffer_index] = color_index;
+            self.shades[buffer_index] = dmg_shade(self.bgp, color_index);
         }
 
         // --- Render Sprites (OBJ) ---
@@ -542,26 +581,54 @@ impl PPU {
         }
     }
 
+    /// Colour index and attributes of pixel `x`, `y` (wrapping at 256) of the BG map at
+    /// `tile_map_addr`. Attributes are only read in CGB mode.
+    fn bg_map_pixel(
+        &self,
+        tile_map_addr: u16,
+        x: usize,
+        y: usize,
+        bg_window_tile_data: bool,
+    ) -> (u8, TileAttributes) {
+        let tile_map_x = (x / 8) % 32;
+        let tile_map_y = (y / 8) % 32;
+        let tile_id_addr = tile_map_addr + (tile_map_y * 32 + tile_map_x) as u16;
+        let tile_id = self.vram[0][vram_index(tile_id_addr)];
+
+        // CGB: Read tile attributes from VRAM bank 1
+        let attributes = if self.cgb_mode {
+            TileAttributes::from(self.vram[1][vram_index(tile_id_addr)])
+        } else {
+            TileAttributes::default()
+        };
+
+        let tile_x = if attributes.x_flip { 7 - x % 8 } else { x % 8 };
+        let tile_y = if attributes.y_flip { 7 - y % 8 } else { y % 8 };
+        let tile_addr = get_tile_address(tile_id, bg_window_tile_data);
+        let color_index = self.tile_color_index(attributes.vram_bank, tile_addr, tile_x, tile_y);
+
+        (color_index, attributes)
+    }
+
+    /// Colour index (0-3) of pixel `x`, `y` of the tile at `tile_addr` in VRAM `bank`.
+    pub fn tile_color_index(&self, bank: usize, tile_addr: u16, x: usize, y: usize) -> u8 {
+        let index = vram_index(tile_addr) + y * 2;
+        let (byte1, byte2) = (self.vram[bank][index], self.vram[bank][index + 1]);
+        let bit1 = (byte1 >> (7 - x)) & BIT_0;
+        let bit2 = (byte2 >> (7 - x)) & BIT_0;
+        (bit2 << 1) | bit1
+    }
+
     /// Returns a list of sprites that are visible on the current scanline.
     ///
     /// `ly` - The current scanline (0-143)
     ///
     /// `sprite_height` - The height of the sprite (8 or 16)
     fn get_visible_sprites(&self, ly: u8, sprite_height: u8) -> Vec<RenderSprite> {
-        let mut visible_sprites = Vec::new();
-
-        // Check all 40 sprites in OAM
-        for i in 0..40 {
-            let oam_sprite = OAMSprite::at_oam(&self.oam, i);
-            let render_sprite = RenderSprite::from(oam_sprite);
-
-            if render_sprite.is_visible_on_line(ly, sprite_height) {
-                visible_sprites.push(render_sprite);
-                if visible_sprites.len() >= MAX_SPRITES_PER_SCANLINE {
-                    break;
-                }
-            }
-        }
+        let mut visible_sprites: Vec<RenderSprite> = self
+            .sprites_on_line(ly, sprite_height)
+            .take(MAX_SPRITES_PER_SCANLINE)
+            .collect();
 
         // Sort by X position (lower X first), then by OAM index (lower index first)
         visible_sprites.sort_by(|a, b| {
@@ -575,6 +642,13 @@ impl PPU {
         visible_sprites
     }
 
+    /// Every sprite overlapping scanline `ly`, in OAM order, before the per-line limit.
+    fn sprites_on_line(&self, ly: u8, sprite_height: u8) -> impl Iterator<Item = RenderSprite> {
+        (0..OAM_SPRITES)
+            .map(|i| RenderSprite::from(OAMSprite::at_oam(&self.oam, i)))
+            .filter(move |sprite| sprite.is_visible_on_line(ly, sprite_height))
+    }
+
     /// Renders sprites for the current scanline.
     fn render_sprites(&mut self) {
         let lcdc_data = LcdcData::from(self.lcdc);
@@ -637,64 +711,86 @@ impl PPU {
                 let buffer_index = self.ly as usize * WIDTH + pixel_x_usize;
 
                 // Get sprite color
+                let obp = if sprite.palette_index { self.obp1 } else { self.obp0 };
                 let out_color = if self.cgb_mode {
                     self.get_cgb_color(sprite.cgb_palette, color_index, true)
                 } else {
-                    let obp = if sprite.palette_index { self.obp1 } else { self.obp0 };
-                    self.get_dmg_color(obp, color_index)
+                    self.get_dmg_color(obp, color_index, Some(sprite.palette_index as u8))
                 };
 
                 if sprite.bg_priority {
                     // Only draw over BG color 0
                     if self.bg_color_indices[buffer_index] == 0 {
                         self.buffer[buffer_index] = out_color;
+                        self.shades[buffer_index] = dmg_shade(obp, color_index);
                     }
                 } else {
                     // Priority clear: OBJ always draws over BG
                     self.buffer[buffer_index] = out_color;
+                    self.shades[buffer_index] = dmg_shade(obp, color_index);
                 }
             }
         }
     }
 
-    fn get_dmg_color(&self, obp: u8, color_index: u8) -> u32 {
-        let color = (obp >> (color_index * 2)) & 0b11;
-        // let palette = [0xFFFFFFFF, 0xFFAAAAAA, 0xFF555555, 0xFF000000];
-        let palette = [0xFF9A9E3F, 0xFF496B22, 0xFF0E450B, 0xFF1B2A09];
-        palette[color as usize]
+    /// Maps a color index through a DMG palette register. `obj_palette` is the
+    /// OBJ palette number for sprites, `None` for the background and window.
+    fn get_dmg_color(&self, obp: u8, color_index: u8, obj_palette: Option<u8>) -> u32 {
+        let color = dmg_shade(obp, color_index);
+        if self.dmg_compat {
+            return self.get_cgb_color(obj_palette.unwrap_or(0), color, obj_palette.is_some());
+        }
+        let shades = match obj_palette {
+            None => &self.dmg_palettes.bg,
+            Some(0) => &self.dmg_palettes.obj0,
+            Some(_) => &self.dmg_palettes.obj1,
+        };
+        shades[color as usize]
     }
 
     /// Get CGB color from palette data (RGB555 format)
     fn get_cgb_color(&self, palette_num: u8, color_index: u8, is_obj: bool) -> u32 {
+        rgb555_to_argb(self.cgb_palette_rgb555(palette_num, color_index, is_obj))
+    }
+
+    /// Raw RGB555 value of `color_index` in CGB palette `palette_num`.
+    pub fn cgb_palette_rgb555(&self, palette_num: u8, color_index: u8, is_obj: bool) -> u16 {
         let palette_data = if is_obj {
             &self.obj_palette_data
         } else {
             &self.bg_palette_data
         };
-        
+
         // Each palette is 8 bytes (4 colors * 2 bytes each)
         // Each color is 2 bytes in little-endian RGB555 format
         let base_index = (palette_num * 8 + color_index * 2) as usize;
         let low = palette_data[base_index] as u16;
         let high = palette_data[base_index + 1] as u16;
-        let rgb555 = (high << 8) | low;
-        
-        // Convert RGB555 to RGB888 with proper scaling
-        // Scale 5-bit values (0-31) to 8-bit values (0-255)
-        let r5 = (rgb555 & 0x1F) as u8;
-        let g5 = ((rgb555 >> 5) & 0x1F) as u8;
-        let b5 = ((rgb555 >> 10) & 0x1F) as u8;
-        
-        // Proper scaling: (value * 255) / 31, or approximately (value << 3) | (value >> 2)
-        let r = (r5 << 3) | (r5 >> 2);
-        let g = (g5 << 3) | (g5 >> 2);
-        let b = (b5 << 3) | (b5 >> 2);
-        
-        // Return as 0xAARRGGBB
-        0xFF000000 | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
+        (high << 8) | low
     }
 }
 
+/// Shade (0-3) that DMG palette register `palette` maps `color_index` to.
+fn dmg_shade(palette: u8, color_index: u8) -> u8 {
+    (palette >> (color_index * 2)) & 0b11
+}
+
+/// Converts an RGB555 colour to 0xAARRGGBB.
+pub fn rgb555_to_argb(rgb555: u16) -> u32 {
+    // Scale 5-bit values (0-31) to 8-bit values (0-255)
+    let r5 = (rgb555 & 0x1F) as u8;
+    let g5 = ((rgb555 >> 5) & 0x1F) as u8;
+    let b5 = ((rgb555 >> 10) & 0x1F) as u8;
+
+    // Proper scaling: (value * 255) / 31, or approximately (value << 3) | (value >> 2)
+    let r = (r5 << 3) | (r5 >> 2);
+    let g = (g5 << 3) | (g5 >> 2);
+    let b = (b5 << 3) | (b5 >> 2);
+
+    // Return as 0xAARRGGBB
+    0xFF000000 | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
+}
+
 /// Converts a VRAM address to an array index
 fn vram_index(addr: u16) -> usize {
     (addr - 0x8000) as usize
@@ -4,6 +4,8 @@ pub const SPRITE_SIZE_BYTES: u8 = 4;
 pub const SPRITE_OFFSET_Y: u8 = 16;
 pub const SPRITE_OFFSET_X: u8 = 8;
 pub const SPRITE_WIDTH: u8 = 8;
+/// Sprites in OAM
+pub const OAM_SPRITES: usize = 40;
 
 /// Raw sprite data as stored in OAM
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,24 @@
+use crate::utils::bits::*;
+
+/// CGB BG map attributes, stored in VRAM bank 1 at the tile's map address
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TileAttributes {
+    pub palette: u8,
+    pub vram_bank: usize,
+    pub x_flip: bool,
+    pub y_flip: bool,
+    /// BG colours 1-3 are drawn over sprites
+    pub bg_priority: bool,
+}
+
+impl From<u8> for TileAttributes {
+    fn from(byte: u8) -> Self {
+        Self {
+            palette: byte & 0x07,
+            vram_bank: ((byte & BIT_3) >> 3) as usize,
+            x_flip: (byte & BIT_5) != 0,
+            y_flip: (byte & BIT_6) != 0,
+            bg_priority: (byte & BIT_7) != 0,
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Rewind buffer: periodic in-memory snapshots of the emulator state.
+//!
+//! Snapshots are grouped behind a keyframe. Each keyframe is a full serialized
+//! state, the snapshots after it only store the XOR with the keyframe. Both are
+//! compressed with a zero-run encoding, so the mostly unchanged memory between
+//! two snapshots costs next to nothing. When the memory budget is exceeded, the
+//! oldest group is dropped as a whole.
+
+use std::collections::VecDeque;
+use std::error::Error;
+
+use crate::emulator::cpu::CPU;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RewindConfig {
+    /// Frames between two snapshots
+    pub interval: u32,
+    /// Snapshots per keyframe, including the keyframe itself
+    pub keyframe_interval: u32,
+    /// Maximum memory used by compressed snapshots, in bytes
+    pub memory_budget: usize,
+}
+
+impl Default for RewindConfig {
+    fn default() -> Self {
+        Self {
+            interval: 2,
+            keyframe_interval: 30,
+            memory_budget: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// A keyframe and the deltas taken against it, all compressed.
+struct Group {
+    keyframe: Vec<u8>,
+    deltas: Vec<Vec<u8>>,
+}
+
+impl Group {
+    fn size(&self) -> usize {
+        self.keyframe.len() + self.deltas.iter().map(Vec::len).sum::<usize>()
+    }
+}
+
+pub struct Rewind {
+    config: RewindConfig,
+    groups: VecDeque<Group>,
+    /// Uncompressed keyframe of the newest group, used to encode new deltas
+    keyframe: Vec<u8>,
+    frames_since_snapshot: u32,
+    used_bytes: usize,
+}
+
+impl Rewind {
+    pub fn new(config: RewindConfig) -> Self {
+        Self {
+            config,
+            groups: VecDeque::new(),
+            keyframe: Vec::new(),
+            frames_since_snapshot: 0,
+            used_bytes: 0,
+        }
+    }
+
+    pub fn config(&self) -> RewindConfig {
+        self.config
+    }
+
+    /// Number of snapshots that can be rewound to.
+    pub fn len(&self) -> usize {
+        self.groups.iter().map(|group| 1 + group.deltas.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Memory used by the compressed snapshots, in bytes.
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+
+    /// Called once per completed frame; takes a snapshot every `interval` frames.
+    pub fn on_frame(&mut self, cpu: &CPU) -> Result<(), Box<dyn Error>> {
+        self.frames_since_snapshot += 1;
+        if self.frames_since_snapshot < self.config.interval.max(1) {
+            return Ok(());
+        }
+        self.frames_since_snapshot = 0;
+        self.push(cpu)
+    }
+
+    fn push(&mut self, cpu: &CPU) -> Result<(), Box<dyn Error>> {
+        let state = serialize(cpu)?;
+
+        let group_full = self.groups.back().is_none_or(|group| {
+            group.deltas.len() + 1 >= self.config.keyframe_interval.max(1) as usize
+        });
+        // The serialized size changes when e.g. the boot ROM is unmapped
+        if group_full || state.len() != self.keyframe.len() {
+            let keyframe = compress(&state);
+            self.used_bytes += keyframe.len();
+            self.groups.push_back(Group {
+                keyframe,
+                deltas: Vec::new(),
+            });
+            self.keyframe = state;
+        } else {
+            let delta = compress(&xor(&state, &self.keyframe));
+            self.used_bytes += delta.len();
+            self.groups.back_mut().unwrap().deltas.push(delta);
+        }
+
+        // Always keep the newest group, even if it alone exceeds the budget
+        while self.used_bytes > self.config.memory_budget && self.groups.len() > 1 {
+            let group = self.groups.pop_front().unwrap();
+            self.used_bytes -= group.size();
+        }
+        Ok(())
+    }
+
+    /// Removes the newest snapshot and returns the state it holds.
+    ///
+    /// The returned CPU has no ROM data, the caller restores it.
+    pub fn pop(&mut self) -> Option<Result<CPU, Box<dyn Error>>> {
+        let group = self.groups.back_mut()?;
+        self.frames_since_snapshot = 0;
+
+        let state = match group.deltas.pop() {
+            Some(delta) => {
+                self.used_bytes -= delta.len();
+                decompress(&delta).map(|delta| xor(&delta, &self.keyframe))
+            }
+            None => {
+                let group = self.groups.pop_back().unwrap();
+                self.used_bytes -= group.keyframe.len();
+                let state = decompress(&group.keyframe);
+                self.load_previous_keyframe();
+                state
+            }
+        };
+
+        Some(state.and_then(|state| deserialize(&state)))
+    }
+
+    /// Decompresses the keyframe of the newest remaining group, which its deltas are
+    /// encoded against. Groups with a corrupted keyframe can't be restored and are dropped.
+    fn load_previous_keyframe(&mut self) {
+        self.keyframe = Vec::new();
+        while let Some(previous) = self.groups.back() {
+            match decompress(&previous.keyframe) {
+                Ok(keyframe) => {
+                    self.keyframe = keyframe;
+                    return;
+                }
+                Err(e) => {
+                    eprintln!("Dropping rewind snapshots: {}", e);
+                    let group = self.groups.pop_back().unwrap();
+                    self.used_bytes -= group.size();
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.groups.clear();
+        self.keyframe.clear();
+        self.frames_since_snapshot = 0;
+        self.used_bytes = 0;
+    }
+}
+
+/// Serializes the CPU along with the framebuffer and SGB state, which it skips.
+fn serialize(cpu: &CPU) -> Result<Vec<u8>, Box<dyn Error>> {
+    Ok(bincode::serialize(&(cpu, &cpu.bus.ppu.buffer, &cpu.bus.sgb))?)
+}
+
+fn deserialize(state: &[u8]) -> Result<CPU, Box<dyn Error>> {
+    let mut reader = state;
+    let mut cpu: CPU = bincode::deserialize_from(&mut reader)?;
+    cpu.bus.ppu.buffer = bincode::deserialize_from(&mut reader)?;
+    cpu.bus.sgb = bincode::deserialize_from(&mut reader)?;
+    cpu.bus.reinit_buffers();
+    Ok(cpu)
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(a, b)| a ^ b).collect()
+}
+
+/// Minimum number of zeros that ends a literal run.
+const MIN_ZERO_RUN: usize = 4;
+
+/// Zero-run encoding: alternating (zero count, literal count, literal bytes)
+/// records, with counts stored as LEB128 varints.
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+
+    while i < data.len() {
+        let zeros = data[i..].iter().take_while(|&&byte| byte == 0).count();
+        i += zeros;
+
+        let start = i;
+        while i < data.len() {
+            let run = data[i..].iter().take(MIN_ZERO_RUN).take_while(|&&byte| byte == 0).count();
+            if run == MIN_ZERO_RUN || (run > 0 && i + run == data.len()) {
+                break;
+            }
+            i += run.max(1);
+        }
+
+        write_varint(&mut out, zeros);
+        write_varint(&mut out, i - start);
+        out.extend_from_slice(&data[start..i]);
+    }
+
+    out
+}
+
+fn decompress(mut data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut out = Vec::new();
+
+    while !data.is_empty() {
+        let zeros = read_varint(&mut data)?;
+        let literals = read_varint(&mut data)?;
+        if literals > data.len() {
+            return Err("Corrupted rewind snapshot".into());
+        }
+        out.resize(out.len() + zeros, 0);
+        out.extend_from_slice(&data[..literals]);
+        data = &data[literals..];
+    }
+
+    Ok(out)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(data: &mut &[u8]) -> Result<usize, Box<dyn Error>> {
+    let mut value = 0;
+    for shift in (0..usize::BITS).step_by(7) {
+        let (&byte, rest) = data.split_first().ok_or("Corrupted rewind snapshot")?;
+        *data = rest;
+        value |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("Corrupted rewind snapshot".into())
+}
@@ -0,0 +1,204 @@
+//! Save-state files: slots, metadata and the on-disk format.
+//!
+//! A state file starts with a fixed preamble (magic number, format version,
+//! cartridge header checksum and payload checksum), followed by the payload:
+//! a `SaveStateHeader`, the serialized `CPU` and its SGB state (kept last so
+//! adding it only took appending to older payloads). States written by older
+//! format versions are upgraded through `MIGRATIONS` before being decoded.
+
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::emulator::cart::Cart;
+use crate::emulator::cpu::CPU;
+
+const MAGIC: [u8; 4] = *b"ZBST";
+/// Current state format. Bump it (and add a migration) whenever the payload changes.
+pub const FORMAT_VERSION: u32 = 3;
+
+/// Upgrades a payload from one format version to the next.
+type Migration = fn(Vec<u8>) -> Result<Vec<u8>, Box<dyn Error>>;
+
+/// `MIGRATIONS[n]` upgrades a version `n + 1` payload to version `n + 2`.
+const MIGRATIONS: [Migration; FORMAT_VERSION as usize - 1] = [
+    // 1 -> 2: version 1 files had no preamble, the payload is unchanged
+    Ok,
+    // 2 -> 3: no SGB state
+    |mut payload| {
+        payload.push(0);
+        Ok(payload)
+    },
+];
+
+/// Number of numbered slots (0-9).
+pub const SLOT_COUNT: u8 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SaveSlot {
+    Quick,
+    Numbered(u8),
+}
+
+impl SaveSlot {
+    /// The quick-save slot followed by every numbered slot.
+    pub fn all() -> impl Iterator<Item = SaveSlot> {
+        std::iter::once(SaveSlot::Quick).chain((0..SLOT_COUNT).map(SaveSlot::Numbered))
+    }
+
+    /// Extension of the state file for this slot, next to the ROM.
+    pub fn extension(self) -> String {
+        match self {
+            SaveSlot::Quick => "ssq".to_string(),
+            SaveSlot::Numbered(slot) => format!("ss{}", slot),
+        }
+    }
+}
+
+impl fmt::Display for SaveSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveSlot::Quick => write!(f, "Quick Save"),
+            SaveSlot::Numbered(slot) => write!(f, "Slot {}", slot),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SaveStateHeader {
+    /// zetaboy version that wrote the state
+    pub version: String,
+    pub rom_title: String,
+    /// Global checksum from the cartridge header
+    pub rom_checksum: u16,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// `WIDTH * HEIGHT` pixels in 0xAARRGGBB format
+    pub thumbnail: Vec<u32>,
+}
+
+impl SaveStateHeader {
+    /// Describes the current state of `cpu`, using the last frame as thumbnail.
+    pub fn new(cpu: &CPU) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            rom_title: cpu.bus.cart.title(),
+            rom_checksum: cpu.bus.cart.global_checksum(),
+            timestamp,
+            thumbnail: cpu.bus.ppu.buffer.clone(),
+        }
+    }
+
+    /// Reads the header of the state file at `path`, without checking which ROM it belongs to.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let (_, payload) = decode_file(&std::fs::read(path)?)?;
+        Ok(bincode::deserialize(&payload)?)
+    }
+}
+
+/// Fixed-size start of a state file.
+#[derive(Deserialize, Serialize)]
+struct Preamble {
+    magic: [u8; 4],
+    version: u32,
+    /// Cartridge header checksum (0x014D) of the ROM the state was made with
+    rom_header_checksum: u8,
+    /// FNV-1a hash of the payload
+    payload_checksum: u64,
+}
+
+/// Writes a state file for the current state of `cpu`.
+pub fn write_state<W: Write>(writer: &mut W, cpu: &CPU) -> Result<(), Box<dyn Error>> {
+    let mut payload = bincode::serialize(&SaveStateHeader::new(cpu))?;
+    bincode::serialize_into(&mut payload, cpu)?;
+    bincode::serialize_into(&mut payload, &cpu.bus.sgb)?;
+
+    let preamble = Preamble {
+        magic: MAGIC,
+        version: FORMAT_VERSION,
+        rom_header_checksum: cpu.bus.cart.header_checksum(),
+        payload_checksum: checksum(&payload),
+    };
+    bincode::serialize_into(&mut *writer, &preamble)?;
+    writer.write_all(&payload)?;
+    Ok(())
+}
+
+/// Decodes a state file, rejecting it if it was made with a ROM other than `cart`.
+///
+/// The returned CPU has no ROM data or framebuffer, the caller restores them.
+pub fn read_state(bytes: &[u8], cart: &Cart) -> Result<(SaveStateHeader, CPU), Box<dyn Error>> {
+    let (rom_header_checksum, payload) = decode_file(bytes)?;
+    let mut reader = payload.as_slice();
+    let header: SaveStateHeader = bincode::deserialize_from(&mut reader)
+        .map_err(|e| format!("Invalid save state header: {}", e))?;
+
+    let same_rom = header.rom_title == cart.title()
+        && header.rom_checksum == cart.global_checksum()
+        && rom_header_checksum.is_none_or(|checksum| checksum == cart.header_checksum());
+    if !same_rom {
+        return Err(format!(
+            "This state was made with \"{}\" (checksum {:04X}), not \"{}\" (checksum {:04X})",
+            header.rom_title,
+            header.rom_checksum,
+            cart.title(),
+            cart.global_checksum()
+        )
+        .into());
+    }
+
+    let mut cpu: CPU = bincode::deserialize_from(&mut reader)
+        .map_err(|e| format!("Invalid save state data: {}", e))?;
+    cpu.bus.sgb = bincode::deserialize_from(&mut reader)
+        .map_err(|e| format!("Invalid save state data: {}", e))?;
+    Ok((header, cpu))
+}
+
+/// Checks the preamble and upgrades the payload to the current format.
+///
+/// Returns the cartridge header checksum (unknown for version 1 files) and the payload.
+fn decode_file(bytes: &[u8]) -> Result<(Option<u8>, Vec<u8>), Box<dyn Error>> {
+    let (version, rom_header_checksum, payload) = if bytes.starts_with(&MAGIC) {
+        let mut reader = bytes;
+        let preamble: Preamble = bincode::deserialize_from(&mut reader)
+            .map_err(|_| "Save state is truncated")?;
+
+        if preamble.version == 0 || preamble.version > FORMAT_VERSION {
+            return Err(format!(
+                "Unsupported save state format version {} (this build supports up to {})",
+                preamble.version, FORMAT_VERSION
+            )
+            .into());
+        }
+        if checksum(reader) != preamble.payload_checksum {
+            return Err("Save state is corrupted (checksum mismatch)".into());
+        }
+        (preamble.version, Some(preamble.rom_header_checksum), reader.to_vec())
+    } else {
+        (1, None, bytes.to_vec())
+    };
+
+    let payload = MIGRATIONS[version as usize - 1..]
+        .iter()
+        .try_fold(payload, |payload, migrate| migrate(payload))?;
+    Ok((rom_header_checksum, payload))
+}
+
+/// 64-bit FNV-1a hash.
+fn checksum(data: &[u8]) -> u64 {
+    const FNV_OFFSET: u64 = 0xCBF29CE484222325;
+    const FNV_PRIME: u64 = 0x100000001B3;
+
+    data.iter().fold(FNV_OFFSET, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
@@ -1,29 +1,121 @@
+//! Serial port (SB/SC) and the devices that can be plugged into it.
+
+mod link;
+mod printer;
+
+use std::sync::{Arc, Mutex};
+
 use serde::{Deserialize, Serialize};
 
+pub use link::LinkCable;
+pub use printer::{PRINT_WIDTH, Printer, Printout};
+
 // Serial transfer takes 8192 cycles (512 cycles per bit * 8 bits)
 // at 4.194304 MHz clock speed
 const SERIAL_TRANSFER_CYCLES: u16 = 512;
+// Maximum number of bytes or transfers kept for `take_output` and `take_transfers`
+const CAPTURE_CAPACITY: usize = 0x10000;
+
+/// Something at the other end of the serial port, such as another Game Boy.
+pub trait SerialDevice: Send {
+    /// The Game Boy started clocking `byte` out with its internal clock. Returns
+    /// the byte shifted in at the same time, or `None` if it isn't known yet:
+    /// after the 8 bits, the transfer then waits for `reply`.
+    fn transfer(&mut self, byte: u8) -> Option<u8>;
+
+    /// The answer to the last `transfer`, once it has arrived.
+    fn reply(&mut self) -> Option<u8> {
+        None
+    }
+
+    /// Called once per bit time (`SERIAL_TRANSFER_CYCLES`), transferring or not,
+    /// for devices that keep time with the Game Boy.
+    fn clock(&mut self) {}
+
+    /// A byte the device clocked in, if it drives the clock itself. `sb` is
+    /// shifted out to it in exchange. Only called while the Game Boy waits for
+    /// an external-clock transfer; bytes sent before that stay queued.
+    fn external_transfer(&mut self, _sb: u8) -> Option<u8> {
+        None
+    }
+}
+
+/// A device shared between the serial port and whoever else looks at it.
+pub type SharedDevice = Arc<Mutex<dyn SerialDevice>>;
+
+/// A completed transfer: one byte out and one in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SerialTransfer {
+    pub sent: u8,
+    pub received: u8,
+    /// Clocked by this Game Boy, rather than the other side
+    pub internal_clock: bool,
+}
 
 #[derive(Clone, Deserialize, Serialize)]
 pub struct Serial {
     sb: u8,                // Serial transfer data (0xFF01)
     sc: u8,                // Serial transfer control (0xFF02)
-    print_serial: bool,    // Whether to print serial output to console
+    print_serial: bool,    // Whether to print sent bytes to stdout
     transfer_counter: u16, // Counter for serial transfer timing
     bits_transferred: u8,  // Number of bits transferred (0-8)
+    #[serde(skip)]
+    clock_counter: u16, // Counter for `SerialDevice::clock`, not reset by transfers
+    #[serde(skip)]
+    output: Vec<u8>, // Bytes sent, not taken yet
+    #[serde(skip)]
+    transfers: Vec<SerialTransfer>, // Transfers not taken yet
+    #[serde(skip)]
+    device: Option<SharedDevice>, // Nothing connected if None
+    #[serde(skip)]
+    awaiting_reply: bool, // The byte was sent to the device, waiting for its answer
+    #[serde(skip)]
+    received: Option<u8>, // The device's answer, kept until the 8 bits are shifted
 }
 
 impl Serial {
-    pub fn new(print_serial: bool) -> Self {
+    pub fn new() -> Self {
         Self {
             sb: 0,
             sc: 0,
-            print_serial,
+            print_serial: false,
             transfer_counter: 0,
             bits_transferred: 0,
+            clock_counter: 0,
+            output: Vec::new(),
+            transfers: Vec::new(),
+            device: None,
+            awaiting_reply: false,
+            received: None,
         }
     }
 
+    /// Plugs `device` into the port, or unplugs it if `None`.
+    pub fn set_device(&mut self, device: Option<SharedDevice>) {
+        self.device = device;
+        self.awaiting_reply = false;
+        self.received = None;
+    }
+
+    pub fn device(&self) -> Option<&SharedDevice> {
+        self.device.as_ref()
+    }
+
+    /// Prints every byte sent to stdout as a character, for test ROMs.
+    pub fn set_print_output(&mut self, print: bool) {
+        self.print_serial = print;
+    }
+
+    /// Takes every byte sent since the last call.
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Takes every transfer completed since the last call, independently of `take_output`.
+    pub fn take_transfers(&mut self) -> Vec<SerialTransfer> {
+        std::mem::take(&mut self.transfers)
+    }
+
     pub fn read_sb(&self) -> u8 {
         self.sb
     }
@@ -45,47 +137,122 @@ impl Serial {
             // Start a new transfer
             self.transfer_counter = 0;
             self.bits_transferred = 0;
+            self.send();
         } else if was_transferring && value & 0x80 == 0 {
             // Transfer was cancelled
             self.transfer_counter = 0;
             self.bits_transferred = 0;
+            self.awaiting_reply = false;
+            self.received = None;
         }
     }
 
     /// Tick the serial transfer. Returns true if transfer completed and interrupt should be requested.
     pub fn tick(&mut self) -> bool {
-        // Check if transfer is active (bit 7) and using internal clock (bit 0)
-        if self.sc & 0x81 != 0x81 {
-            return false;
+        self.clock_counter += 1;
+        if self.clock_counter == SERIAL_TRANSFER_CYCLES {
+            self.clock_counter = 0;
+            if let Some(device) = &self.device {
+                device.lock().unwrap().clock();
+            }
         }
 
         self.transfer_counter += 1;
+        // The device is checked once per bit time
+        if self.transfer_counter < SERIAL_TRANSFER_CYCLES {
+            return false;
+        }
+        self.transfer_counter = 0;
+
+        // Check if transfer is active (bit 7) and using internal clock (bit 0)
+        if self.sc & 0x81 != 0x81 {
+            return self.poll_external_clock();
+        }
 
         // Each bit takes 512 cycles to transfer
-        if self.transfer_counter >= SERIAL_TRANSFER_CYCLES {
-            self.transfer_counter = 0;
+        if self.bits_transferred < 8 {
             self.bits_transferred += 1;
+            if self.bits_transferred < 8 {
+                return false;
+            }
+        }
 
-            // After 8 bits, transfer is complete
-            if self.bits_transferred >= 8 {
-                // Print the character if enabled
-                if self.print_serial {
-                    print!("{}", self.sb as char);
-                }
+        // After 8 bits, transfer is complete once the device has answered
+        if self.received.is_none() && !self.awaiting_reply {
+            // Plugged in (or state loaded) after the transfer started
+            self.send();
+        }
+        if self.awaiting_reply {
+            self.received = self.poll_reply();
+        }
+        match self.received.take() {
+            Some(byte) => self.complete(byte),
+            None => false,
+        }
+    }
 
-                // Transfer complete: clear bit 7 and reset counters
-                self.sc &= 0x7F;
-                self.bits_transferred = 0;
-                self.transfer_counter = 0;
+    /// Sends SB to the device as an internal-clock transfer starts.
+    fn send(&mut self) {
+        // When no external device is connected, shift in 0xFF
+        self.received = match &self.device {
+            Some(device) => device.lock().unwrap().transfer(self.sb),
+            None => Some(0xFF),
+        };
+        self.awaiting_reply = self.received.is_none();
+    }
 
-                // When no external device is connected, shift in 0xFF
-                self.sb = 0xFF;
+    /// Checks whether the device answered the byte sent by an internal-clock transfer.
+    fn poll_reply(&self) -> Option<u8> {
+        match &self.device {
+            Some(device) => device.lock().unwrap().reply(),
+            // Unplugged while waiting
+            None => Some(0xFF),
+        }
+    }
 
-                // Request serial interrupt
-                return true;
-            }
+    /// Lets the device clock a byte in, while an external-clock transfer is active.
+    fn poll_external_clock(&mut self) -> bool {
+        if self.sc & 0x81 != 0x80 {
+            return false;
         }
+        let Some(device) = &self.device else {
+            return false;
+        };
+        let received = device.lock().unwrap().external_transfer(self.sb);
+        received.is_some_and(|byte| self.complete(byte))
+    }
+
+    /// Ends the transfer with `received` in SB. Always true, for the serial interrupt.
+    fn complete(&mut self, received: u8) -> bool {
+        // Print the character if enabled
+        if self.print_serial {
+            print!("{}", self.sb as char);
+        }
+        if self.output.len() >= CAPTURE_CAPACITY {
+            self.output.drain(..CAPTURE_CAPACITY / 2);
+        }
+        self.output.push(self.sb);
+        if self.transfers.len() >= CAPTURE_CAPACITY {
+            self.transfers.drain(..CAPTURE_CAPACITY / 2);
+        }
+        self.transfers.push(SerialTransfer {
+            sent: self.sb,
+            received,
+            internal_clock: self.sc & 0x01 != 0,
+        });
+
+        // Transfer complete: clear bit 7 and reset counters
+        self.sc &= 0x7F;
+        self.bits_transferred = 0;
+        self.transfer_counter = 0;
+        self.awaiting_reply = false;
+        self.sb = received;
+        true
+    }
+}
 
-        false
+impl Default for Serial {
+    fn default() -> Self {
+        Self::new()
     }
 }
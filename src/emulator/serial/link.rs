@@ -0,0 +1,304 @@
+//! Link cable to another emulator, in the same process or over a socket.
+//!
+//! When a Game Boy starts a transfer with its internal clock, its end sends a
+//! `TRANSFER` message and the other end answers with its own SB in a `REPLY`.
+//! If both sides start a transfer at once, each takes the other's `TRANSFER`
+//! as the reply.
+//!
+//! Each end counts bit times (512 T-cycles) in `clock`, stamps its messages
+//! with that time and sends a `SYNC` every `SYNC_INTERVAL` bit times. A message
+//! stamped `t` is handled at the other end's time `t + LATENCY`, once that end
+//! has heard from this one past `t`: during a transfer, an end that gets ahead
+//! waits for the other. Transfers therefore happen at the same emulated time
+//! however fast each emulator runs, in one process or two.
+//! `Emulator::run_frame_linked` runs two emulators in one process in lockstep.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+
+use super::SerialDevice;
+
+const TRANSFER: u8 = 0x01;
+const REPLY: u8 = 0x02;
+const SYNC: u8 = 0x03;
+
+/// Bit times between `SYNC` messages
+const SYNC_INTERVAL: u64 = 4;
+/// Bit times between sending a message and the other end handling it. Longer
+/// than `SYNC_INTERVAL`, so that emulators run in lockstep never wait.
+const LATENCY: u64 = SYNC_INTERVAL + 1;
+
+/// Prefix of socket addresses naming a Unix socket rather than a TCP `host:port`
+const UNIX_PREFIX: &str = "unix:";
+
+/// Kind, data byte and the sender's time, as sent over a socket
+type Encoded = [u8; 10];
+
+#[derive(Clone, Copy)]
+struct Message {
+    kind: u8,
+    byte: u8,
+    /// Bit times clocked by the sender when it sent the message
+    time: u64,
+}
+
+impl Message {
+    fn encode(&self) -> Encoded {
+        let mut bytes = [0; 10];
+        bytes[0] = self.kind;
+        bytes[1] = self.byte;
+        bytes[2..].copy_from_slice(&self.time.to_le_bytes());
+        bytes
+    }
+
+    fn decode(bytes: &Encoded) -> Self {
+        let mut time = [0; 8];
+        time.copy_from_slice(&bytes[2..]);
+        Self {
+            kind: bytes[0],
+            byte: bytes[1],
+            time: u64::from_le_bytes(time),
+        }
+    }
+}
+
+enum Outgoing {
+    Channel(Sender<Message>),
+    Stream(Box<dyn Write + Send>),
+}
+
+/// One end of a link cable.
+pub struct LinkCable {
+    outgoing: Outgoing,
+    incoming: Receiver<Message>,
+    /// Bit times clocked at this end
+    time: u64,
+    /// Time of the last message from the other end. Everything it sent
+    /// earlier has arrived.
+    peer_time: Option<u64>,
+    /// Transfers and replies received, not handled yet
+    pending: VecDeque<Message>,
+    /// Bytes clocked out by the other side, not answered yet
+    transfers: VecDeque<u8>,
+    /// Waiting for the answer to our own transfer
+    awaiting: bool,
+    reply: Option<u8>,
+    connected: bool,
+}
+
+impl LinkCable {
+    /// Both ends of a cable, for two emulators in the same process.
+    pub fn pair() -> (Self, Self) {
+        let (first_tx, first_rx) = mpsc::channel();
+        let (second_tx, second_rx) = mpsc::channel();
+        (
+            Self::new(Outgoing::Channel(first_tx), second_rx),
+            Self::new(Outgoing::Channel(second_tx), first_rx),
+        )
+    }
+
+    /// Waits for the other emulator to connect to `address`, either `host:port`
+    /// or `unix:<path>`.
+    pub fn listen(address: &str) -> Result<Self, Box<dyn Error>> {
+        let error = |e| format!("Failed to listen on {}: {}", address, e);
+        if let Some(path) = address.strip_prefix(UNIX_PREFIX) {
+            return listen_unix(path).map_err(|e| error(e).into());
+        }
+
+        let listener = TcpListener::bind(address).map_err(error)?;
+        let (stream, _) = listener.accept().map_err(error)?;
+        stream.set_nodelay(true)?;
+        Ok(Self::from_stream(stream.try_clone()?, stream))
+    }
+
+    /// Connects to an emulator listening on `address`, see [`LinkCable::listen`].
+    pub fn connect(address: &str) -> Result<Self, Box<dyn Error>> {
+        let error = |e| format!("Failed to connect to {}: {}", address, e);
+        if let Some(path) = address.strip_prefix(UNIX_PREFIX) {
+            return connect_unix(path).map_err(|e| error(e).into());
+        }
+
+        let stream = TcpStream::connect(address).map_err(error)?;
+        stream.set_nodelay(true)?;
+        Ok(Self::from_stream(stream.try_clone()?, stream))
+    }
+
+    fn new(outgoing: Outgoing, incoming: Receiver<Message>) -> Self {
+        Self {
+            outgoing,
+            incoming,
+            time: 0,
+            peer_time: None,
+            pending: VecDeque::new(),
+            transfers: VecDeque::new(),
+            awaiting: false,
+            reply: None,
+            connected: true,
+        }
+    }
+
+    /// Reads messages on a separate thread, so polling never blocks.
+    fn from_stream<R, W>(mut reader: R, writer: W) -> Self
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut message = [0; 10];
+            while reader.read_exact(&mut message).is_ok()
+                && sender.send(Message::decode(&message)).is_ok()
+            {}
+        });
+        Self::new(Outgoing::Stream(Box::new(writer)), receiver)
+    }
+
+    /// False once the other side has gone away. The cable then acts as if unplugged.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    fn send(&mut self, kind: u8, byte: u8) {
+        if !self.connected {
+            return;
+        }
+        let message = Message {
+            kind,
+            byte,
+            time: self.time,
+        };
+        let sent = match &mut self.outgoing {
+            Outgoing::Channel(sender) => sender.send(message).is_ok(),
+            Outgoing::Stream(writer) => writer.write_all(&message.encode()).is_ok(),
+        };
+        if !sent {
+            self.disconnect();
+        }
+    }
+
+    /// Takes the messages that have arrived, without waiting.
+    fn receive(&mut self) {
+        loop {
+            match self.incoming.try_recv() {
+                Ok(message) => self.queue(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.disconnect();
+                    break;
+                }
+            }
+        }
+    }
+
+    fn queue(&mut self, message: Message) {
+        self.peer_time = Some(message.time);
+        if matches!(message.kind, TRANSFER | REPLY) {
+            self.pending.push_back(message);
+        }
+    }
+
+    /// Handles the messages due by now, first waiting for the other end to
+    /// get far enough for all of them to have arrived.
+    fn catch_up(&mut self) {
+        self.receive();
+        let Some(due) = self.time.checked_sub(LATENCY) else {
+            return;
+        };
+        while self.connected && self.peer_time.is_none_or(|time| time <= due) {
+            match self.incoming.recv() {
+                Ok(message) => self.queue(message),
+                Err(_) => self.disconnect(),
+            }
+        }
+
+        while self
+            .pending
+            .front()
+            .is_some_and(|message| message.time <= due)
+        {
+            let Some(message) = self.pending.pop_front() else {
+                break;
+            };
+            match message.kind {
+                TRANSFER if !self.awaiting => self.transfers.push_back(message.byte),
+                _ => self.reply = Some(message.byte),
+            }
+        }
+    }
+
+    fn disconnect(&mut self) {
+        if self.connected {
+            self.connected = false;
+            eprintln!("Link cable disconnected");
+        }
+    }
+}
+
+impl SerialDevice for LinkCable {
+    fn transfer(&mut self, byte: u8) -> Option<u8> {
+        self.reply = None;
+        self.awaiting = true;
+        self.send(TRANSFER, byte);
+        // Nothing at the other end: the line stays high
+        (!self.connected).then_some(0xFF)
+    }
+
+    fn reply(&mut self) -> Option<u8> {
+        self.catch_up();
+        let reply = self.reply.take().or((!self.connected).then_some(0xFF));
+        if reply.is_some() {
+            self.awaiting = false;
+        }
+        reply
+    }
+
+    fn clock(&mut self) {
+        self.time += 1;
+        if self.time.is_multiple_of(SYNC_INTERVAL) {
+            self.send(SYNC, 0);
+        }
+        self.receive();
+    }
+
+    fn external_transfer(&mut self, sb: u8) -> Option<u8> {
+        self.catch_up();
+        let byte = self.transfers.pop_front()?;
+        self.send(REPLY, sb);
+        Some(byte)
+    }
+}
+
+#[cfg(unix)]
+fn listen_unix(path: &str) -> std::io::Result<LinkCable> {
+    let listener = UnixListener::bind(path)?;
+    let accepted = listener.accept();
+    // Nobody else can connect to it any more
+    let _ = std::fs::remove_file(path);
+    let (stream, _) = accepted?;
+    Ok(LinkCable::from_stream(stream.try_clone()?, stream))
+}
+
+#[cfg(unix)]
+fn connect_unix(path: &str) -> std::io::Result<LinkCable> {
+    let stream = UnixStream::connect(path)?;
+    Ok(LinkCable::from_stream(stream.try_clone()?, stream))
+}
+
+#[cfg(not(unix))]
+fn listen_unix(_path: &str) -> std::io::Result<LinkCable> {
+    Err(std::io::Error::other(
+        "Unix sockets are not supported on this platform",
+    ))
+}
+
+#[cfg(not(unix))]
+fn connect_unix(_path: &str) -> std::io::Result<LinkCable> {
+    Err(std::io::Error::other(
+        "Unix sockets are not supported on this platform",
+    ))
+}
@@ -0,0 +1,323 @@
+//! Game Boy Printer.
+//!
+//! The Game Boy sends packets of: magic bytes 0x88 0x33, command, compression
+//! flag, data length (little endian), data, checksum (sum of the command to
+//! the last data byte, little endian), then two 0x00 bytes. The printer
+//! answers the first with 0x81 and the second with its status.
+//!
+//! DATA packets hold 2bpp tiles, 20 per row, optionally RLE compressed. PRINT
+//! prints them through the palette it is given; successive prints without a
+//! feed after them go on the same page.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use super::SerialDevice;
+use crate::screenshot;
+
+const MAGIC: [u8; 2] = [0x88, 0x33];
+const ALIVE: u8 = 0x81;
+
+const INIT: u8 = 0x01;
+const PRINT: u8 = 0x02;
+const DATA: u8 = 0x04;
+const STATUS: u8 = 0x0F;
+
+/// Status bits
+const CHECKSUM_ERROR: u8 = 0x01;
+const BUSY: u8 = 0x02;
+const IMAGE_FULL: u8 = 0x04;
+const UNPROCESSED: u8 = 0x08;
+const PACKET_ERROR: u8 = 0x10;
+
+/// Packets for which the printer stays busy after PRINT
+const BUSY_PACKETS: u8 = 4;
+
+pub const PRINT_WIDTH: usize = 160;
+const TILES_PER_ROW: usize = PRINT_WIDTH / 8;
+const TILE_SIZE: usize = 16;
+/// The printer's buffer holds one 160x144 image
+const IMAGE_CAPACITY: usize = PRINT_WIDTH * 144 / 4;
+
+/// Paper colours of the four shades
+const SHADES: [u32; 4] = [0xFFFFFFFF, 0xFFAAAAAA, 0xFF555555, 0xFF000000];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Stage {
+    Magic(usize),
+    Command,
+    Compression,
+    Length(usize),
+    Data,
+    Checksum(usize),
+    Alive,
+    Status,
+}
+
+/// A printed page, `PRINT_WIDTH` pixels wide.
+#[derive(Clone, Debug, Default)]
+pub struct Printout {
+    /// 0xAARRGGBB pixels
+    pub pixels: Vec<u32>,
+    /// Where the page was saved, if it was
+    pub path: Option<PathBuf>,
+}
+
+impl Printout {
+    pub fn height(&self) -> usize {
+        self.pixels.len() / PRINT_WIDTH
+    }
+}
+
+pub struct Printer {
+    stage: Stage,
+    command: u8,
+    compressed: bool,
+    length: u16,
+    data: Vec<u8>,
+    /// Sum of the packet bytes from the command on
+    sum: u16,
+    checksum: u16,
+    status: u8,
+    busy_packets: u8,
+    /// Tile data received since the last print
+    image: Vec<u8>,
+    page: Printout,
+    pages: Vec<Printout>,
+    /// Finished pages are saved as `<save_base>-<n>.png`
+    save_base: Option<PathBuf>,
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Self {
+            stage: Stage::Magic(0),
+            command: 0,
+            compressed: false,
+            length: 0,
+            data: Vec::new(),
+            sum: 0,
+            checksum: 0,
+            status: 0,
+            busy_packets: 0,
+            image: Vec::new(),
+            page: Printout::default(),
+            pages: Vec::new(),
+            save_base: None,
+        }
+    }
+
+    /// Saves every finished page as `<base>-<n>.png`, skipping numbers already
+    /// in use. Pages are only kept in memory if `None`.
+    pub fn save_to(&mut self, base: Option<PathBuf>) {
+        self.save_base = base;
+    }
+
+    /// Pages printed so far, oldest first.
+    pub fn pages(&self) -> &[Printout] {
+        &self.pages
+    }
+
+    /// The page being printed, empty if none.
+    pub fn current_page(&self) -> &Printout {
+        &self.page
+    }
+
+    /// Ends the current page as if the paper was fed and torn off.
+    pub fn finish_page(&mut self) {
+        if self.page.pixels.is_empty() {
+            return;
+        }
+        let mut page = std::mem::take(&mut self.page);
+        if let Some(base) = &self.save_base {
+            match save_page(base, &page) {
+                Ok(path) => {
+                    println!("Saved print-out to {}", path.display());
+                    page.path = Some(path);
+                }
+                Err(e) => eprintln!("Failed to save print-out: {}", e),
+            }
+        }
+        self.pages.push(page);
+    }
+
+    /// Takes in a byte from the Game Boy, returning the byte sent back.
+    fn receive(&mut self, byte: u8) -> u8 {
+        if matches!(
+            self.stage,
+            Stage::Command | Stage::Compression | Stage::Length(_) | Stage::Data
+        ) {
+            self.sum = self.sum.wrapping_add(byte as u16);
+        }
+
+        let mut response = 0x00;
+        self.stage = match self.stage {
+            Stage::Magic(i) if byte == MAGIC[i] => {
+                if i + 1 < MAGIC.len() {
+                    Stage::Magic(i + 1)
+                } else {
+                    self.sum = 0;
+                    Stage::Command
+                }
+            }
+            Stage::Magic(_) => Stage::Magic(usize::from(byte == MAGIC[0])),
+            Stage::Command => {
+                self.command = byte;
+                Stage::Compression
+            }
+            Stage::Compression => {
+                self.compressed = byte & 1 != 0;
+                Stage::Length(0)
+            }
+            Stage::Length(0) => {
+                self.length = byte as u16;
+                Stage::Length(1)
+            }
+            Stage::Length(_) => {
+                self.length |= (byte as u16) << 8;
+                self.data.clear();
+                if self.length == 0 {
+                    Stage::Checksum(0)
+                } else {
+                    Stage::Data
+                }
+            }
+            Stage::Data => {
+                self.data.push(byte);
+                if self.data.len() < self.length as usize {
+                    Stage::Data
+                } else {
+                    Stage::Checksum(0)
+                }
+            }
+            Stage::Checksum(0) => {
+                self.checksum = byte as u16;
+                Stage::Checksum(1)
+            }
+            Stage::Checksum(_) => {
+                self.checksum |= (byte as u16) << 8;
+                Stage::Alive
+            }
+            Stage::Alive => {
+                response = ALIVE;
+                Stage::Status
+            }
+            Stage::Status => {
+                self.execute(self.checksum == self.sum);
+                response = self.status;
+                Stage::Magic(0)
+            }
+        };
+        response
+    }
+
+    fn execute(&mut self, checksum_ok: bool) {
+        if !checksum_ok {
+            self.status |= CHECKSUM_ERROR;
+            return;
+        }
+        self.status &= !(CHECKSUM_ERROR | PACKET_ERROR);
+
+        if self.busy_packets > 0 {
+            self.busy_packets -= 1;
+            if self.busy_packets == 0 {
+                self.status &= !BUSY;
+            }
+        }
+
+        match self.command {
+            INIT => {
+                self.image.clear();
+                self.status = 0;
+                self.busy_packets = 0;
+            }
+            PRINT if self.data.len() >= 4 => {
+                let [sheets, margins, palette, _exposure] =
+                    [self.data[0], self.data[1], self.data[2], self.data[3]];
+                self.print(palette);
+                self.status = (self.status | BUSY) & !(UNPROCESSED | IMAGE_FULL);
+                self.busy_packets = BUSY_PACKETS;
+                // A feed after the image, or printing no sheet at all, ends the page
+                if sheets == 0 || margins & 0x0F != 0 {
+                    self.finish_page();
+                }
+            }
+            DATA => {
+                let data = if self.compressed {
+                    decompress(&self.data)
+                } else {
+                    std::mem::take(&mut self.data)
+                };
+                let space = IMAGE_CAPACITY - self.image.len();
+                self.image.extend_from_slice(&data[..data.len().min(space)]);
+                if !data.is_empty() {
+                    self.status |= UNPROCESSED;
+                }
+                if self.image.len() == IMAGE_CAPACITY {
+                    self.status |= IMAGE_FULL;
+                }
+            }
+            STATUS => {}
+            _ => self.status |= PACKET_ERROR,
+        }
+    }
+
+    /// Adds the received tiles to the page, through `palette` (laid out like BGP).
+    fn print(&mut self, palette: u8) {
+        // 0x00 is treated like the usual 0xE4
+        let palette = if palette == 0 { 0xE4 } else { palette };
+        let rows = self.image.len() / (TILES_PER_ROW * TILE_SIZE);
+        for y in 0..rows * 8 {
+            for x in 0..PRINT_WIDTH {
+                let tile = (y / 8) * TILES_PER_ROW + x / 8;
+                let offset = tile * TILE_SIZE + (y % 8) * 2;
+                let bit = 7 - (x % 8);
+                let low = (self.image[offset] >> bit) & 1;
+                let high = (self.image[offset + 1] >> bit) & 1;
+                let color = (high << 1) | low;
+                let shade = (palette >> (color * 2)) & 0b11;
+                self.page.pixels.push(SHADES[shade as usize]);
+            }
+        }
+        self.image.clear();
+    }
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SerialDevice for Printer {
+    fn transfer(&mut self, byte: u8) -> Option<u8> {
+        Some(self.receive(byte))
+    }
+}
+
+/// Expands the printer's RLE: a control byte with bit 7 set repeats the next
+/// byte `(control & 0x7F) + 2` times, otherwise `control + 1` literal bytes follow.
+fn decompress(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut bytes = data.iter();
+    while let Some(&control) = bytes.next() {
+        if control & 0x80 != 0 {
+            let Some(&byte) = bytes.next() else { break };
+            let count = (control & 0x7F) as usize + 2;
+            output.extend(std::iter::repeat_n(byte, count));
+        } else {
+            output.extend(bytes.by_ref().take(control as usize + 1));
+        }
+    }
+    output
+}
+
+fn save_page(base: &Path, page: &Printout) -> Result<PathBuf, Box<dyn Error>> {
+    let name = base.file_name().unwrap_or_default().to_string_lossy();
+    let path = (1..)
+        .map(|n| base.with_file_name(format!("{}-{}.png", name, n)))
+        .find(|path| !path.exists())
+        .ok_or("No free file name")?;
+    screenshot::save_png(&path, &page.pixels, PRINT_WIDTH, page.height())?;
+    Ok(path)
+}
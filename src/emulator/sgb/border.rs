@@ -0,0 +1,91 @@
+//! The 256x224 picture frame around the Game Boy screen, loaded with CHR_TRN and PCT_TRN.
+
+use serde::{Deserialize, Serialize};
+
+use super::{SGB_HEIGHT, SGB_WIDTH};
+use crate::emulator::ppu::rgb555_to_argb;
+
+/// 4bpp SNES tiles, 32 bytes each
+const TILE_SIZE: usize = 32;
+const TILES: usize = 256;
+const MAP_COLUMNS: usize = 32;
+const MAP_ROWS: usize = SGB_HEIGHT / 8;
+/// Size of the map in a PCT_TRN transfer (32x32 entries), followed by the palettes
+const MAP_SIZE: usize = 0x800;
+const PALETTES: usize = 4;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Border {
+    tiles: Vec<u8>,
+    /// SNES tile map entries: tile number, palette (4-7) and flips
+    map: Vec<u16>,
+    /// Border palettes 4-7 as RGB555, colour 0 is transparent
+    palettes: [[u16; 16]; PALETTES],
+}
+
+impl Default for Border {
+    fn default() -> Self {
+        Self {
+            tiles: vec![0; TILES * TILE_SIZE],
+            map: vec![0; MAP_COLUMNS * MAP_COLUMNS],
+            palettes: [[0; 16]; PALETTES],
+        }
+    }
+}
+
+impl Border {
+    /// CHR_TRN: tiles 0x00-0x7F, or 0x80-0xFF if `upper`.
+    pub fn set_tiles(&mut self, upper: bool, data: &[u8]) {
+        let start = if upper { TILES / 2 * TILE_SIZE } else { 0 };
+        let tiles = &mut self.tiles[start..start + TILES / 2 * TILE_SIZE];
+        tiles.copy_from_slice(&data[..tiles.len()]);
+    }
+
+    /// PCT_TRN: the tile map, then the colours of palettes 4-7.
+    pub fn set_map(&mut self, data: &[u8]) {
+        for (entry, bytes) in self.map.iter_mut().zip(data[..MAP_SIZE].chunks_exact(2)) {
+            *entry = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+        let colors = data[MAP_SIZE..].chunks_exact(2);
+        for (color, bytes) in self.palettes.as_flattened_mut().iter_mut().zip(colors) {
+            *color = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+    }
+
+    /// Draws the opaque border pixels over `frame` (`SGB_WIDTH * SGB_HEIGHT`).
+    pub fn draw(&self, frame: &mut [u32]) {
+        for row in 0..MAP_ROWS {
+            for column in 0..MAP_COLUMNS {
+                let entry = self.map[row * MAP_COLUMNS + column];
+                let tile = &self.tiles[(entry & 0xFF) as usize * TILE_SIZE..][..TILE_SIZE];
+                let palette = &self.palettes[((entry >> 10) & 0b11) as usize];
+                let x_flip = entry & 0x4000 != 0;
+                let y_flip = entry & 0x8000 != 0;
+
+                for y in 0..8 {
+                    let tile_y = if y_flip { 7 - y } else { y };
+                    for x in 0..8 {
+                        let tile_x = if x_flip { 7 - x } else { x };
+                        let color = tile_pixel(tile, tile_x, tile_y);
+                        if color != 0 {
+                            let index = (row * 8 + y) * SGB_WIDTH + column * 8 + x;
+                            frame[index] = rgb555_to_argb(palette[color]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Colour index (0-15) of a pixel in a 4bpp tile: bitplanes 0 and 1 interleaved
+/// by row, followed by bitplanes 2 and 3.
+fn tile_pixel(tile: &[u8], x: usize, y: usize) -> usize {
+    let bit = 7 - x;
+    let planes = [y * 2, y * 2 + 1, 16 + y * 2, 16 + y * 2 + 1];
+    planes
+        .iter()
+        .enumerate()
+        .map(|(plane, &offset)| (((tile[offset] >> bit) & 1) as usize) << plane)
+        .sum()
+}
@@ -0,0 +1,443 @@
+//! Super Game Boy functions: colourisation, borders and multiplayer.
+//!
+//! Games control the SGB with command packets sent through P1. Bulk data
+//! (palettes, border tiles and map, attribute files) follows a command as a
+//! frame of screen contents, which the SGB reads back from the LCD output.
+//! The Game Boy screen is shown at the centre of a 256x224 frame.
+
+mod border;
+mod packet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::emulator::ppu::{HEIGHT, PPU, WIDTH, rgb555_to_argb};
+use border::Border;
+use packet::PacketReader;
+
+pub const SGB_WIDTH: usize = 256;
+pub const SGB_HEIGHT: usize = 224;
+
+/// Top-left corner of the Game Boy screen in the SGB frame
+const SCREEN_X: usize = (SGB_WIDTH - WIDTH) / 2;
+const SCREEN_Y: usize = (SGB_HEIGHT - HEIGHT) / 2;
+
+/// Palettes are assigned per 8x8 block of the screen
+const ATTR_COLUMNS: usize = WIDTH / 8;
+const ATTR_ROWS: usize = HEIGHT / 8;
+
+/// Bytes read from the screen by a VRAM transfer (256 tiles)
+const TRANSFER_SIZE: usize = 0x1000;
+/// 2bpp tiles of a VRAM transfer
+const TILE_SIZE: usize = 16;
+const SYSTEM_PALETTES: usize = 512;
+const ATTR_FILES: usize = 45;
+/// 4 blocks per byte
+const ATTR_FILE_SIZE: usize = ATTR_COLUMNS * ATTR_ROWS / 4;
+
+/// Palette 1-A of the SGB, used until the game sets its own
+const DEFAULT_PALETTE: [u16; 4] = [0x67BF, 0x265B, 0x10B5, 0x2866];
+
+/// Command codes, from the upper 5 bits of the first packet byte
+const PAL01: u8 = 0x00;
+const PAL23: u8 = 0x01;
+const PAL03: u8 = 0x02;
+const PAL12: u8 = 0x03;
+const ATTR_BLK: u8 = 0x04;
+const ATTR_LIN: u8 = 0x05;
+const ATTR_DIV: u8 = 0x06;
+const ATTR_CHR: u8 = 0x07;
+const PAL_SET: u8 = 0x0A;
+const PAL_TRN: u8 = 0x0B;
+const MLT_REQ: u8 = 0x11;
+const CHR_TRN: u8 = 0x13;
+const PCT_TRN: u8 = 0x14;
+const ATTR_TRN: u8 = 0x15;
+const ATTR_SET: u8 = 0x16;
+const MASK_EN: u8 = 0x17;
+
+/// What MASK_EN shows instead of the game screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Mask {
+    None,
+    /// Keep showing the last frame
+    Freeze,
+    Black,
+    /// Fill with colour 0
+    Color0,
+}
+
+/// Data a command takes from the next frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+enum Transfer {
+    Palettes,
+    /// Border tiles 0x00-0x7F, or 0x80-0xFF if set
+    BorderTiles(bool),
+    BorderMap,
+    AttributeFiles,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Sgb {
+    packets: PacketReader,
+    /// Palettes 0-3 as RGB555; colour 0 is shared by all of them
+    palettes: [[u16; 4]; 4],
+    /// Palettes loaded with PAL_TRN, for PAL_SET
+    system_palettes: Vec<[u16; 4]>,
+    /// Palette of each 8x8 block of the screen
+    attributes: Vec<u8>,
+    /// Attribute maps loaded with ATTR_TRN, for ATTR_SET and PAL_SET
+    attribute_files: Vec<u8>,
+    border: Border,
+    mask: Mask,
+    pending_transfer: Option<Transfer>,
+    /// Number of controllers enabled by MLT_REQ (1, 2 or 4)
+    players: u8,
+    /// Controller read through P1
+    player: u8,
+    last_p1: u8,
+    /// Colourised Game Boy screen, kept while the mask freezes it
+    #[serde(skip)]
+    screen: Vec<u32>,
+    #[serde(skip)]
+    frame: Vec<u32>,
+}
+
+impl Sgb {
+    pub fn new() -> Self {
+        let mut sgb = Self {
+            packets: PacketReader::default(),
+            palettes: [DEFAULT_PALETTE; 4],
+            system_palettes: vec![[0; 4]; SYSTEM_PALETTES],
+            attributes: vec![0; ATTR_COLUMNS * ATTR_ROWS],
+            attribute_files: vec![0; ATTR_FILES * ATTR_FILE_SIZE],
+            border: Border::default(),
+            mask: Mask::None,
+            pending_transfer: None,
+            players: 1,
+            player: 0,
+            last_p1: 0xFF,
+            screen: Vec::new(),
+            frame: Vec::new(),
+        };
+        sgb.reinit_buffers();
+        sgb
+    }
+
+    /// Recreates the frame after deserialization, with a blank screen until the next one.
+    pub fn reinit_buffers(&mut self) {
+        if self.screen.len() != WIDTH * HEIGHT {
+            self.screen = vec![rgb555_to_argb(self.palettes[0][0]); WIDTH * HEIGHT];
+        }
+        if self.frame.len() != SGB_WIDTH * SGB_HEIGHT {
+            self.frame = vec![0; SGB_WIDTH * SGB_HEIGHT];
+            self.compose();
+        }
+    }
+
+    /// The last frame, `SGB_WIDTH * SGB_HEIGHT` pixels in 0xAARRGGBB format.
+    pub fn frame(&self) -> &[u32] {
+        &self.frame
+    }
+
+    pub fn mask(&self) -> Mask {
+        self.mask
+    }
+
+    /// Applies the multiplayer controller selection to a P1 read.
+    pub fn read_p1(&self, value: u8) -> u8 {
+        if self.players > 1 && value & 0x30 == 0x30 {
+            // Both groups deselected: the ID of the current controller (0xF for player 1)
+            (value & 0xF0) | (0x0F - self.player)
+        } else if self.player != 0 {
+            // Only the first controller is connected
+            value | 0x0F
+        } else {
+            value
+        }
+    }
+
+    /// Handles a write to P1: command packets and multiplayer controller selection.
+    pub fn write_p1(&mut self, value: u8) {
+        // P15 going high moves on to the next controller
+        if self.last_p1 & 0x20 == 0 && value & 0x20 != 0 {
+            self.player = (self.player + 1) % self.players;
+        }
+        self.last_p1 = value;
+
+        if let Some(command) = self.packets.write(value) {
+            self.execute(&command);
+        }
+    }
+
+    /// Runs a command. `data` holds all of its packets.
+    pub fn execute(&mut self, data: &[u8]) {
+        match data[0] >> 3 {
+            PAL01 => self.set_palette_pair(0, 1, data),
+            PAL23 => self.set_palette_pair(2, 3, data),
+            PAL03 => self.set_palette_pair(0, 3, data),
+            PAL12 => self.set_palette_pair(1, 2, data),
+            ATTR_BLK => self.attribute_blocks(data),
+            ATTR_LIN => self.attribute_lines(data),
+            ATTR_DIV => self.attribute_division(data),
+            ATTR_CHR => self.attribute_characters(data),
+            PAL_SET => self.palette_set(data),
+            PAL_TRN => self.pending_transfer = Some(Transfer::Palettes),
+            MLT_REQ => {
+                self.players = match data[1] & 0b11 {
+                    0b01 => 2,
+                    0b11 => 4,
+                    _ => 1,
+                };
+                self.player = 0;
+            }
+            CHR_TRN => self.pending_transfer = Some(Transfer::BorderTiles(data[1] & 1 != 0)),
+            PCT_TRN => self.pending_transfer = Some(Transfer::BorderMap),
+            ATTR_TRN => self.pending_transfer = Some(Transfer::AttributeFiles),
+            ATTR_SET => {
+                self.load_attribute_file(data[1] & 0x3F);
+                if data[1] & 0x40 != 0 {
+                    self.mask = Mask::None;
+                }
+            }
+            MASK_EN => {
+                self.mask = match data[1] & 0b11 {
+                    1 => Mask::Freeze,
+                    2 => Mask::Black,
+                    3 => Mask::Color0,
+                    _ => Mask::None,
+                };
+            }
+            // Sound, SNES program upload and the rest don't affect the picture
+            _ => {}
+        }
+    }
+
+    /// Completes any pending transfer with the frame `ppu` just finished, then
+    /// composes the SGB frame.
+    pub fn end_frame(&mut self, ppu: &PPU) {
+        if let Some(transfer) = self.pending_transfer.take() {
+            let data = transfer_data(ppu.shades());
+            match transfer {
+                Transfer::Palettes => {
+                    let colors = data
+                        .chunks_exact(2)
+                        .map(|b| u16::from_le_bytes([b[0], b[1]]));
+                    for (color, value) in self
+                        .system_palettes
+                        .as_flattened_mut()
+                        .iter_mut()
+                        .zip(colors)
+                    {
+                        *color = value;
+                    }
+                }
+                Transfer::BorderTiles(upper) => self.border.set_tiles(upper, &data),
+                Transfer::BorderMap => self.border.set_map(&data),
+                Transfer::AttributeFiles => {
+                    let size = self.attribute_files.len();
+                    self.attribute_files.copy_from_slice(&data[..size]);
+                }
+            }
+        }
+
+        match self.mask {
+            Mask::None => {
+                for (index, (pixel, &shade)) in self.screen.iter_mut().zip(ppu.shades()).enumerate()
+                {
+                    let block = (index / WIDTH / 8) * ATTR_COLUMNS + (index % WIDTH) / 8;
+                    let palette = &self.palettes[self.attributes[block] as usize];
+                    *pixel = rgb555_to_argb(palette[shade as usize]);
+                }
+            }
+            Mask::Freeze => {}
+            Mask::Black => self.screen.fill(rgb555_to_argb(0)),
+            Mask::Color0 => self.screen.fill(rgb555_to_argb(self.palettes[0][0])),
+        }
+        self.compose();
+    }
+
+    /// Backdrop in colour 0, the Game Boy screen, then the border on top.
+    fn compose(&mut self) {
+        self.frame.fill(rgb555_to_argb(self.palettes[0][0]));
+        for (y, line) in self.screen.chunks_exact(WIDTH).enumerate() {
+            let start = (SCREEN_Y + y) * SGB_WIDTH + SCREEN_X;
+            self.frame[start..start + WIDTH].copy_from_slice(line);
+        }
+        self.border.draw(&mut self.frame);
+    }
+
+    /// PAL01, PAL23, PAL03 and PAL12: the shared colour 0, then colours 1-3 of
+    /// palettes `first` and `second`.
+    fn set_palette_pair(&mut self, first: usize, second: usize, data: &[u8]) {
+        let color = |i: usize| u16::from_le_bytes([data[1 + i * 2], data[2 + i * 2]]);
+        self.set_color0(color(0));
+        for i in 1..4 {
+            self.palettes[first][i] = color(i);
+            self.palettes[second][i] = color(i + 3);
+        }
+    }
+
+    fn set_color0(&mut self, color: u16) {
+        for palette in &mut self.palettes {
+            palette[0] = color;
+        }
+    }
+
+    /// PAL_SET: palettes 0-3 from the system palettes, optionally with an attribute file.
+    fn palette_set(&mut self, data: &[u8]) {
+        for (i, palette) in self.palettes.iter_mut().enumerate() {
+            let number = u16::from_le_bytes([data[1 + i * 2], data[2 + i * 2]]) as usize;
+            *palette = self.system_palettes[number % SYSTEM_PALETTES];
+        }
+        let color0 = self.palettes[0][0];
+        self.set_color0(color0);
+
+        let flags = data[9];
+        if flags & 0x80 != 0 {
+            self.load_attribute_file(flags & 0x3F);
+        }
+        if flags & 0x40 != 0 {
+            self.mask = Mask::None;
+        }
+    }
+
+    /// ATTR_BLK: palettes inside, on the edge of and outside rectangles.
+    fn attribute_blocks(&mut self, data: &[u8]) {
+        let count = data[1] as usize;
+        for set in data[2..].chunks_exact(6).take(count) {
+            let [control, palettes, x1, y1, x2, y2] =
+                [set[0], set[1], set[2], set[3], set[4], set[5]];
+            let inside = control & 0b001 != 0;
+            let edge = control & 0b010 != 0;
+            let outside = control & 0b100 != 0;
+            let inside_palette = palettes & 0b11;
+            let edge_palette = (palettes >> 2) & 0b11;
+            let outside_palette = (palettes >> 4) & 0b11;
+
+            // With only one of inside/outside set, the edge goes along with it
+            let edge = match (inside, edge, outside) {
+                (_, true, _) => Some(edge_palette),
+                (true, false, false) => Some(inside_palette),
+                (false, false, true) => Some(outside_palette),
+                _ => None,
+            };
+
+            for y in 0..ATTR_ROWS as u8 {
+                for x in 0..ATTR_COLUMNS as u8 {
+                    let palette = if x > x1 && x < x2 && y > y1 && y < y2 {
+                        inside.then_some(inside_palette)
+                    } else if x >= x1 && x <= x2 && y >= y1 && y <= y2 {
+                        edge
+                    } else {
+                        outside.then_some(outside_palette)
+                    };
+                    if let Some(palette) = palette {
+                        self.attributes[y as usize * ATTR_COLUMNS + x as usize] = palette;
+                    }
+                }
+            }
+        }
+    }
+
+    /// ATTR_LIN: palettes for whole rows or columns.
+    fn attribute_lines(&mut self, data: &[u8]) {
+        let count = data[1] as usize;
+        for &line in data[2..].iter().take(count) {
+            let number = (line & 0x1F) as usize;
+            let palette = (line >> 5) & 0b11;
+            if line & 0x80 != 0 {
+                if number < ATTR_ROWS {
+                    self.attributes[number * ATTR_COLUMNS..][..ATTR_COLUMNS].fill(palette);
+                }
+            } else if number < ATTR_COLUMNS {
+                for row in 0..ATTR_ROWS {
+                    self.attributes[row * ATTR_COLUMNS + number] = palette;
+                }
+            }
+        }
+    }
+
+    /// ATTR_DIV: splits the screen at a row or column.
+    fn attribute_division(&mut self, data: &[u8]) {
+        let after = data[1] & 0b11;
+        let before = (data[1] >> 2) & 0b11;
+        let on_line = (data[1] >> 4) & 0b11;
+        let horizontal = data[1] & 0x40 != 0;
+        let line = data[2] as usize;
+
+        for y in 0..ATTR_ROWS {
+            for x in 0..ATTR_COLUMNS {
+                let position = if horizontal { y } else { x };
+                self.attributes[y * ATTR_COLUMNS + x] = match position.cmp(&line) {
+                    std::cmp::Ordering::Less => before,
+                    std::cmp::Ordering::Equal => on_line,
+                    std::cmp::Ordering::Greater => after,
+                };
+            }
+        }
+    }
+
+    /// ATTR_CHR: palettes for a run of blocks, left to right or top to bottom.
+    fn attribute_characters(&mut self, data: &[u8]) {
+        let (mut x, mut y) = (data[1] as usize, data[2] as usize);
+        let count = u16::from_le_bytes([data[3], data[4]]) as usize;
+        let vertical = data[5] & 1 != 0;
+
+        let palettes = data[6..]
+            .iter()
+            .flat_map(|&byte| [byte >> 6, byte >> 4, byte >> 2, byte].map(|p| p & 0b11));
+        for palette in palettes.take(count) {
+            if x >= ATTR_COLUMNS || y >= ATTR_ROWS {
+                break;
+            }
+            self.attributes[y * ATTR_COLUMNS + x] = palette;
+            if vertical {
+                y += 1;
+                if y == ATTR_ROWS {
+                    y = 0;
+                    x += 1;
+                }
+            } else {
+                x += 1;
+                if x == ATTR_COLUMNS {
+                    x = 0;
+                    y += 1;
+                }
+            }
+        }
+    }
+
+    fn load_attribute_file(&mut self, file: u8) {
+        let file = file as usize;
+        if file >= ATTR_FILES {
+            return;
+        }
+        let bytes = &self.attribute_files[file * ATTR_FILE_SIZE..][..ATTR_FILE_SIZE];
+        for (block, attribute) in self.attributes.iter_mut().enumerate() {
+            *attribute = (bytes[block / 4] >> (6 - (block % 4) * 2)) & 0b11;
+        }
+    }
+}
+
+impl Default for Sgb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads the 4 KiB of a VRAM transfer back from the screen: 256 2bpp tiles,
+/// 20 per row, as displayed through BGP.
+fn transfer_data(shades: &[u8]) -> Vec<u8> {
+    let mut data = vec![0; TRANSFER_SIZE];
+    for (tile, bytes) in data.chunks_exact_mut(TILE_SIZE).enumerate() {
+        let (column, row) = (tile % ATTR_COLUMNS, tile / ATTR_COLUMNS);
+        for y in 0..8 {
+            for x in 0..8 {
+                let shade = shades[(row * 8 + y) * WIDTH + column * 8 + x];
+                bytes[y * 2] |= (shade & 1) << (7 - x);
+                bytes[y * 2 + 1] |= (shade >> 1) << (7 - x);
+            }
+        }
+    }
+    data
+}
@@ -0,0 +1,69 @@
+//! Decoding of SGB command packets from P1 writes.
+//!
+//! A packet starts with a reset pulse (P14 and P15 both low), followed by 128
+//! bits, least significant bit of each byte first: P14 low for a 0, P15 low for
+//! a 1, with both lines high between pulses. A 0 bit ends the packet. The low
+//! 3 bits of the first byte give the number of packets in the command.
+
+use serde::{Deserialize, Serialize};
+
+const PACKET_SIZE: usize = 16;
+
+const PACKET_BITS: usize = PACKET_SIZE * 8;
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct PacketReader {
+    /// Packets received so far for the current command
+    command: Vec<u8>,
+    packet: [u8; PACKET_SIZE],
+    /// Bits received in `packet`, `PACKET_BITS` while waiting for the stop bit
+    bit: usize,
+    /// Inside a packet, after its reset pulse
+    receiving: bool,
+    /// Both lines went high since the last pulse
+    released: bool,
+}
+
+impl PacketReader {
+    /// Handles a write to P1. Returns the command once all of its packets are in.
+    pub fn write(&mut self, value: u8) -> Option<Vec<u8>> {
+        let pulse = match (value >> 4) & 0b11 {
+            0b11 => {
+                self.released = true;
+                return None;
+            }
+            0b00 => {
+                self.receiving = true;
+                self.bit = 0;
+                self.packet = [0; PACKET_SIZE];
+                None
+            }
+            _ if !self.released => return None,
+            0b10 => Some(false),
+            _ => Some(true),
+        };
+        self.released = false;
+
+        let bit = pulse?;
+        if !self.receiving {
+            return None;
+        }
+
+        if self.bit < PACKET_BITS {
+            self.packet[self.bit / 8] |= (bit as u8) << (self.bit % 8);
+            self.bit += 1;
+            return None;
+        }
+
+        self.receiving = false;
+        if bit {
+            // Missing stop bit: drop the whole command
+            self.command.clear();
+            return None;
+        }
+        self.command.extend_from_slice(&self.packet);
+
+        let packets = (self.command[0] & 0b111).max(1) as usize;
+        (self.command.len() >= packets * PACKET_SIZE).then(|| std::mem::take(&mut self.command))
+    }
+}
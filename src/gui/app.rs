@@ -4,25 +4,63 @@ use std::time::{Duration, Instant};
 use crate::{
     audio::AudioSampleSender,
     emulator::{
-        CPU_FREQUENCY, Emulator,
-        ppu::{HEIGHT, WIDTH},
+        CPU_FREQUENCY, Emulator, EmulatorConfig,
+        rewind::RewindConfig,
     },
     rendering::{GlContext, init_gl_context, render_with_shader},
 };
 
-use super::{input, menu, panels};
+use super::{
+    debugger::DebuggerInput,
+    disassembly::DisassemblyView,
+    input,
+    memory_viewer::MemoryViewer,
+    menu::{self, MenuState},
+    oam_viewer::OamViewer,
+    palette_viewer::PaletteViewer,
+    printer_viewer::PrinterViewer,
+    serial_console::SerialConsole,
+    panels,
+    save_slots::SlotThumbnails,
+    speed::Speed,
+    tile_map_viewer::TileMapViewer,
+    tile_viewer::TileViewer,
+};
 
 const TIME_STEP: Duration = Duration::from_micros(2_000);
 const CYCLES_PER_STEP: u64 = (CPU_FREQUENCY as u64 * TIME_STEP.as_micros() as u64) / 1_000_000;
+/// Longest time spent catching up per GUI frame before dropping the backlog
+const MAX_CATCH_UP: Duration = Duration::from_millis(30);
+/// Time spent emulating per GUI frame at uncapped speed
+const UNCAPPED_SLICE: Duration = Duration::from_millis(12);
+
+/// Single step requested from the keyboard or the Emulation menu, run while paused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepRequest {
+    /// Run until the next V-Blank
+    Frame,
+    /// Execute one instruction
+    Instruction,
+}
 
 pub struct EmulatorApp {
     pub emulator: Option<Emulator>,
     pub audio_sender: Option<AudioSampleSender>,
-    pub show_debug: bool,
-    pub paused: bool,
-    pub force_dmg: bool,
-    pub audio_mono: bool,
-    pub audio_volume: f32,
+    pub menu: MenuState,
+    debugger_input: DebuggerInput,
+    disassembly_view: DisassemblyView,
+    memory_viewer: MemoryViewer,
+    tile_viewer: TileViewer,
+    tile_map_viewer: TileMapViewer,
+    oam_viewer: OamViewer,
+    palette_viewer: PaletteViewer,
+    printer_viewer: PrinterViewer,
+    serial_console: SerialConsole,
+    /// Rewind key held
+    pub rewinding: bool,
+    pub config: EmulatorConfig,
+    slot_thumbnails: SlotThumbnails,
+    next_step: Instant,
     gl_context: Option<GlContext>,
 }
 
@@ -31,61 +69,132 @@ impl EmulatorApp {
         Self {
             emulator,
             audio_sender,
-            show_debug: false,
-            paused: false,
-            force_dmg: false,
-            audio_mono: false,
-            audio_volume: 0.5,
+            menu: MenuState::default(),
+            debugger_input: DebuggerInput::default(),
+            disassembly_view: DisassemblyView::default(),
+            memory_viewer: MemoryViewer::default(),
+            tile_viewer: TileViewer::default(),
+            tile_map_viewer: TileMapViewer::default(),
+            oam_viewer: OamViewer::default(),
+            palette_viewer: PaletteViewer::default(),
+            printer_viewer: PrinterViewer::default(),
+            serial_console: SerialConsole::default(),
+            rewinding: false,
+            config: EmulatorConfig {
+                rewind: Some(RewindConfig::default()),
+                ..Default::default()
+            },
+            slot_thumbnails: SlotThumbnails::default(),
+            next_step: Instant::now(),
             gl_context: None,
         }
     }
 
     fn update_emulator(&mut self) {
-        if let Some(emulator) = &mut self.emulator {
-            if !emulator.running || self.paused {
-                return;
+        let Some(emulator) = &mut self.emulator else {
+            self.next_step = Instant::now();
+            return;
+        };
+
+        // Stepping pauses emulation; the display and debug panel show the new state
+        if let Some(request) = self.menu.step_request.take() {
+            self.menu.paused = true;
+            emulator.cpu.bus.debugger.resume();
+            match request {
+                StepRequest::Frame => emulator.run_frame(),
+                StepRequest::Instruction => emulator.step(),
+            };
+            emulator.drain_audio();
+        }
+
+        // Keep the clock current while stopped to prevent catch-up on resume
+        if !emulator.running || self.menu.paused {
+            self.next_step = Instant::now();
+            return;
+        }
+
+        emulator.set_rewind(self.config.rewind);
+
+        // Continue past the breakpoint or watchpoint that paused emulation
+        emulator.cpu.bus.debugger.resume();
+
+        // Go back one snapshot per GUI frame while the rewind key is held
+        if self.rewinding {
+            emulator.rewind();
+            emulator.drain_audio();
+            self.next_step = Instant::now();
+            return;
+        }
+
+        let now = Instant::now();
+        let speed = self.menu.speed.current();
+
+        // Audio is pitch-shifted with the speed: resampling for a device `multiplier`
+        // times slower keeps the output queue filled at the same rate. Uncapped is muted.
+        let sample_rate = match speed {
+            Speed::Multiplier(multiplier) => {
+                self.audio_sender.as_ref().map(|s| s.sample_rate() / multiplier)
             }
+            Speed::Uncapped => None,
+        };
+        emulator.set_audio_sample_rate(sample_rate);
 
-            let now = Instant::now();
-
-            // Emulate in chunks (TIME_STEP) to keep timing consistent
-            while emulator.next_step <= now {
-                let mut cycles_this_step = 0u64;
-                while cycles_this_step < CYCLES_PER_STEP {
-                    let t_cycles_taken = emulator.cpu.step();
-                    cycles_this_step += t_cycles_taken as u64;
-
-                    // Process audio
-                    if let Some(audio_sender) = &mut self.audio_sender {
-                        let audio_mono = self.audio_mono;
-                        let audio_volume = self.audio_volume;
-                        audio_sender.process_cpu_cycles(t_cycles_taken as u32, || {
-                            let (left, right) = if audio_mono {
-                                emulator.cpu.bus.apu.sample_mono()
-                            } else {
-                                emulator.cpu.bus.apu.sample_stereo()
-                            };
-                            (left * audio_volume, right * audio_volume)
-                        });
+        match speed {
+            Speed::Multiplier(multiplier) => {
+                let cycles = (CYCLES_PER_STEP as f32 * multiplier) as u64;
+
+                // Emulate in chunks (TIME_STEP) to keep timing consistent
+                while self.next_step <= now {
+                    emulator.run_cycles(cycles);
+                    self.next_step += TIME_STEP;
+
+                    // The host can't keep up with this speed
+                    if now.elapsed() > MAX_CATCH_UP {
+                        self.next_step = Instant::now();
+                        break;
                     }
                 }
-                emulator.next_step += TIME_STEP;
+            }
+            Speed::Uncapped => {
+                while now.elapsed() < UNCAPPED_SLICE && emulator.cpu.bus.debugger.hit().is_none() {
+                    emulator.run_cycles(CYCLES_PER_STEP);
+                }
+                self.next_step = Instant::now();
+            }
+        }
+
+        if emulator.cpu.bus.debugger.hit().is_some() {
+            self.menu.paused = true;
+        }
+
+        // Process audio
+        if let Some(audio_sender) = &mut self.audio_sender {
+            for (left, right) in emulator.drain_audio() {
+                let (left, right) = if self.menu.audio_mono {
+                    let mono = (left + right) / 2.0;
+                    (mono, mono)
+                } else {
+                    (left, right)
+                };
+                let volume = self.menu.audio_volume;
+                audio_sender.send((left * volume, right * volume));
             }
         }
     }
 
     fn render_emulator(&mut self, ui: &mut egui::Ui) {
         if let Some(emulator) = &self.emulator {
-            let image_buffer = emulator.cpu.bus.ppu.buffer.clone();
+            let image_buffer = emulator.framebuffer().to_vec();
+            let (width, height) = emulator.frame_size();
 
             let available_size = ui.available_size();
-            let scale_x = (available_size.x / WIDTH as f32).floor().max(1.0);
-            let scale_y = (available_size.y / HEIGHT as f32).floor().max(1.0);
+            let scale_x = (available_size.x / width as f32).floor().max(1.0);
+            let scale_y = (available_size.y / height as f32).floor().max(1.0);
             let scale = scale_x.min(scale_y); // Use the smaller scale to fit in both dimensions
             
             let display_size = egui::vec2(
-                WIDTH as f32 * scale,
-                HEIGHT as f32 * scale,
+                width as f32 * scale,
+                height as f32 * scale,
             );
 
             // Center the allocated space
@@ -109,7 +218,12 @@ impl EmulatorApp {
                         rect,
                         callback: std::sync::Arc::new(egui_glow::CallbackFn::new(
                             move |_info, painter| {
-                                render_with_shader(painter.gl(), &gl_context_clone, &image_buffer);
+                                render_with_shader(
+                                    painter.gl(),
+                                    &gl_context_clone,
+                                    &image_buffer,
+                                    (width, height),
+                                );
                             },
                         )),
                     };
@@ -133,12 +247,7 @@ impl eframe::App for EmulatorApp {
             }
         }
 
-        input::handle_keyboard_input(
-            ctx,
-            &mut self.emulator,
-            &mut self.show_debug,
-            &mut self.paused,
-        );
+        input::handle_keyboard_input(ctx, &mut self.emulator, &mut self.menu, &mut self.rewinding);
         self.update_emulator();
 
         // Menu bar
@@ -147,11 +256,9 @@ impl eframe::App for EmulatorApp {
                 ctx,
                 ui,
                 &mut self.emulator,
-                &mut self.paused,
-                &mut self.force_dmg,
-                &mut self.show_debug,
-                &mut self.audio_mono,
-                &mut self.audio_volume,
+                &mut self.config,
+                &mut self.slot_thumbnails,
+                &mut self.menu,
             );
         });
 
@@ -161,8 +268,56 @@ impl eframe::App for EmulatorApp {
         });
 
         // Debug panel
-        if self.show_debug {
-            panels::render_debug_panel(ctx, &self.emulator);
+        if self.menu.show_debug {
+            panels::render_debug_panel(
+                ctx,
+                &mut self.emulator,
+                &mut self.debugger_input,
+                &mut self.disassembly_view,
+            );
+        }
+
+        if self.menu.show_memory
+            && let Some(emulator) = &mut self.emulator
+        {
+            self.memory_viewer
+                .render(ctx, emulator, &mut self.menu.show_memory);
+        }
+        if self.menu.show_tiles
+            && let Some(emulator) = &self.emulator
+        {
+            self.tile_viewer
+                .render(ctx, emulator, &mut self.menu.show_tiles);
+        }
+        if self.menu.show_tile_maps
+            && let Some(emulator) = &self.emulator
+        {
+            self.tile_map_viewer
+                .render(ctx, emulator, &mut self.menu.show_tile_maps);
+        }
+        if self.menu.show_oam
+            && let Some(emulator) = &self.emulator
+        {
+            self.oam_viewer
+                .render(ctx, emulator, &mut self.menu.show_oam);
+        }
+        if self.menu.show_palettes
+            && let Some(emulator) = &mut self.emulator
+        {
+            self.palette_viewer
+                .render(ctx, emulator, &mut self.menu.show_palettes);
+        }
+        if self.menu.show_printer
+            && let Some(emulator) = &mut self.emulator
+        {
+            self.printer_viewer
+                .render(ctx, emulator, &mut self.menu.show_printer);
+        }
+        if self.menu.show_serial
+            && let Some(emulator) = &mut self.emulator
+        {
+            self.serial_console
+                .render(ctx, emulator, &mut self.menu.show_serial);
         }
 
         // Controls help bar
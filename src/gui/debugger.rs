@@ -0,0 +1,109 @@
+use crate::emulator::Emulator;
+use crate::emulator::debugger::{Breakpoint, DebugHit, Watchpoint};
+
+const HIT_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 96, 96);
+
+/// Text typed into the breakpoint and watchpoint fields.
+#[derive(Default)]
+pub struct DebuggerInput {
+    breakpoint: String,
+    watchpoint: String,
+    error: Option<String>,
+}
+
+/// Breakpoint and watchpoint lists, with the hit that paused emulation highlighted.
+pub fn render_debugger(ui: &mut egui::Ui, emulator: &mut Emulator, input: &mut DebuggerInput) {
+    let pc = emulator.cpu.reg.pc;
+    let pc_label = match emulator.cpu.bus.bank_at(pc) {
+        Some(bank) => format!("PC = {:02X}:{:04X}", bank, pc),
+        None => format!("PC = {:04X}", pc),
+    };
+    let debugger = &mut emulator.cpu.bus.debugger;
+    let hit = debugger.hit();
+
+    ui.monospace(pc_label);
+    if let Some(hit) = hit {
+        ui.colored_label(HIT_COLOR, hit.to_string());
+    }
+
+    ui.separator();
+    ui.label("Breakpoints");
+    let mut removed = None;
+    for (index, breakpoint) in debugger.breakpoints.iter_mut().enumerate() {
+        let is_hit = matches!(hit, Some(DebugHit::Breakpoint { index: i, .. }) if i == index);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut breakpoint.enabled, "");
+            let text = egui::RichText::new(breakpoint.to_string()).monospace();
+            ui.label(if is_hit { text.color(HIT_COLOR) } else { text });
+            if ui.small_button("✖").clicked() {
+                removed = Some(index);
+            }
+        });
+    }
+    if let Some(index) = removed {
+        debugger.breakpoints.remove(index);
+        debugger.resume();
+    }
+    ui.horizontal(|ui| {
+        let field = ui.add(
+            egui::TextEdit::singleline(&mut input.breakpoint)
+                .hint_text("03:4A10")
+                .desired_width(70.0),
+        );
+        let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if ui.button("Add").clicked() || submitted {
+            match input.breakpoint.parse::<Breakpoint>() {
+                Ok(breakpoint) => {
+                    debugger.breakpoints.push(breakpoint);
+                    input.breakpoint.clear();
+                    input.error = None;
+                }
+                Err(e) => input.error = Some(e),
+            }
+        }
+    });
+
+    ui.separator();
+    ui.label("Watchpoints");
+    let mut removed = None;
+    for (index, watchpoint) in debugger.watchpoints.iter_mut().enumerate() {
+        let is_hit = matches!(hit, Some(DebugHit::Watchpoint { index: i, .. }) if i == index);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut watchpoint.enabled, "");
+            let text = egui::RichText::new(watchpoint.to_string()).monospace();
+            ui.label(if is_hit { text.color(HIT_COLOR) } else { text });
+            ui.toggle_value(&mut watchpoint.read, "R");
+            ui.toggle_value(&mut watchpoint.write, "W");
+            ui.toggle_value(&mut watchpoint.execute, "X");
+            if ui.small_button("✖").clicked() {
+                removed = Some(index);
+            }
+        });
+    }
+    if let Some(index) = removed {
+        debugger.watchpoints.remove(index);
+        debugger.resume();
+    }
+    ui.horizontal(|ui| {
+        let field = ui.add(
+            egui::TextEdit::singleline(&mut input.watchpoint)
+                .hint_text("C000-C0FF")
+                .desired_width(70.0),
+        );
+        let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if ui.button("Add").clicked() || submitted {
+            match input.watchpoint.parse::<Watchpoint>() {
+                Ok(watchpoint) => {
+                    debugger.watchpoints.push(watchpoint);
+                    input.watchpoint.clear();
+                    input.error = None;
+                }
+                Err(e) => input.error = Some(e),
+            }
+        }
+    });
+
+    if let Some(error) = &input.error {
+        ui.colored_label(HIT_COLOR, error);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::emulator::Emulator;
+use crate::emulator::cpu::disassembler::{self, DisassembledInstruction};
+
+/// Bytes disassembled before and after PC.
+const BYTES_BEFORE: u16 = 0x30;
+const BYTES_AFTER: u16 = 0x60;
+
+const BREAKPOINT_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 96, 96);
+const PC_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 220, 96);
+
+#[derive(Default)]
+pub struct DisassemblyView {
+    /// PC the view was last scrolled to
+    last_pc: Option<u16>,
+}
+
+/// Scrolling disassembly around PC, read through the bus so it follows the mapped banks.
+///
+/// Clicking the gutter of a line toggles a breakpoint on it.
+pub fn render_disassembly(ui: &mut egui::Ui, emulator: &mut Emulator, view: &mut DisassemblyView) {
+    let pc = emulator.cpu.reg.pc;
+    let follow_pc = view.last_pc.replace(pc) != Some(pc);
+    let bus = &emulator.cpu.bus;
+    let lines = lines_around(|address| bus.peek_byte(address), pc);
+    let mut toggled = None;
+
+    egui::ScrollArea::vertical()
+        .max_height(240.0)
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            for line in &lines {
+                let bank = bus.bank_at(line.address);
+                let has_breakpoint = bus.debugger.breakpoints.iter().any(|breakpoint| {
+                    breakpoint.enabled && breakpoint.is_at(line.address, bank)
+                });
+                let address = match bank {
+                    Some(bank) => format!("{:02X}:{:04X}", bank, line.address),
+                    None => format!("   {:04X}", line.address),
+                };
+                let bytes: String = line.bytes.iter().map(|byte| format!("{:02X}", byte)).collect();
+
+                ui.horizontal(|ui| {
+                    let marker = if has_breakpoint { "●" } else { " " };
+                    let gutter = egui::RichText::new(marker).monospace().color(BREAKPOINT_COLOR);
+                    if ui.add(egui::Label::new(gutter).sense(egui::Sense::click())).clicked() {
+                        toggled = Some((line.address, bank));
+                    }
+
+                    let text = egui::RichText::new(format!("{} {:<6} {}", address, bytes, line.text))
+                        .monospace();
+                    if line.address == pc {
+                        if follow_pc {
+                            ui.scroll_to_cursor(Some(egui::Align::Center));
+                        }
+                        ui.label(text.color(PC_COLOR));
+                    } else {
+                        ui.label(text);
+                    }
+                });
+            }
+        });
+
+    if let Some((address, bank)) = toggled {
+        emulator.cpu.bus.debugger.toggle_breakpoint(address, bank);
+    }
+}
+
+/// Disassembles the code around `pc`.
+///
+/// Instructions before PC are ambiguous, so decoding starts from the earliest
+/// address whose instruction stream lines up with PC.
+fn lines_around(read: impl Fn(u16) -> u8 + Copy, pc: u16) -> Vec<DisassembledInstruction> {
+    let end = pc.saturating_add(BYTES_AFTER);
+    let start = (pc.saturating_sub(BYTES_BEFORE)..pc)
+        .find(|&start| {
+            let mut address = start;
+            while address < pc {
+                address = address.wrapping_add(disassembler::disassemble(read, address).size());
+            }
+            address == pc
+        })
+        .unwrap_or(pc);
+
+    let mut lines = Vec::new();
+    let mut address = start;
+    while address < end && address >= start {
+        let line = disassembler::disassemble(read, address);
+        address = address.wrapping_add(line.size());
+        lines.push(line);
+    }
+    lines
+}
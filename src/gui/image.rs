@@ -0,0 +1,36 @@
+/// Converts a 0xAARRGGBB pixel buffer to an egui image.
+pub fn to_color_image(pixels: &[u32], width: usize, height: usize) -> egui::ColorImage {
+    let rgba: Vec<u8> = pixels
+        .iter()
+        .flat_map(|&pixel| {
+            [
+                (pixel >> 16) as u8,
+                (pixel >> 8) as u8,
+                pixel as u8,
+                (pixel >> 24) as u8,
+            ]
+        })
+        .collect();
+    egui::ColorImage::from_rgba_unmultiplied([width, height], &rgba)
+}
+
+/// Uploads `image` to `texture`, creating it on first use.
+pub fn update_texture<'a>(
+    ctx: &egui::Context,
+    texture: &'a mut Option<egui::TextureHandle>,
+    name: &str,
+    image: egui::ColorImage,
+) -> &'a egui::TextureHandle {
+    match texture {
+        Some(texture) => {
+            texture.set(image, egui::TextureOptions::NEAREST);
+            texture
+        }
+        None => texture.insert(ctx.load_texture(name, image, egui::TextureOptions::NEAREST)),
+    }
+}
+
+/// Converts a 0xAARRGGBB pixel to an egui colour, ignoring alpha.
+pub fn to_color32(pixel: u32) -> egui::Color32 {
+    egui::Color32::from_rgb((pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8)
+}
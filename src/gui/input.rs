@@ -1,39 +1,67 @@
+use super::app::StepRequest;
+use super::menu::MenuState;
 use crate::emulator::Emulator;
-use std::time::Instant;
+use crate::emulator::save_state::SLOT_COUNT;
 
 pub fn handle_keyboard_input(
     ctx: &egui::Context,
     emulator: &mut Option<Emulator>,
-    show_debug: &mut bool,
-    paused: &mut bool,
+    state: &mut MenuState,
+    rewinding: &mut bool,
 ) {
+    // Leave the keyboard to text fields while one has focus
+    if ctx.wants_keyboard_input() {
+        *rewinding = false;
+        state.speed.turbo_held = false;
+        return;
+    }
+
     if let Some(emulator) = emulator {
         ctx.input(|i| {
             // Gameboy controls
-            emulator.input_state.a = i.key_down(egui::Key::J);
-            emulator.input_state.b = i.key_down(egui::Key::K);
-            emulator.input_state.up = i.key_down(egui::Key::W);
-            emulator.input_state.down = i.key_down(egui::Key::S);
-            emulator.input_state.left = i.key_down(egui::Key::A);
-            emulator.input_state.right = i.key_down(egui::Key::D);
-            emulator.input_state.start = i.key_down(egui::Key::Enter);
-            emulator.input_state.select = i.key_down(egui::Key::Space);
+            emulator.input_state.buttons.a = i.key_down(egui::Key::J);
+            emulator.input_state.buttons.b = i.key_down(egui::Key::K);
+            emulator.input_state.buttons.up = i.key_down(egui::Key::W);
+            emulator.input_state.buttons.down = i.key_down(egui::Key::S);
+            emulator.input_state.buttons.left = i.key_down(egui::Key::A);
+            emulator.input_state.buttons.right = i.key_down(egui::Key::D);
+            emulator.input_state.buttons.start = i.key_down(egui::Key::Enter);
+            emulator.input_state.buttons.select = i.key_down(egui::Key::Space);
 
             // Emulator controls
             emulator.input_state.save = i.key_down(egui::Key::F1);
             emulator.input_state.load = i.key_down(egui::Key::F2);
+            emulator.input_state.quick_save = i.key_down(egui::Key::F5);
+            emulator.input_state.quick_load = i.key_down(egui::Key::F6);
+
+            let slot = &mut emulator.input_state.slot;
+            if i.key_pressed(egui::Key::F7) {
+                *slot = (*slot + SLOT_COUNT - 1) % SLOT_COUNT;
+            }
+            if i.key_pressed(egui::Key::F8) {
+                *slot = (*slot + 1) % SLOT_COUNT;
+            }
 
             if i.key_pressed(egui::Key::F3) {
-                *show_debug = !*show_debug;
+                state.show_debug = !state.show_debug;
             }
             if i.key_pressed(egui::Key::Escape) {
-                let was_paused = *paused;
-                *paused = !*paused;
+                state.paused = !state.paused;
+            }
+            if i.key_pressed(egui::Key::F) {
+                state.step_request = Some(StepRequest::Frame);
+            }
+            if i.key_pressed(egui::Key::N) {
+                state.step_request = Some(StepRequest::Instruction);
+            }
+            *rewinding = i.key_down(egui::Key::Backspace);
 
-                // Reset timing when unpausing to prevent catch-up
-                if was_paused && !*paused {
-                    emulator.next_step = Instant::now();
-                }
+            state.speed.turbo_held = i.modifiers.shift;
+            if i.key_pressed(egui::Key::F9) {
+                state.speed.fast_forward = !state.speed.fast_forward;
+            }
+            if i.key_pressed(egui::Key::F10) {
+                state.speed.cycle_slow_motion();
             }
         });
 
@@ -0,0 +1,277 @@
+use crate::emulator::Emulator;
+use crate::emulator::cpu::memory_bus::{MemoryBus, MemoryRegion};
+
+const BYTES_PER_ROW: usize = 16;
+
+const SELECTED_COLOR: egui::Color32 = egui::Color32::from_rgb(60, 90, 160);
+const CHANGED_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 160, 64);
+
+/// Hex editor over the bus or one memory region.
+pub struct MemoryViewer {
+    region: MemoryRegion,
+    selected: Option<usize>,
+    goto: String,
+    search: String,
+    edit: String,
+    status: Option<String>,
+    /// Row to scroll to on the next frame
+    scroll_to_row: Option<usize>,
+    /// Region contents at the last two frame boundaries, to highlight changes
+    previous: Vec<u8>,
+    current: Vec<u8>,
+    snapshot: Option<(MemoryRegion, u64)>,
+}
+
+impl Default for MemoryViewer {
+    fn default() -> Self {
+        Self {
+            region: MemoryRegion::Bus,
+            selected: None,
+            goto: String::new(),
+            search: String::new(),
+            edit: String::new(),
+            status: None,
+            scroll_to_row: None,
+            previous: Vec::new(),
+            current: Vec::new(),
+            snapshot: None,
+        }
+    }
+}
+
+impl MemoryViewer {
+    pub fn render(&mut self, ctx: &egui::Context, emulator: &mut Emulator, open: &mut bool) {
+        egui::Window::new("Memory")
+            .open(open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                let regions = emulator.cpu.bus.memory_regions();
+                if !regions.contains(&self.region) {
+                    self.region = MemoryRegion::Bus;
+                }
+                self.update_snapshot(&emulator.cpu.bus, emulator.frame_count());
+
+                self.render_toolbar(ui, &mut emulator.cpu.bus, &regions);
+                ui.separator();
+                self.render_rows(ui, &emulator.cpu.bus);
+            });
+    }
+
+    fn render_toolbar(&mut self, ui: &mut egui::Ui, bus: &mut MemoryBus, regions: &[MemoryRegion]) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("memory_region")
+                .selected_text(self.region.to_string())
+                .show_ui(ui, |ui| {
+                    for &region in regions {
+                        if ui
+                            .selectable_value(&mut self.region, region, region.to_string())
+                            .clicked()
+                        {
+                            self.selected = None;
+                            self.scroll_to_row = Some(0);
+                        }
+                    }
+                });
+
+            let field = ui.add(
+                egui::TextEdit::singleline(&mut self.goto)
+                    .hint_text("Go to")
+                    .desired_width(50.0),
+            );
+            if field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.goto(bus);
+            }
+
+            let field = ui.add(
+                egui::TextEdit::singleline(&mut self.search)
+                    .hint_text("Search: 3E 01 or \"text\"")
+                    .desired_width(140.0),
+            );
+            let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("Find Next").clicked() || submitted {
+                self.find_next(bus);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            match self.selected {
+                Some(offset) => {
+                    ui.monospace(format!("{:04X}:", self.address_of(offset)));
+                    let field = ui.add(
+                        egui::TextEdit::singleline(&mut self.edit)
+                            .hint_text("Value")
+                            .desired_width(30.0),
+                    );
+                    let submitted =
+                        field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Write").clicked() || submitted {
+                        match u8::from_str_radix(self.edit.trim(), 16) {
+                            Ok(value) => {
+                                bus.write_region(self.region, offset, value);
+                                self.selected = Some((offset + 1) % bus.region_len(self.region));
+                                self.edit.clear();
+                                self.status = None;
+                            }
+                            Err(_) => self.status = Some(format!("Invalid value: {}", self.edit)),
+                        }
+                    }
+                }
+                None => {
+                    ui.label("Click a byte to edit it");
+                }
+            }
+            if let Some(status) = &self.status {
+                ui.separator();
+                ui.label(status);
+            }
+        });
+    }
+
+    fn render_rows(&mut self, ui: &mut egui::Ui, bus: &MemoryBus) {
+        let len = bus.region_len(self.region);
+        let rows = len.div_ceil(BYTES_PER_ROW);
+        let row_height =
+            ui.text_style_height(&egui::TextStyle::Monospace) + ui.spacing().item_spacing.y;
+
+        let mut scroll_area = egui::ScrollArea::vertical().auto_shrink([false, false]);
+        if let Some(row) = self.scroll_to_row.take() {
+            scroll_area = scroll_area.vertical_scroll_offset(row as f32 * row_height);
+        }
+
+        scroll_area.show_rows(ui, row_height, rows, |ui, visible_rows| {
+            for row in visible_rows {
+                let start = row * BYTES_PER_ROW;
+                let end = (start + BYTES_PER_ROW).min(len);
+
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 4.0;
+                    ui.monospace(format!("{:04X}", self.address_of(start)));
+                    ui.add_space(4.0);
+
+                    let mut ascii = String::new();
+                    for offset in start..end {
+                        let value = bus.read_region(self.region, offset);
+                        ascii.push(if value.is_ascii_graphic() {
+                            value as char
+                        } else {
+                            '.'
+                        });
+
+                        let mut text = egui::RichText::new(format!("{:02X}", value)).monospace();
+                        if self.has_changed(offset) {
+                            text = text.color(CHANGED_COLOR);
+                        }
+                        if self.selected == Some(offset) {
+                            text = text.background_color(SELECTED_COLOR);
+                        }
+                        if ui
+                            .add(egui::Label::new(text).sense(egui::Sense::click()))
+                            .clicked()
+                        {
+                            self.selected = Some(offset);
+                            self.edit = format!("{:02X}", value);
+                        }
+                    }
+
+                    ui.add_space(8.0);
+                    ui.monospace(ascii);
+                });
+            }
+        });
+    }
+
+    /// Address shown for `offset`, as mapped in the CPU address space.
+    fn address_of(&self, offset: usize) -> usize {
+        self.region.base_address() as usize + offset
+    }
+
+    fn select(&mut self, offset: usize) {
+        self.selected = Some(offset);
+        self.scroll_to_row = Some(offset / BYTES_PER_ROW);
+    }
+
+    fn goto(&mut self, bus: &MemoryBus) {
+        let input = self.goto.trim().trim_start_matches('$');
+        let offset = usize::from_str_radix(input, 16)
+            .ok()
+            .and_then(|address| address.checked_sub(self.region.base_address() as usize))
+            .filter(|&offset| offset < bus.region_len(self.region));
+
+        match offset {
+            Some(offset) => {
+                self.select(offset);
+                self.status = None;
+            }
+            None => self.status = Some(format!("Not in {}: {}", self.region, self.goto.trim())),
+        }
+    }
+
+    /// Searches forward from the selected byte, wrapping around the region.
+    fn find_next(&mut self, bus: &MemoryBus) {
+        let Some(pattern) = parse_pattern(&self.search) else {
+            self.status = Some(format!("Invalid pattern: {}", self.search));
+            return;
+        };
+
+        let len = bus.region_len(self.region);
+        let start = self.selected.map_or(0, |offset| offset + 1);
+        let found = (0..len).map(|i| (start + i) % len).find(|&offset| {
+            offset + pattern.len() <= len
+                && pattern
+                    .iter()
+                    .enumerate()
+                    .all(|(i, &byte)| bus.read_region(self.region, offset + i) == byte)
+        });
+
+        match found {
+            Some(offset) => {
+                self.select(offset);
+                self.status = Some(format!("Found at {:04X}", self.address_of(offset)));
+            }
+            None => self.status = Some("Not found".to_string()),
+        }
+    }
+
+    /// Takes a copy of the region on every new frame.
+    fn update_snapshot(&mut self, bus: &MemoryBus, frame: u64) {
+        let read_all = || {
+            (0..bus.region_len(self.region))
+                .map(|offset| bus.read_region(self.region, offset))
+                .collect::<Vec<u8>>()
+        };
+
+        match self.snapshot {
+            Some((region, last_frame)) if region == self.region && last_frame == frame => {}
+            Some((region, _)) if region == self.region => {
+                self.previous = std::mem::replace(&mut self.current, read_all());
+            }
+            _ => {
+                self.current = read_all();
+                self.previous = self.current.clone();
+            }
+        }
+        self.snapshot = Some((self.region, frame));
+    }
+
+    /// Whether the byte changed during the last frame.
+    fn has_changed(&self, offset: usize) -> bool {
+        self.previous.get(offset) != self.current.get(offset)
+    }
+}
+
+/// Parses hex bytes (`3E 01`, `3E01`) or quoted ASCII text (`"POKEMON"`).
+fn parse_pattern(input: &str) -> Option<Vec<u8>> {
+    let input = input.trim();
+    if let Some(text) = input.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        return (!text.is_empty()).then(|| text.as_bytes().to_vec());
+    }
+
+    let digits: String = input.split_whitespace().collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok())
+        .collect()
+}
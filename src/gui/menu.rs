@@ -1,29 +1,91 @@
-use crate::emulator::Emulator;
-use std::time::Instant;
+use super::app::StepRequest;
+use super::image;
+use super::save_slots::{self, SlotThumbnails};
+use super::speed::{SLOW_MOTION_FACTORS, Speed, SpeedSettings, TurboSpeed};
+use crate::emulator::boot::PaletteCombo;
+use crate::emulator::model::Model;
+use crate::emulator::ppu::dmg_palette::{DmgLayer, DmgPalettes, PRESETS, Shades};
+use crate::emulator::rewind::RewindConfig;
+use crate::emulator::{Emulator, EmulatorConfig};
+
+/// Frontend toggles changed from the menu bar and keyboard shortcuts.
+pub struct MenuState {
+    pub paused: bool,
+    pub step_request: Option<StepRequest>,
+    pub speed: SpeedSettings,
+    pub audio_mono: bool,
+    pub audio_volume: f32,
+    /// Window visibility
+    pub show_debug: bool,
+    pub show_memory: bool,
+    pub show_tiles: bool,
+    pub show_tile_maps: bool,
+    pub show_oam: bool,
+    pub show_palettes: bool,
+    pub show_printer: bool,
+    pub show_serial: bool,
+}
+
+impl Default for MenuState {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            step_request: None,
+            speed: SpeedSettings::default(),
+            audio_mono: false,
+            audio_volume: 0.5,
+            show_debug: false,
+            show_memory: false,
+            show_tiles: false,
+            show_tile_maps: false,
+            show_oam: false,
+            show_palettes: false,
+            show_printer: false,
+            show_serial: false,
+        }
+    }
+}
 
 pub fn render_menu_bar(
     ctx: &egui::Context,
     ui: &mut egui::Ui,
     emulator: &mut Option<Emulator>,
-    paused: &mut bool,
-    force_dmg: &mut bool,
-    show_debug: &mut bool,
-    audio_mono: &mut bool,
-    audio_volume: &mut f32,
+    config: &mut EmulatorConfig,
+    slot_thumbnails: &mut SlotThumbnails,
+    state: &mut MenuState,
 ) {
     egui::MenuBar::new().ui(ui, |ui| {
-        render_file_menu(ui, ctx, emulator, paused);
-        render_emulation_menu(ui, emulator, paused, force_dmg);
-        render_audio_menu(ui, audio_mono, audio_volume);
-        render_debug_menu(ui, show_debug);
+        render_file_menu(
+            ui,
+            ctx,
+            emulator,
+            &mut state.paused,
+            config,
+            slot_thumbnails,
+        );
+        render_emulation_menu(ui, emulator, state, config);
+        render_video_menu(ui, emulator);
+        render_audio_menu(ui, &mut state.audio_mono, &mut state.audio_volume);
+        render_debug_menu(ui, state);
 
         ui.separator();
 
-        if *paused {
+        if state.paused {
             ui.colored_label(egui::Color32::YELLOW, "⏸ PAUSED");
         } else {
             ui.colored_label(egui::Color32::GREEN, "▶ RUNNING");
         }
+
+        ui.separator();
+        match state.speed.current() {
+            Speed::Multiplier(1.0) => ui.label("Speed: 1×"),
+            current => ui.colored_label(egui::Color32::LIGHT_BLUE, format!("Speed: {}", current)),
+        };
+
+        if let Some(emulator) = emulator {
+            ui.separator();
+            ui.label(format!("State slot {}", emulator.input_state.slot));
+        }
     });
 }
 
@@ -32,16 +94,11 @@ fn render_file_menu(
     ctx: &egui::Context,
     emulator: &mut Option<Emulator>,
     paused: &mut bool,
+    config: &EmulatorConfig,
+    slot_thumbnails: &mut SlotThumbnails,
 ) {
     ui.menu_button("File", |ui| {
         if ui.button("Load ROM...").clicked() {
-            // Reset timing when unpausing to prevent catch-up
-            let was_paused = *paused;
-            if was_paused && !*paused {
-                if let Some(emulator) = emulator {
-                    emulator.next_step = Instant::now();
-                }
-            }
             *paused = true;
 
             let file = rfd::FileDialog::new()
@@ -51,7 +108,7 @@ fn render_file_menu(
 
             if let Some(path) = file {
                 if let Some(path_str) = path.to_str() {
-                    *emulator = Emulator::new(path_str, false).ok();
+                    *emulator = Emulator::with_config(path_str, config).ok();
                 } else {
                     eprintln!("Failed to convert path to string");
                 }
@@ -59,24 +116,30 @@ fn render_file_menu(
             *paused = false;
         }
         ui.separator();
-        if ui.button("Save State").clicked() {
-            if let Some(emulator) = emulator {
-                match emulator.save_state() {
-                    Ok(path) => println!("Saved state to {}", path),
-                    Err(e) => eprintln!("Failed to save state: {}", e),
+        ui.add_enabled_ui(emulator.is_some(), |ui| {
+            ui.menu_button("Save State", |ui| {
+                let Some(emulator) = emulator else { return };
+                let selected = emulator.input_state.slot;
+                if let Some(slot) = save_slots::render_slot_list(ui, slot_thumbnails, emulator, selected) {
+                    match emulator.save_state(slot) {
+                        Ok(path) => println!("Saved state to {}", path),
+                        Err(e) => eprintln!("Failed to save state: {}", e),
+                    }
+                    ui.close();
                 }
-            }
-            ui.close();
-        }
-        if ui.button("Load State").clicked() {
-            if let Some(emulator) = emulator {
-                match emulator.load_state() {
-                    Ok(path) => println!("Loaded state from {}", path),
-                    Err(e) => eprintln!("Failed to load state: {}", e),
+            });
+            ui.menu_button("Load State", |ui| {
+                let Some(emulator) = emulator else { return };
+                let selected = emulator.input_state.slot;
+                if let Some(slot) = save_slots::render_slot_list(ui, slot_thumbnails, emulator, selected) {
+                    match emulator.load_state(slot) {
+                        Ok(path) => println!("Loaded state from {}", path),
+                        Err(e) => eprintln!("Failed to load state: {}", e),
+                    }
+                    ui.close();
                 }
-            }
-            ui.close();
-        }
+            });
+        });
         ui.separator();
         if ui.button("Quit").clicked() {
             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
@@ -87,50 +150,232 @@ fn render_file_menu(
 fn render_emulation_menu(
     ui: &mut egui::Ui,
     emulator: &mut Option<Emulator>,
-    paused: &mut bool,
-    force_dmg: &mut bool,
+    state: &mut MenuState,
+    config: &mut EmulatorConfig,
 ) {
     ui.menu_button("Emulation", |ui| {
         if ui
-            .button(if *paused { "Resume" } else { "Pause" })
+            .button(if state.paused { "Resume" } else { "Pause" })
             .clicked()
         {
-            let was_paused = *paused;
-            *paused = !*paused;
-
-            // Reset timing when unpausing to prevent catch-up
-            if was_paused && !*paused {
-                if let Some(emulator) = emulator {
-                    emulator.next_step = Instant::now();
-                }
-            }
+            state.paused = !state.paused;
             ui.close();
         }
-        if ui.button("Reset").clicked() {
-            if let Some(_emulator) = emulator {
-                // TODO: Implement reset
-            }
+        if ui.button("Frame Advance (F)").clicked() {
+            state.step_request = Some(StepRequest::Frame);
+        }
+        if ui.button("Step Instruction (N)").clicked() {
+            state.step_request = Some(StepRequest::Instruction);
+        }
+        ui.add_enabled_ui(emulator.is_some(), |ui| {
+            ui.menu_button("Reset", |ui| {
+                let Some(emulator) = emulator else { return };
+                let soft = ui.button("Soft Reset").clicked();
+                let hard = ui.button("Hard Reset (Reload ROM)").clicked();
+                if soft || hard {
+                    // Apply the selected model and boot ROM
+                    emulator.config.model = config.model;
+                    emulator.config.boot_rom = config.boot_rom.clone();
+                    let result = if hard { emulator.hard_reset() } else { emulator.reset() };
+                    if let Err(e) = result {
+                        eprintln!("Failed to reset: {}", e);
+                    }
+                    ui.close();
+                }
+            });
+        });
+        ui.separator();
+        ui.menu_button("Speed", |ui| render_speed_menu(ui, &mut state.speed));
+        if ui.button("Game Boy Printer").clicked() {
+            state.show_printer = !state.show_printer;
             ui.close();
         }
         ui.separator();
-        if ui.checkbox(force_dmg, "Force Game Boy (DMG)").clicked() {
+        ui.label("Model:");
+        let mut model_changed = ui.radio_value(&mut config.model, None, "Auto").clicked();
+        for model in Model::ALL {
+            model_changed |= ui
+                .radio_value(&mut config.model, Some(model), model.description())
+                .clicked();
+        }
+        if model_changed {
             // Reload emulator if one is loaded
             if let Some(emulator) = emulator {
                 let rom_path = emulator.rom_path.to_str().unwrap_or("").to_string();
                 if !rom_path.is_empty() {
-                    if let Ok(new_emulator) = Emulator::new(&rom_path, *force_dmg) {
-                        *emulator = new_emulator;
-                        println!(
-                            "Reloaded ROM with {} mode",
-                            if *force_dmg { "DMG" } else { "Auto" }
-                        );
+                    match Emulator::with_config(&rom_path, config) {
+                        Ok(new_emulator) => {
+                            *emulator = new_emulator;
+                            println!("Reloaded ROM as {}", emulator.cpu.bus.model());
+                        }
+                        Err(e) => eprintln!("Failed to reload ROM: {}", e),
                     }
                 }
             }
         }
+        ui.separator();
+        render_boot_rom_selector(ui, config);
+        ui.separator();
+        render_rewind_settings(ui, emulator, config);
     });
 }
 
+fn render_speed_menu(ui: &mut egui::Ui, speed: &mut SpeedSettings) {
+    ui.checkbox(&mut speed.fast_forward, "Fast-Forward (F9, hold Shift)");
+    ui.label("Turbo speed:");
+    for turbo in TurboSpeed::ALL {
+        ui.radio_value(&mut speed.turbo, turbo, turbo.to_string());
+    }
+    ui.separator();
+    ui.label("Slow motion (F10):");
+    ui.radio_value(&mut speed.slow_motion, None, "Off");
+    for factor in SLOW_MOTION_FACTORS {
+        ui.radio_value(&mut speed.slow_motion, Some(factor), format!("{}×", factor));
+    }
+}
+
+fn render_rewind_settings(
+    ui: &mut egui::Ui,
+    emulator: &Option<Emulator>,
+    config: &mut EmulatorConfig,
+) {
+    let mut enabled = config.rewind.is_some();
+    if ui.checkbox(&mut enabled, "Rewind (hold Backspace)").changed() {
+        config.rewind = enabled.then(RewindConfig::default);
+    }
+
+    if let Some(rewind) = &mut config.rewind {
+        let mut budget_mb = rewind.memory_budget / (1024 * 1024);
+        ui.add(egui::Slider::new(&mut budget_mb, 8..=512).text("MB buffer"));
+        rewind.memory_budget = budget_mb * 1024 * 1024;
+
+        if let Some(emulator) = emulator {
+            ui.label(format!(
+                "Used: {:.1} MB",
+                emulator.rewind_memory_usage() as f32 / (1024.0 * 1024.0)
+            ));
+        }
+    }
+}
+
+/// Boot ROM selection, used the next time a ROM is loaded.
+fn render_boot_rom_selector(ui: &mut egui::Ui, config: &mut EmulatorConfig) {
+    let label = match config.boot_rom.as_ref().and_then(|path| path.file_name()) {
+        Some(name) => format!("Boot ROM: {}", name.to_string_lossy()),
+        None => "Boot ROM: None".to_string(),
+    };
+    ui.label(label);
+
+    if ui.button("Select Boot ROM...").clicked() {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Boot ROM", &["bin", "rom", "gb", "gbc"])
+            .set_title("ZetaBoy - Select Boot ROM")
+            .pick_file()
+        {
+            config.boot_rom = Some(path);
+        }
+        ui.close();
+    }
+    if config.boot_rom.is_some() && ui.button("Skip Boot ROM").clicked() {
+        config.boot_rom = None;
+        ui.close();
+    }
+}
+
+/// DMG palette selection, per layer. The choice is saved next to the ROM.
+fn render_video_menu(ui: &mut egui::Ui, emulator: &mut Option<Emulator>) {
+    ui.menu_button("Video", |ui| {
+        let Some(emulator) = emulator else {
+            ui.label("No ROM loaded");
+            return;
+        };
+
+        ui.label("DMG palette:");
+        let current = emulator.dmg_palettes();
+        let mut palettes = current;
+        let uniform =
+            (current.bg == current.obj0 && current.bg == current.obj1).then_some(current.bg);
+        ui.menu_button("All Layers", |ui| {
+            if let Some(selected) = render_palette_choices(ui, uniform) {
+                palettes = selected;
+            }
+        });
+        for layer in DmgLayer::ALL {
+            ui.menu_button(layer.to_string(), |ui| {
+                if let Some(selected) = render_palette_choices(ui, Some(palettes.layer(layer))) {
+                    // A file with separate layers applies the matching one
+                    palettes.set_layer(layer, selected.layer(layer));
+                }
+            });
+        }
+
+        if palettes != current
+            && let Err(e) = emulator.set_dmg_palettes(palettes)
+        {
+            eprintln!("Failed to save palettes: {}", e);
+        }
+
+        ui.separator();
+        render_compat_palette_menu(ui, emulator);
+    });
+}
+
+/// Colours of DMG games on CGB hardware, picked like the boot ROM button combinations.
+fn render_compat_palette_menu(ui: &mut egui::Ui, emulator: &mut Emulator) {
+    let enabled = emulator.cpu.bus.ppu.dmg_compat;
+    ui.add_enabled_ui(enabled, |ui| {
+        ui.menu_button("CGB Colours", |ui| {
+            let mut combo = emulator.config.compat_palette;
+            let mut changed = ui.radio_value(&mut combo, None, "Auto (by title)").clicked();
+            for choice in PaletteCombo::ALL {
+                changed |= ui.radio_value(&mut combo, Some(choice), choice.to_string()).clicked();
+            }
+            if changed {
+                emulator.set_compat_palette(combo);
+                ui.close();
+            }
+        });
+    })
+    .response
+    .on_disabled_hover_text("Only for DMG games on CGB or AGB hardware");
+}
+
+/// Lists the presets, marking `current`, and a custom file option. Returns the chosen palettes.
+fn render_palette_choices(ui: &mut egui::Ui, current: Option<Shades>) -> Option<DmgPalettes> {
+    let mut selected = None;
+    for preset in &PRESETS {
+        ui.horizontal(|ui| {
+            if ui.radio(current == Some(preset.shades), preset.name).clicked() {
+                selected = Some(DmgPalettes::uniform(preset.shades));
+            }
+            for color in preset.shades {
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                ui.painter().rect_filled(rect, 1.0, image::to_color32(color));
+            }
+        });
+    }
+
+    ui.separator();
+    if ui.button("Load from File...").clicked() {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Palette", &["pal", "txt"])
+            .set_title("ZetaBoy - Load Palette")
+            .pick_file()
+        {
+            match DmgPalettes::load(&path) {
+                Ok(palettes) => selected = Some(palettes),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        ui.close();
+    }
+
+    if selected.is_some() {
+        ui.close();
+    }
+    selected
+}
+
 fn render_audio_menu(
     ui: &mut egui::Ui,
     audio_mono: &mut bool,
@@ -148,11 +393,22 @@ fn render_audio_menu(
     });
 }
 
-fn render_debug_menu(ui: &mut egui::Ui, show_debug: &mut bool) {
+fn render_debug_menu(ui: &mut egui::Ui, state: &mut MenuState) {
+    let windows = [
+        ("CPU", &mut state.show_debug),
+        ("Memory Viewer", &mut state.show_memory),
+        ("VRAM Tiles", &mut state.show_tiles),
+        ("Tile Maps", &mut state.show_tile_maps),
+        ("OAM", &mut state.show_oam),
+        ("Palettes", &mut state.show_palettes),
+        ("Serial Console", &mut state.show_serial),
+    ];
     ui.menu_button("Debug", |ui| {
-        if ui.button("CPU").clicked() {
-            *show_debug = !*show_debug;
-            ui.close();
+        for (label, show) in windows {
+            if ui.button(label).clicked() {
+                *show = !*show;
+                ui.close();
+            }
         }
         ui.separator();
         ui.label("Display:");
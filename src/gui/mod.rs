@@ -1,6 +1,18 @@
 mod app;
+mod debugger;
+mod disassembly;
+mod image;
 mod input;
+mod memory_viewer;
 mod menu;
+mod oam_viewer;
+mod palette_viewer;
 mod panels;
+mod printer_viewer;
+mod save_slots;
+mod serial_console;
+mod speed;
+mod tile_map_viewer;
+mod tile_viewer;
 
 pub use app::EmulatorApp;
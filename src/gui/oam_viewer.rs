@@ -0,0 +1,126 @@
+use super::image;
+use crate::emulator::Emulator;
+use crate::emulator::ppu::{OAM_SPRITES, RenderSprite};
+
+const PREVIEW_SCALE: f32 = 3.0;
+
+const DROPPED_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 96, 96);
+
+/// Table of the 40 OAM entries.
+#[derive(Default)]
+pub struct OamViewer {
+    previews: Vec<Option<egui::TextureHandle>>,
+}
+
+impl OamViewer {
+    pub fn render(&mut self, ctx: &egui::Context, emulator: &Emulator, open: &mut bool) {
+        let ppu = &emulator.cpu.bus.ppu;
+        let sprite_height = ppu.sprite_height() as usize;
+        let dropped = ppu.dropped_sprite_lines();
+        self.previews.resize(OAM_SPRITES, None);
+
+        egui::Window::new("OAM")
+            .open(open)
+            .default_height(480.0)
+            .show(ctx, |ui| {
+                let dropped_count = dropped.iter().filter(|lines| !lines.is_empty()).count();
+                ui.label(format!(
+                    "Sprite size: 8x{}, {} sprite(s) dropped by the 10 per line limit",
+                    sprite_height, dropped_count
+                ));
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("oam_sprites")
+                        .striped(true)
+                        .spacing([12.0, 4.0])
+                        .show(ui, |ui| {
+                            for header in [
+                                "#",
+                                "",
+                                "X",
+                                "Y",
+                                "Tile",
+                                "Flags",
+                                "Palette",
+                                "Bank",
+                                "Dropped on lines",
+                            ] {
+                                ui.strong(header);
+                            }
+                            ui.end_row();
+
+                            for (index, dropped_lines) in dropped.iter().enumerate() {
+                                let sprite = ppu.oam_sprite(index);
+                                let pixels = ppu.render_sprite(index);
+                                let texture = image::update_texture(
+                                    ctx,
+                                    &mut self.previews[index],
+                                    &format!("oam_sprite_{}", index),
+                                    image::to_color_image(&pixels, 8, sprite_height),
+                                );
+
+                                ui.monospace(format!("{:2}", index));
+                                ui.add(egui::Image::new((
+                                    texture.id(),
+                                    egui::vec2(8.0, sprite_height as f32) * PREVIEW_SCALE,
+                                )));
+                                render_sprite_columns(ui, &sprite, ppu.cgb_mode);
+                                if dropped_lines.is_empty() {
+                                    ui.label("-");
+                                } else {
+                                    ui.colored_label(DROPPED_COLOR, format_lines(dropped_lines));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+    }
+}
+
+fn render_sprite_columns(ui: &mut egui::Ui, sprite: &RenderSprite, cgb_mode: bool) {
+    // Positions as stored in OAM, offset by (8, 16) from the screen
+    ui.monospace(format!("{:3}", sprite.screen_x + 8));
+    ui.monospace(format!("{:3}", sprite.screen_y + 16));
+    ui.monospace(format!("${:02X}", sprite.tile_index));
+
+    let flag = |set: bool, name: &'static str| if set { name } else { "-" };
+    ui.monospace(format!(
+        "{}{}{}",
+        flag(sprite.x_flip, "X"),
+        flag(sprite.y_flip, "Y"),
+        flag(sprite.bg_priority, "P")
+    ));
+
+    if cgb_mode {
+        ui.monospace(format!("OBJ {}", sprite.cgb_palette));
+        ui.monospace(sprite.cgb_vram_bank.to_string());
+    } else {
+        ui.monospace(if sprite.palette_index { "OBP1" } else { "OBP0" });
+        ui.monospace("-");
+    }
+}
+
+/// Formats sorted scanlines as ranges, e.g. `16-31, 40`.
+fn format_lines(lines: &[u8]) -> String {
+    let mut ranges: Vec<(u8, u8)> = Vec::new();
+    for &line in lines {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == line => *end = line,
+            _ => ranges.push((line, line)),
+        }
+    }
+
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,159 @@
+use super::image;
+use crate::emulator::Emulator;
+use crate::emulator::ppu::{PPU, ViewerPalette, rgb555_to_argb};
+
+const SWATCH_SIZE: egui::Vec2 = egui::vec2(20.0, 20.0);
+
+/// DMG palette registers with their address
+const DMG_PALETTES: [(ViewerPalette, u16); 3] = [
+    (ViewerPalette::Bgp, 0xFF47),
+    (ViewerPalette::Obp0, 0xFF48),
+    (ViewerPalette::Obp1, 0xFF49),
+];
+
+/// A colour in CGB palette RAM
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct CgbColor {
+    is_obj: bool,
+    palette: u8,
+    index: u8,
+}
+
+/// DMG palette registers and CGB palette RAM, editable in place.
+#[derive(Default)]
+pub struct PaletteViewer {
+    selected: Option<CgbColor>,
+    hex: String,
+}
+
+impl PaletteViewer {
+    pub fn render(&mut self, ctx: &egui::Context, emulator: &mut Emulator, open: &mut bool) {
+        let ppu = &mut emulator.cpu.bus.ppu;
+
+        egui::Window::new("Palettes")
+            .open(open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.strong("DMG (click a colour to cycle its shade)");
+                render_dmg_palettes(ui, ppu);
+
+                // DMG games on CGB are coloured through the CGB palettes
+                if ppu.cgb_mode || ppu.dmg_compat {
+                    ui.separator();
+                    ui.horizontal_top(|ui| {
+                        for is_obj in [false, true] {
+                            ui.vertical(|ui| {
+                                ui.strong(if is_obj { "OBJ (OCPD)" } else { "BG (BCPD)" });
+                                self.render_cgb_palettes(ui, ppu, is_obj);
+                            });
+                        }
+                    });
+
+                    if let Some(color) = self.selected {
+                        ui.separator();
+                        self.render_editor(ui, ppu, color);
+                    }
+                }
+            });
+    }
+
+    fn render_cgb_palettes(&mut self, ui: &mut egui::Ui, ppu: &PPU, is_obj: bool) {
+        egui::Grid::new(("cgb_palettes", is_obj)).show(ui, |ui| {
+            for palette in 0..8 {
+                ui.label(palette.to_string());
+                for index in 0..4 {
+                    let color = CgbColor {
+                        is_obj,
+                        palette,
+                        index,
+                    };
+                    let rgb555 = ppu.cgb_palette_rgb555(palette, index, is_obj);
+                    let selected = self.selected == Some(color);
+
+                    ui.vertical(|ui| {
+                        if swatch(ui, rgb555_to_argb(rgb555), selected).clicked() {
+                            self.selected = Some(color);
+                            self.hex = format!("{:04X}", rgb555);
+                        }
+                        ui.small(format!("{:04X}", rgb555));
+                    });
+                }
+                ui.end_row();
+            }
+        });
+    }
+
+    fn render_editor(&mut self, ui: &mut egui::Ui, ppu: &mut PPU, color: CgbColor) {
+        let rgb555 = ppu.cgb_palette_rgb555(color.palette, color.index, color.is_obj);
+        let kind = if color.is_obj { "OBJ" } else { "BG" };
+        ui.label(format!(
+            "{} palette {}, colour {}",
+            kind, color.palette, color.index
+        ));
+
+        let mut channels = [rgb555 & 0x1F, (rgb555 >> 5) & 0x1F, (rgb555 >> 10) & 0x1F];
+        let mut changed = false;
+        for (name, value) in ["R", "G", "B"].into_iter().zip(&mut channels) {
+            changed |= ui
+                .add(egui::Slider::new(value, 0..=31).text(name))
+                .changed();
+        }
+        let mut edited = changed.then(|| channels_to_rgb555(channels));
+
+        ui.horizontal(|ui| {
+            ui.label("RGB555: $");
+            let field = ui.add(egui::TextEdit::singleline(&mut self.hex).desired_width(40.0));
+            if field.lost_focus()
+                && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                && let Ok(value) = u16::from_str_radix(self.hex.trim(), 16)
+            {
+                edited = Some(value & 0x7FFF);
+            }
+        });
+
+        if let Some(value) = edited {
+            ppu.set_cgb_palette_rgb555(color.palette, color.index, color.is_obj, value);
+            self.hex = format!("{:04X}", value);
+        }
+    }
+}
+
+/// One row per register: its value, then each colour index and the shade it maps to.
+fn render_dmg_palettes(ui: &mut egui::Ui, ppu: &mut PPU) {
+    egui::Grid::new("dmg_palettes").show(ui, |ui| {
+        for (palette, address) in DMG_PALETTES {
+            let value = ppu.read_register(address);
+            ui.monospace(format!("{:<4} ${:02X}", palette.to_string(), value));
+
+            for index in 0..4 {
+                let shift = index * 2;
+                let shade = (value >> shift) & 0b11;
+                ui.horizontal(|ui| {
+                    let response = swatch(ui, ppu.palette_color(palette, index), false)
+                        .on_hover_text(format!("Colour {} uses shade {}", index, shade));
+                    if response.clicked() {
+                        let cycled = (shade + 1) & 0b11;
+                        ppu.write_register(address, (value & !(0b11 << shift)) | (cycled << shift));
+                    }
+                    ui.monospace(format!("{}→{}", index, shade));
+                });
+            }
+            ui.end_row();
+        }
+    });
+}
+
+fn swatch(ui: &mut egui::Ui, argb: u32, selected: bool) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(SWATCH_SIZE, egui::Sense::click());
+    ui.painter().rect_filled(rect, 2.0, image::to_color32(argb));
+    if selected {
+        let stroke = egui::Stroke::new(2.0, ui.visuals().selection.stroke.color);
+        ui.painter()
+            .rect_stroke(rect, 2.0, stroke, egui::StrokeKind::Outside);
+    }
+    response
+}
+
+fn channels_to_rgb555([r, g, b]: [u16; 3]) -> u16 {
+    r | (g << 5) | (b << 10)
+}
@@ -1,8 +1,15 @@
 use egui::RichText;
 
+use super::debugger::{self, DebuggerInput};
+use super::disassembly::{self, DisassemblyView};
 use crate::emulator::Emulator;
 
-pub fn render_debug_panel(ctx: &egui::Context, emulator: &Option<Emulator>) {
+pub fn render_debug_panel(
+    ctx: &egui::Context,
+    emulator: &mut Option<Emulator>,
+    debugger_input: &mut DebuggerInput,
+    disassembly_view: &mut DisassemblyView,
+) {
     egui::SidePanel::right("debug_panel")
         .default_width(140.0)
         .show(ctx, |ui| {
@@ -10,6 +17,8 @@ pub fn render_debug_panel(ctx: &egui::Context, emulator: &Option<Emulator>) {
             ui.separator();
 
             if let Some(emulator) = emulator {
+                ui.monospace(format!("Frame {}", emulator.frame_count()));
+                ui.separator();
                 ui.label("16-bit Registers");
                 ui.monospace(format!("PC = ${:04X}", emulator.cpu.reg.pc));
                 ui.monospace(format!("SP = ${:04X}", emulator.cpu.reg.sp));
@@ -41,6 +50,16 @@ pub fn render_debug_panel(ctx: &egui::Context, emulator: &Option<Emulator>) {
                     if emulator.cpu.reg.f.h { "1" } else { "0" },
                     if emulator.cpu.reg.f.c { "1" } else { "0" }
                 ));
+
+                ui.add_space(8.0);
+                ui.heading("Disassembly");
+                ui.separator();
+                disassembly::render_disassembly(ui, emulator, disassembly_view);
+
+                ui.add_space(8.0);
+                ui.heading("Debugger");
+                ui.separator();
+                debugger::render_debugger(ui, emulator, debugger_input);
             }
         });
 }
@@ -60,9 +79,21 @@ pub fn render_controls_panel(ctx: &egui::Context) {
             ui.separator();
             ui.label("F1/F2: Save/Load State");
             ui.separator();
+            ui.label("F5/F6: Quick Save/Load");
+            ui.separator();
+            ui.label("F7/F8: State Slot");
+            ui.separator();
+            ui.label("Backspace: Rewind");
+            ui.separator();
+            ui.label("Shift/F9: Turbo/Fast-Forward");
+            ui.separator();
+            ui.label("F10: Slow Motion");
+            ui.separator();
             ui.label("F3: Debug");
             ui.separator();
             ui.label("Esc: Pause");
+            ui.separator();
+            ui.label("F/N: Frame Advance/Step");
         });
     });
 }
@@ -0,0 +1,121 @@
+use std::sync::{Arc, Mutex};
+
+use super::image;
+use crate::emulator::Emulator;
+use crate::emulator::serial::{PRINT_WIDTH, Printer, Printout};
+
+const SCALE: f32 = 2.0;
+const MAX_HEIGHT: f32 = 480.0;
+
+/// Game Boy Printer on the serial port, with the pages it printed.
+#[derive(Default)]
+pub struct PrinterViewer {
+    /// Last printer used, kept to plug it back in for another game
+    printer: Option<Arc<Mutex<Printer>>>,
+    /// Page shown, the one being printed if `None`
+    selected: Option<usize>,
+    texture: Option<egui::TextureHandle>,
+}
+
+impl PrinterViewer {
+    pub fn render(&mut self, ctx: &egui::Context, emulator: &mut Emulator, open: &mut bool) {
+        // Picks up a printer connected from the command line
+        if let Some(printer) = emulator.printer() {
+            self.printer = Some(printer.clone());
+        }
+
+        egui::Window::new("Game Boy Printer")
+            .open(open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut connected = emulator.printer().is_some();
+                if ui.checkbox(&mut connected, "Connected").changed() {
+                    self.set_connected(emulator, connected);
+                }
+                let Some(printer) = &self.printer else {
+                    ui.label("Connect the printer and print from the game.");
+                    return;
+                };
+                let mut printer = printer.lock().unwrap();
+
+                ui.horizontal(|ui| {
+                    let pages = printer.pages().len();
+                    let printing = !printer.current_page().pixels.is_empty();
+                    if self.selected.is_some_and(|page| page >= pages) {
+                        self.selected = None;
+                    }
+                    egui::ComboBox::from_label("Page")
+                        .selected_text(page_name(self.selected))
+                        .show_ui(ui, |ui| {
+                            for page in 0..pages {
+                                ui.selectable_value(
+                                    &mut self.selected,
+                                    Some(page),
+                                    page_name(Some(page)),
+                                );
+                            }
+                            ui.selectable_value(&mut self.selected, None, page_name(None));
+                        });
+                    if ui
+                        .add_enabled(printing, egui::Button::new("Tear Off"))
+                        .on_hover_text("End the page being printed")
+                        .clicked()
+                    {
+                        printer.finish_page();
+                        self.selected = Some(pages);
+                    }
+                });
+
+                let page = match self.selected {
+                    Some(page) => &printer.pages()[page],
+                    None => printer.current_page(),
+                };
+                render_page(ui, ctx, &mut self.texture, page);
+            });
+    }
+
+    /// Plugs the printer in, keeping the pages printed for earlier games.
+    fn set_connected(&mut self, emulator: &mut Emulator, connected: bool) {
+        if !connected {
+            emulator.set_serial_device(None);
+            return;
+        }
+        match &self.printer {
+            Some(printer) => emulator.plug_printer(printer.clone()),
+            None => self.printer = Some(emulator.connect_printer()),
+        }
+    }
+}
+
+fn page_name(page: Option<usize>) -> String {
+    match page {
+        Some(page) => format!("{}", page + 1),
+        None => "Printing".to_string(),
+    }
+}
+
+fn render_page(
+    ui: &mut egui::Ui,
+    ctx: &egui::Context,
+    texture: &mut Option<egui::TextureHandle>,
+    page: &Printout,
+) {
+    if let Some(path) = &page.path {
+        ui.label(format!("Saved to {}", path.display()));
+    }
+    if page.pixels.is_empty() {
+        ui.label("Nothing printed");
+        return;
+    }
+
+    let texture = image::update_texture(
+        ctx,
+        texture,
+        "printer_page",
+        image::to_color_image(&page.pixels, PRINT_WIDTH, page.height()),
+    );
+    let size = egui::vec2(PRINT_WIDTH as f32, page.height() as f32) * SCALE;
+    egui::ScrollArea::vertical()
+        .max_height(MAX_HEIGHT)
+        .show(ui, |ui| ui.add(egui::Image::new((texture.id(), size))));
+}
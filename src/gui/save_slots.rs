@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use crate::emulator::Emulator;
+use crate::emulator::ppu::{HEIGHT, WIDTH};
+use crate::emulator::save_state::SaveSlot;
+use crate::utils::time::format_unix_time;
+
+const THUMBNAIL_SIZE: egui::Vec2 = egui::vec2(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0);
+
+/// Summary of a state file shown in the slot picker.
+struct SlotInfo {
+    label: String,
+    thumbnail: egui::TextureHandle,
+}
+
+/// Slot picker entries, reloaded when the state file changes on disk.
+#[derive(Default)]
+pub struct SlotThumbnails {
+    entries: HashMap<String, (SystemTime, Option<SlotInfo>)>,
+}
+
+impl SlotThumbnails {
+    fn get(
+        &mut self,
+        ctx: &egui::Context,
+        emulator: &Emulator,
+        slot: SaveSlot,
+    ) -> Option<&SlotInfo> {
+        let path = emulator.get_state_path(slot);
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+
+        let stale = self
+            .entries
+            .get(&path)
+            .is_none_or(|(time, _)| *time != modified);
+        if stale {
+            let info = emulator.state_header(slot).ok().map(|header| {
+                let image = egui::ColorImage::from_rgba_unmultiplied(
+                    [WIDTH, HEIGHT],
+                    &header
+                        .thumbnail
+                        .iter()
+                        .flat_map(|&pixel| {
+                            [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8, 0xFF]
+                        })
+                        .collect::<Vec<u8>>(),
+                );
+                SlotInfo {
+                    label: format!("{} UTC", format_unix_time(header.timestamp)),
+                    thumbnail: ctx.load_texture(&path, image, egui::TextureOptions::NEAREST),
+                }
+            });
+            self.entries.insert(path.clone(), (modified, info));
+        }
+
+        self.entries.get(&path).and_then(|(_, info)| info.as_ref())
+    }
+}
+
+/// Lists every slot with its thumbnail. Returns the slot that was clicked.
+pub fn render_slot_list(
+    ui: &mut egui::Ui,
+    thumbnails: &mut SlotThumbnails,
+    emulator: &Emulator,
+    selected: u8,
+) -> Option<SaveSlot> {
+    let mut clicked = None;
+
+    egui::ScrollArea::vertical()
+        .max_height(480.0)
+        .show(ui, |ui| {
+            for slot in SaveSlot::all() {
+                let info = thumbnails.get(ui.ctx(), emulator, slot);
+                let mut title = slot.to_string();
+                if slot == SaveSlot::Numbered(selected) {
+                    title.push_str(" (selected)");
+                }
+
+                let response = ui
+                    .horizontal(|ui| {
+                        match info {
+                            Some(info) => {
+                                ui.add(
+                                    egui::Image::new(&info.thumbnail)
+                                        .fit_to_exact_size(THUMBNAIL_SIZE),
+                                );
+                            }
+                            None => {
+                                ui.allocate_exact_size(THUMBNAIL_SIZE, egui::Sense::hover());
+                            }
+                        }
+                        let label = match info {
+                            Some(info) => format!("{}\n{}", title, info.label),
+                            None => format!("{}\nEmpty", title),
+                        };
+                        ui.button(label).clicked()
+                    })
+                    .inner;
+
+                if response {
+                    clicked = Some(slot);
+                }
+            }
+        });
+
+    clicked
+}
@@ -0,0 +1,100 @@
+use crate::emulator::Emulator;
+use crate::emulator::serial::SerialTransfer;
+
+/// Transfers kept in the console, oldest dropped first
+const LOG_CAPACITY: usize = 0x4000;
+
+/// Log of serial port transfers, with the bytes sent and received.
+#[derive(Default)]
+pub struct SerialConsole {
+    log: Vec<SerialTransfer>,
+    /// Show the bytes as text instead of one transfer per row
+    text: bool,
+}
+
+impl SerialConsole {
+    pub fn render(&mut self, ctx: &egui::Context, emulator: &mut Emulator, open: &mut bool) {
+        self.log.extend(emulator.take_serial_transfers());
+        if self.log.len() > LOG_CAPACITY {
+            self.log.drain(..self.log.len() - LOG_CAPACITY);
+        }
+
+        egui::Window::new("Serial Console")
+            .open(open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let mut print = emulator.config.print_serial;
+                    if ui.checkbox(&mut print, "Print to stdout").changed() {
+                        emulator.set_print_serial(print);
+                    }
+                    ui.checkbox(&mut self.text, "As text");
+                    if ui.button("Clear").clicked() {
+                        self.log.clear();
+                    }
+                });
+                ui.separator();
+
+                if self.text {
+                    self.render_text(ui);
+                } else {
+                    self.render_transfers(ui);
+                }
+            });
+    }
+
+    /// One row per transfer: who drove the clock, then each byte in hex and ASCII.
+    fn render_transfers(&self, ui: &mut egui::Ui) {
+        ui.monospace("Clock  Sent      Received");
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::vertical()
+            .stick_to_bottom(true)
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, self.log.len(), |ui, rows| {
+                for transfer in &self.log[rows] {
+                    let clock = if transfer.internal_clock {
+                        "Int"
+                    } else {
+                        "Ext"
+                    };
+                    ui.monospace(format!(
+                        "{:<6} {:02X} {}      {:02X} {}",
+                        clock,
+                        transfer.sent,
+                        printable(transfer.sent),
+                        transfer.received,
+                        printable(transfer.received),
+                    ));
+                }
+            });
+    }
+
+    /// The bytes sent and received, each as a block of text.
+    fn render_text(&self, ui: &mut egui::Ui) {
+        render_text_block(ui, "Sent", self.log.iter().map(|t| t.sent));
+        render_text_block(ui, "Received", self.log.iter().map(|t| t.received));
+    }
+}
+
+fn render_text_block(ui: &mut egui::Ui, label: &str, bytes: impl Iterator<Item = u8>) {
+    ui.strong(label);
+    let text: String = bytes.map(text_char).collect();
+    egui::ScrollArea::vertical()
+        .id_salt(label)
+        .max_height(160.0)
+        .stick_to_bottom(true)
+        .show(ui, |ui| ui.monospace(text));
+}
+
+fn printable(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+/// Like `printable`, keeping line breaks.
+fn text_char(byte: u8) -> char {
+    if byte == b'\n' { '\n' } else { printable(byte) }
+}
@@ -0,0 +1,99 @@
+//! Emulation speed: turbo/fast-forward and slow motion.
+
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TurboSpeed {
+    X2,
+    X4,
+    X8,
+    /// As fast as the host allows, with audio muted
+    Uncapped,
+}
+
+impl TurboSpeed {
+    pub const ALL: [TurboSpeed; 4] = [
+        TurboSpeed::X2,
+        TurboSpeed::X4,
+        TurboSpeed::X8,
+        TurboSpeed::Uncapped,
+    ];
+
+    fn speed(self) -> Speed {
+        match self {
+            TurboSpeed::X2 => Speed::Multiplier(2.0),
+            TurboSpeed::X4 => Speed::Multiplier(4.0),
+            TurboSpeed::X8 => Speed::Multiplier(8.0),
+            TurboSpeed::Uncapped => Speed::Uncapped,
+        }
+    }
+}
+
+/// Slow-motion factors selectable from the menu, cycled by the hotkey.
+pub const SLOW_MOTION_FACTORS: [f32; 2] = [0.5, 0.25];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Speed {
+    /// Fraction of real hardware speed
+    Multiplier(f32),
+    Uncapped,
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Speed::Multiplier(multiplier) => write!(f, "{}×", multiplier),
+            Speed::Uncapped => write!(f, "Uncapped"),
+        }
+    }
+}
+
+pub struct SpeedSettings {
+    /// Speed used while turbo is held or fast-forward is toggled on
+    pub turbo: TurboSpeed,
+    /// Turbo key held
+    pub turbo_held: bool,
+    pub fast_forward: bool,
+    /// Slow-motion factor, used when not fast-forwarding
+    pub slow_motion: Option<f32>,
+}
+
+impl Default for SpeedSettings {
+    fn default() -> Self {
+        Self {
+            turbo: TurboSpeed::X4,
+            turbo_held: false,
+            fast_forward: false,
+            slow_motion: None,
+        }
+    }
+}
+
+impl SpeedSettings {
+    /// The speed to emulate at right now. Turbo takes precedence over slow motion.
+    pub fn current(&self) -> Speed {
+        if self.turbo_held || self.fast_forward {
+            self.turbo.speed()
+        } else {
+            Speed::Multiplier(self.slow_motion.unwrap_or(1.0))
+        }
+    }
+
+    /// Off -> 0.5× -> 0.25× -> off.
+    pub fn cycle_slow_motion(&mut self) {
+        self.slow_motion = match self.slow_motion {
+            None => Some(SLOW_MOTION_FACTORS[0]),
+            Some(factor) => SLOW_MOTION_FACTORS
+                .iter()
+                .position(|&f| f == factor)
+                .and_then(|i| SLOW_MOTION_FACTORS.get(i + 1))
+                .copied(),
+        };
+    }
+}
+
+impl fmt::Display for TurboSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.speed().fmt(f)
+    }
+}
@@ -0,0 +1,147 @@
+use super::image;
+use crate::emulator::Emulator;
+use crate::emulator::ppu::{HEIGHT, MapLayout, TILE_MAP_SIZE, TileMap, TileMapEntry, WIDTH};
+
+const SCALE: f32 = 2.0;
+
+const VIEWPORT_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 64, 64);
+const WINDOW_COLOR: egui::Color32 = egui::Color32::from_rgb(64, 160, 255);
+
+/// Full 256x256 BG maps with the visible screen area overlaid.
+pub struct TileMapViewer {
+    map: TileMap,
+    show_viewport: bool,
+    texture: Option<egui::TextureHandle>,
+}
+
+impl Default for TileMapViewer {
+    fn default() -> Self {
+        Self {
+            map: TileMap::Map9800,
+            show_viewport: true,
+            texture: None,
+        }
+    }
+}
+
+impl TileMapViewer {
+    pub fn render(&mut self, ctx: &egui::Context, emulator: &Emulator, open: &mut bool) {
+        let ppu = &emulator.cpu.bus.ppu;
+        let layout = ppu.map_layout();
+
+        egui::Window::new("Tile Maps")
+            .open(open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for map in TileMap::ALL {
+                        let mut label = map.to_string();
+                        if map == layout.bg_map {
+                            label.push_str(" (BG)");
+                        }
+                        if layout.window_enabled && map == layout.window_map {
+                            label.push_str(" (Window)");
+                        }
+                        ui.selectable_value(&mut self.map, map, label);
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.show_viewport, "Show screen area");
+                });
+                ui.monospace(format!(
+                    "SCX/SCY: {:3},{:3}   WX/WY: {:3},{:3}",
+                    layout.scx, layout.scy, layout.wx, layout.wy
+                ));
+
+                let pixels = ppu.render_tile_map(self.map);
+                let texture = image::update_texture(
+                    ctx,
+                    &mut self.texture,
+                    "tile_map",
+                    image::to_color_image(&pixels, TILE_MAP_SIZE, TILE_MAP_SIZE),
+                );
+                let size = egui::Vec2::splat(TILE_MAP_SIZE as f32 * SCALE);
+                let response =
+                    ui.add(egui::Image::new((texture.id(), size)).sense(egui::Sense::hover()));
+
+                if self.show_viewport {
+                    draw_screen_area(ui, response.rect, self.map, &layout);
+                }
+
+                ui.separator();
+                let hovered = response.hover_pos().map(|pos| {
+                    let offset = (pos - response.rect.min) / (SCALE * 8.0);
+                    let column = (offset.x as usize).min(31);
+                    let row = (offset.y as usize).min(31);
+                    ppu.tile_map_entry(self.map, column, row)
+                });
+                match hovered {
+                    Some(entry) => render_entry_info(ui, &entry, ppu.cgb_mode),
+                    None => {
+                        ui.label("Hover a tile for its map entry");
+                    }
+                }
+            });
+    }
+}
+
+/// Outlines the part of `map` shown as BG (wrapping around the edges) and as window.
+fn draw_screen_area(ui: &egui::Ui, rect: egui::Rect, map: TileMap, layout: &MapLayout) {
+    let painter = ui.painter_at(rect);
+    let stroke = |color| egui::Stroke::new(1.5, color);
+    let map_size = TILE_MAP_SIZE as f32;
+
+    if map == layout.bg_map {
+        let screen = egui::vec2(WIDTH as f32, HEIGHT as f32) * SCALE;
+        // The viewport wraps, so draw it again shifted one map left and/or up
+        for dx in [0.0, -map_size] {
+            for dy in [0.0, -map_size] {
+                let min =
+                    rect.min + egui::vec2(layout.scx as f32 + dx, layout.scy as f32 + dy) * SCALE;
+                painter.rect_stroke(
+                    egui::Rect::from_min_size(min, screen),
+                    0.0,
+                    stroke(VIEWPORT_COLOR),
+                    egui::StrokeKind::Inside,
+                );
+            }
+        }
+    }
+
+    // The window always shows its map from the top-left corner
+    let window_width = (WIDTH + 7).saturating_sub(layout.wx as usize).min(WIDTH);
+    let window_height = HEIGHT.saturating_sub(layout.wy as usize);
+    if layout.window_enabled && map == layout.window_map && window_width > 0 && window_height > 0 {
+        let size = egui::vec2(window_width as f32, window_height as f32) * SCALE;
+        painter.rect_stroke(
+            egui::Rect::from_min_size(rect.min, size),
+            0.0,
+            stroke(WINDOW_COLOR),
+            egui::StrokeKind::Inside,
+        );
+    }
+}
+
+fn render_entry_info(ui: &mut egui::Ui, entry: &TileMapEntry, cgb_mode: bool) {
+    ui.monospace(format!(
+        "Map ${:04X}: tile ${:02X}, data at ${:04X}",
+        entry.address, entry.tile_id, entry.tile_address
+    ));
+    if cgb_mode {
+        let attributes = &entry.attributes;
+        let flag = |set: bool, name: &str| {
+            if set {
+                name.to_string()
+            } else {
+                "-".repeat(name.len())
+            }
+        };
+        ui.monospace(format!(
+            "Palette {}, bank {}, {} {} {}",
+            attributes.palette,
+            attributes.vram_bank,
+            flag(attributes.x_flip, "X-flip"),
+            flag(attributes.y_flip, "Y-flip"),
+            flag(attributes.bg_priority, "Priority"),
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+use super::image;
+use crate::emulator::Emulator;
+use crate::emulator::ppu::{
+    TILE_SHEET_COLUMNS, TILE_SHEET_HEIGHT, TILE_SHEET_WIDTH, TILES_PER_BANK, ViewerPalette,
+    tile_address,
+};
+
+const SCALE: f32 = 2.0;
+
+/// Sheet of every tile in VRAM, per bank.
+pub struct TileViewer {
+    palette: ViewerPalette,
+    textures: [Option<egui::TextureHandle>; 2],
+}
+
+impl Default for TileViewer {
+    fn default() -> Self {
+        Self {
+            palette: ViewerPalette::Bgp,
+            textures: [None, None],
+        }
+    }
+}
+
+impl TileViewer {
+    pub fn render(&mut self, ctx: &egui::Context, emulator: &Emulator, open: &mut bool) {
+        let ppu = &emulator.cpu.bus.ppu;
+        let palettes = ViewerPalette::all(ppu.cgb_mode);
+        if !palettes.contains(&self.palette) {
+            self.palette = ViewerPalette::Bgp;
+        }
+        let banks = if ppu.cgb_mode { 2 } else { 1 };
+
+        egui::Window::new("VRAM Tiles")
+            .open(open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::ComboBox::from_label("Palette")
+                    .selected_text(self.palette.to_string())
+                    .show_ui(ui, |ui| {
+                        for palette in palettes {
+                            ui.selectable_value(&mut self.palette, palette, palette.to_string());
+                        }
+                    });
+
+                let mut hovered = None;
+                ui.horizontal(|ui| {
+                    for bank in 0..banks {
+                        ui.vertical(|ui| {
+                            ui.label(format!("Bank {}", bank));
+                            let pixels = ppu.render_tile_sheet(bank, self.palette);
+                            let texture = image::update_texture(
+                                ctx,
+                                &mut self.textures[bank],
+                                &format!("vram_tiles_{}", bank),
+                                image::to_color_image(&pixels, TILE_SHEET_WIDTH, TILE_SHEET_HEIGHT),
+                            );
+                            let size = egui::vec2(
+                                TILE_SHEET_WIDTH as f32 * SCALE,
+                                TILE_SHEET_HEIGHT as f32 * SCALE,
+                            );
+                            let response = ui.add(
+                                egui::Image::new((texture.id(), size)).sense(egui::Sense::hover()),
+                            );
+
+                            if let Some(pos) = response.hover_pos() {
+                                let offset = (pos - response.rect.min) / (SCALE * 8.0);
+                                let column = (offset.x as usize).min(TILE_SHEET_COLUMNS - 1);
+                                let row = offset.y as usize;
+                                let tile =
+                                    (row * TILE_SHEET_COLUMNS + column).min(TILES_PER_BANK - 1);
+                                hovered = Some((bank, tile));
+                            }
+                        });
+                    }
+                });
+
+                ui.separator();
+                match hovered {
+                    Some((bank, tile)) => ui.monospace(tile_info(bank, tile)),
+                    None => ui.label("Hover a tile for its address and index"),
+                };
+            });
+    }
+}
+
+/// Address of a tile and the index BG/OBJ maps use to refer to it.
+///
+/// Tiles 0-255 are indexed from $8000, tiles 128-383 from $8800 (BG and window
+/// with LCDC bit 4 clear), so tiles 128-255 have both.
+fn tile_info(bank: usize, tile: usize) -> String {
+    let mut info = format!(
+        "Tile {} (bank {}) at ${:04X}",
+        tile,
+        bank,
+        tile_address(tile)
+    );
+    if tile < 256 {
+        info.push_str(&format!(", index ${:02X} from $8000", tile));
+    }
+    if tile >= 128 {
+        info.push_str(&format!(", index ${:02X} from $8800", tile as u8));
+    }
+    info
+}
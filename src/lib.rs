@@ -1,29 +1,122 @@
+#[cfg(feature = "gui")]
 mod audio;
-mod emulator;
+pub mod cli;
+pub mod conformance;
+pub mod emulator;
+#[cfg(feature = "gui")]
 mod gui;
+#[cfg(feature = "gui")]
 mod rendering;
+pub mod screenshot;
 mod utils;
 
 use std::error::Error;
+use std::sync::{Arc, Mutex};
 
+#[cfg(all(feature = "gui", feature = "audio"))]
 use audio::AudioManager;
-use emulator::Emulator;
+use cli::{LinkOption, Options};
+use emulator::serial::LinkCable;
+#[cfg(feature = "gui")]
+use emulator::ppu::{HEIGHT, WIDTH};
+#[cfg(feature = "gui")]
 use gui::EmulatorApp;
 
-pub const PRINT_STATE: bool = false; // Print CPU state after each instruction
-pub const PRINT_CART_INFO: bool = false; // Prints cartridge information
+pub use emulator::joypad::Buttons;
+pub use emulator::{Emulator, EmulatorConfig};
 
-pub fn run(rom_path: Option<&str>) -> Result<(), Box<dyn Error>> {
-    let (_audio_manager, audio_sender) = AudioManager::new()?;
-
-    let emulator = rom_path
-        .map(|path| Emulator::new(path, false))
+/// Runs the emulator as described by the command-line `options`.
+pub fn run(options: &Options) -> Result<(), Box<dyn Error>> {
+    let emulator = options
+        .rom_path
+        .as_deref()
+        .map(|path| load_emulator(path, options))
         .transpose()?;
-    let app = EmulatorApp::new(emulator, Some(audio_sender));
+
+    match emulator {
+        Some(emulator) if options.headless => run_headless(emulator, options),
+        emulator => run_gui(emulator, options),
+    }
+}
+
+fn load_emulator(rom_path: &str, options: &Options) -> Result<Emulator, Box<dyn Error>> {
+    let mut emulator = Emulator::with_config(rom_path, &options.config)?;
+
+    if let Some(state_path) = &options.load_state {
+        emulator.load_state_from(state_path)?;
+        println!("Loaded state from {}", state_path);
+    }
+
+    if let Some(link) = &options.link {
+        let cable = match link {
+            LinkOption::Listen(address) => {
+                println!("Waiting for a link cable connection on {}", address);
+                LinkCable::listen(address)?
+            }
+            LinkOption::Connect(address) => LinkCable::connect(address)?,
+        };
+        println!("Link cable connected");
+        emulator.set_serial_device(Some(Arc::new(Mutex::new(cable))));
+    }
+    if options.printer {
+        emulator.connect_printer();
+        println!("Printer connected");
+    }
+
+    Ok(emulator)
+}
+
+/// Runs a fixed number of frames without a window.
+fn run_headless(mut emulator: Emulator, options: &Options) -> Result<(), Box<dyn Error>> {
+    for _ in 0..options.frames.unwrap_or(0) {
+        emulator.run_frame();
+    }
+
+    if let Some(path) = &options.screenshot {
+        let (width, height) = emulator.frame_size();
+        screenshot::save_png(path, emulator.framebuffer(), width, height)?;
+        println!("Saved screenshot to {}", path.display());
+    }
+
+    emulator.save_sram()
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_gui(_emulator: Option<Emulator>, _options: &Options) -> Result<(), Box<dyn Error>> {
+    Err("This build has no GUI support, use --headless".into())
+}
+
+#[cfg(feature = "gui")]
+fn run_gui(emulator: Option<Emulator>, options: &Options) -> Result<(), Box<dyn Error>> {
+    #[cfg(feature = "audio")]
+    let (_audio_manager, audio_sender) = if options.mute {
+        (None, None)
+    } else {
+        let (manager, sender) = AudioManager::new()?;
+        (Some(manager), Some(sender))
+    };
+    #[cfg(not(feature = "audio"))]
+    let audio_sender = None;
+
+    let (width, height) = emulator.as_ref().map_or((WIDTH, HEIGHT), Emulator::frame_size);
+    let mut app = EmulatorApp::new(emulator, audio_sender);
+    app.config = EmulatorConfig {
+        rewind: app.config.rewind,
+        ..options.config.clone()
+    };
+
+    // Leave room for the menu bar and the controls panel
+    let inner_size = match options.scale {
+        Some(scale) => [
+            (width as u32 * scale) as f32,
+            (height as u32 * scale) as f32 + 60.0,
+        ],
+        None => [800.0, 600.0],
+    };
 
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([800.0, 600.0])
+            .with_inner_size(inner_size)
             .with_min_inner_size([400.0, 300.0])
             .with_title("ZetaBoy - Game Boy (Color) Emulator")
             .with_icon(
@@ -1,8 +1,20 @@
+use zetaboy::cli::{Options, USAGE};
+
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    let rom_path = args.get(1).map(|s| s.as_str());
-    
-    if let Err(e) = zetaboy::run(rom_path) {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match Options::parse(&args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = zetaboy::run(&options) {
         println!("Application error: {}", e);
         std::process::exit(1);
     }
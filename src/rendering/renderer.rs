@@ -8,8 +8,13 @@ pub struct GlContext {
     pub texture: glow::Texture,
 }
 
-pub fn render_with_shader(gl: &glow::Context, ctx: &GlContext, image_buffer: &[u32]) {
-    use crate::emulator::ppu::{HEIGHT, WIDTH};
+/// Draws `image_buffer`, a frame of `width` x `height` pixels.
+pub fn render_with_shader(
+    gl: &glow::Context,
+    ctx: &GlContext,
+    image_buffer: &[u32],
+    (width, height): (usize, usize),
+) {
     use eframe::egui_glow::glow::HasContext;
 
     unsafe {
@@ -18,7 +23,7 @@ pub fn render_with_shader(gl: &glow::Context, ctx: &GlContext, image_buffer: &[u
         gl.bind_texture(glow::TEXTURE_2D, Some(ctx.texture));
 
         // Convert buffer to RGBA format
-        let mut rgba_data = Vec::with_capacity(WIDTH * HEIGHT * 4);
+        let mut rgba_data = Vec::with_capacity(width * height * 4);
         for &pixel in image_buffer {
             rgba_data.push((pixel >> 16) as u8); // R
             rgba_data.push((pixel >> 8) as u8); // G
@@ -31,8 +36,8 @@ pub fn render_with_shader(gl: &glow::Context, ctx: &GlContext, image_buffer: &[u
             glow::TEXTURE_2D,
             0,
             glow::RGBA as i32,
-            WIDTH as i32,
-            HEIGHT as i32,
+            width as i32,
+            height as i32,
             0,
             glow::RGBA,
             glow::UNSIGNED_BYTE,
@@ -49,7 +54,7 @@ pub fn render_with_shader(gl: &glow::Context, ctx: &GlContext, image_buffer: &[u
 
         // Set resolution uniform
         if let Some(location) = gl.get_uniform_location(ctx.program, "u_resolution") {
-            gl.uniform_2_f32(Some(&location), WIDTH as f32, HEIGHT as f32);
+            gl.uniform_2_f32(Some(&location), width as f32, height as f32);
         }
 
         // Draw the quad
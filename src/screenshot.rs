@@ -0,0 +1,58 @@
+//! Conversion between 0xAARRGGBB pixel buffers and PNG files.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// Writes `pixels` (0xAARRGGBB, row-major) to `path` as an RGBA PNG.
+pub fn save_png<P: AsRef<Path>>(
+    path: P,
+    pixels: &[u32],
+    width: usize,
+    height: usize,
+) -> Result<(), Box<dyn Error>> {
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut rgba_data = Vec::with_capacity(width * height * 4);
+    for &pixel in pixels {
+        rgba_data.push((pixel >> 16) as u8); // R
+        rgba_data.push((pixel >> 8) as u8); // G
+        rgba_data.push(pixel as u8); // B
+        rgba_data.push((pixel >> 24) as u8); // A
+    }
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&rgba_data)?;
+    Ok(())
+}
+
+/// Reads a PNG from `path`, returning its pixels as 0xAARRGGBB along with width and height.
+pub fn load_png<P: AsRef<Path>>(path: P) -> Result<(Vec<u32>, usize, usize), Box<dyn Error>> {
+    let file = File::open(path)?;
+    let mut decoder = png::Decoder::new(BufReader::new(file));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info()?;
+    let mut data = vec![0; reader.output_buffer_size().ok_or("PNG too large")?];
+    let info = reader.next_frame(&mut data)?;
+    let data = &data[..info.buffer_size()];
+
+    let pixels = match info.color_type {
+        png::ColorType::Grayscale => data.iter().map(|&v| rgb(v, v, v)).collect(),
+        png::ColorType::GrayscaleAlpha => data.chunks_exact(2).map(|p| rgb(p[0], p[0], p[0])).collect(),
+        png::ColorType::Rgb => data.chunks_exact(3).map(|p| rgb(p[0], p[1], p[2])).collect(),
+        png::ColorType::Rgba => data.chunks_exact(4).map(|p| rgb(p[0], p[1], p[2])).collect(),
+        png::ColorType::Indexed => return Err("Unexpected indexed PNG output".into()),
+    };
+
+    Ok((pixels, info.width as usize, info.height as usize))
+}
+
+/// Packs an opaque colour as 0xAARRGGBB.
+fn rgb(r: u8, g: u8, b: u8) -> u32 {
+    0xFF000000 | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
+}
@@ -9,4 +9,25 @@ pub mod bits {
     pub const BIT_5: u8 = 0b00100000;
     pub const BIT_6: u8 = 0b01000000;
     pub const BIT_7: u8 = 0b10000000;
-}
\ No newline at end of file
+}
+
+pub mod time {
+    /// Formats seconds since the Unix epoch as `YYYY-MM-DD HH:MM` (UTC).
+    pub fn format_unix_time(secs: u64) -> String {
+        let days = (secs / 86400) as i64;
+        let (hour, minute) = ((secs % 86400) / 3600, (secs % 3600) / 60);
+
+        // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, hour, minute)
+    }
+}
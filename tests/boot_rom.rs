@@ -0,0 +1,92 @@
+//! Boot ROM mapping tests, using a minimal synthetic boot ROM.
+
+mod common;
+
+use std::path::PathBuf;
+
+use zetaboy::emulator::model::Model;
+use zetaboy::{Emulator, EmulatorConfig};
+
+fn write_boot_rom(name: &str, data: &[u8]) -> PathBuf {
+    let path = common::temp_path(name);
+    std::fs::write(&path, data).expect("Failed to write test boot ROM");
+    path
+}
+
+/// Sets SP, then unmaps itself from 0x00FC so execution falls through to 0x0100.
+fn boot_rom() -> Vec<u8> {
+    let mut boot = vec![0; 0x100];
+    boot[0x00..0x06].copy_from_slice(&[0x31, 0xFE, 0xFF, 0xC3, 0xFC, 0x00]); // LD SP,$FFFE; JP $00FC
+    boot[0xFC..0x100].copy_from_slice(&[0x3E, 0x01, 0xE0, 0x50]); // LD A,1; LDH ($50),A
+    boot
+}
+
+#[test]
+fn boot_rom_is_mapped_until_ff50_write() {
+    let rom_path = common::write_rom("boot-cart", common::LOOP, &[]);
+    let boot_path = write_boot_rom("boot.bin", &boot_rom());
+    let config = EmulatorConfig {
+        boot_rom: Some(boot_path.clone()),
+        ..Default::default()
+    };
+    let mut emulator = Emulator::with_config(rom_path.to_str().unwrap(), &config).unwrap();
+    let _ = std::fs::remove_file(rom_path);
+    let _ = std::fs::remove_file(boot_path);
+
+    let bus = &emulator.cpu.bus;
+    assert_eq!(emulator.cpu.reg.pc, 0x0000);
+    assert!(bus.is_boot_rom_mapped());
+    assert_eq!(bus.read_byte(0x0000), 0x31);
+    assert_eq!(
+        bus.read_byte(0x0101),
+        0xC3,
+        "cartridge visible above the boot ROM"
+    );
+
+    emulator.run_frame();
+
+    let bus = &emulator.cpu.bus;
+    assert!(!bus.is_boot_rom_mapped());
+    assert_eq!(bus.read_byte(0x0000), 0x00);
+    assert_eq!(emulator.cpu.reg.pc, 0x0150);
+}
+
+#[test]
+fn invalid_boot_rom_size_is_rejected() {
+    let rom_path = common::write_rom("boot-size-cart", common::LOOP, &[]);
+    let boot_path = write_boot_rom("boot-size.bin", &[0; 0x200]);
+    let config = EmulatorConfig {
+        boot_rom: Some(boot_path.clone()),
+        ..Default::default()
+    };
+    let result = Emulator::with_config(rom_path.to_str().unwrap(), &config);
+    let _ = std::fs::remove_file(rom_path);
+    let _ = std::fs::remove_file(boot_path);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn logo_sound_is_playing_without_a_boot_rom() {
+    let load = |name, model| {
+        let config = EmulatorConfig {
+            model: Some(model),
+            ..Default::default()
+        };
+        common::load_rom(name, common::LOOP, &[], &config)
+    };
+    let dmg = load("boot-apu-dmg", Model::Dmg);
+    let sgb = load("boot-apu-sgb", Model::Sgb);
+
+    assert_eq!(
+        dmg.cpu.bus.read_byte(0xFF26),
+        0xF1,
+        "channel 1 on after the ding"
+    );
+    assert_eq!(dmg.cpu.bus.read_byte(0xFF25), 0xF3);
+    assert_eq!(
+        sgb.cpu.bus.read_byte(0xFF26),
+        0xF0,
+        "the SGB boot ROM is silent"
+    );
+}
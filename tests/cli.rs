@@ -0,0 +1,145 @@
+//! Command-line option parsing and validation.
+
+use std::path::PathBuf;
+
+use zetaboy::cli::{LinkOption, Options};
+use zetaboy::emulator::model::Model;
+
+fn parse(args: &[&str]) -> Result<Option<Options>, String> {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    Options::parse(&args)
+}
+
+fn parse_ok(args: &[&str]) -> Options {
+    parse(args)
+        .unwrap_or_else(|e| panic!("{:?} rejected: {}", args, e))
+        .expect("Help requested")
+}
+
+fn parse_err(args: &[&str]) -> String {
+    match parse(args) {
+        Ok(_) => panic!("{:?} accepted", args),
+        Err(e) => e,
+    }
+}
+
+#[test]
+fn options_are_parsed() {
+    let options = parse_ok(&[
+        "--model",
+        "sgb",
+        "--boot-rom",
+        "sgb.bin",
+        "--load-state",
+        "game.state",
+        "--scale",
+        "3",
+        "--mute",
+        "--print-serial",
+        "game.gb",
+    ]);
+    assert_eq!(options.rom_path.as_deref(), Some("game.gb"));
+    assert_eq!(options.config.model, Some(Model::Sgb));
+    assert_eq!(options.config.boot_rom, Some(PathBuf::from("sgb.bin")));
+    assert_eq!(options.load_state.as_deref(), Some("game.state"));
+    assert_eq!(options.scale, Some(3));
+    assert!(options.mute);
+    assert!(options.config.print_serial);
+    assert!(!options.headless);
+
+    let options = parse_ok(&[
+        "--headless",
+        "--frames",
+        "60",
+        "--screenshot",
+        "out.png",
+        "a.gb",
+    ]);
+    assert!(options.headless);
+    assert_eq!(options.frames, Some(60));
+    assert_eq!(options.screenshot, Some(PathBuf::from("out.png")));
+
+    let options = parse_ok(&["--link-connect", "localhost:8765", "a.gb"]);
+    assert_eq!(
+        options.link,
+        Some(LinkOption::Connect("localhost:8765".to_string()))
+    );
+}
+
+#[test]
+fn no_arguments_opens_the_window_without_a_rom() {
+    let options = parse_ok(&[]);
+    assert!(options.rom_path.is_none());
+    assert!(!options.headless);
+}
+
+#[test]
+fn help_returns_none() {
+    assert!(parse(&["--help"]).unwrap().is_none());
+    assert!(parse(&["game.gb", "-h"]).unwrap().is_none());
+}
+
+#[test]
+fn unknown_and_extra_arguments_are_rejected() {
+    assert_eq!(parse_err(&["--turbo", "a.gb"]), "Unknown option: --turbo");
+    assert_eq!(parse_err(&["a.gb", "b.gb"]), "Unexpected argument: b.gb");
+}
+
+#[test]
+fn missing_and_invalid_values_are_rejected() {
+    assert_eq!(parse_err(&["a.gb", "--model"]), "Missing value for --model");
+    assert_eq!(
+        parse_err(&["a.gb", "--load-state"]),
+        "Missing value for --load-state"
+    );
+    assert_eq!(parse_err(&["--model", "NES", "a.gb"]), "Unknown model: NES");
+    assert_eq!(
+        parse_err(&["--headless", "--frames", "ten", "a.gb"]),
+        "Invalid value for --frames: ten"
+    );
+    assert_eq!(
+        parse_err(&["--scale", "11", "a.gb"]),
+        "--scale must be between 1 and 10"
+    );
+    assert_eq!(
+        parse_err(&["--scale", "0", "a.gb"]),
+        "--scale must be between 1 and 10"
+    );
+}
+
+#[test]
+fn options_needing_a_rom_are_rejected_without_one() {
+    assert_eq!(
+        parse_err(&["--load-state", "game.state"]),
+        "--load-state requires a ROM"
+    );
+    assert_eq!(
+        parse_err(&["--link-listen", "0.0.0.0:8765"]),
+        "--link-listen and --link-connect require a ROM"
+    );
+    assert_eq!(parse_err(&["--printer"]), "--printer requires a ROM");
+    assert_eq!(
+        parse_err(&["--headless", "--frames", "10"]),
+        "--headless requires a ROM"
+    );
+}
+
+#[test]
+fn conflicting_options_are_rejected() {
+    assert_eq!(
+        parse_err(&["--printer", "--link-connect", "localhost:8765", "a.gb"]),
+        "--printer can't be used with a link cable"
+    );
+    assert_eq!(
+        parse_err(&["--headless", "a.gb"]),
+        "--headless requires --frames"
+    );
+    assert_eq!(
+        parse_err(&["--frames", "10", "a.gb"]),
+        "--frames is only valid with --headless"
+    );
+    assert_eq!(
+        parse_err(&["--screenshot", "out.png", "a.gb"]),
+        "--screenshot is only valid with --headless"
+    );
+}
@@ -0,0 +1,48 @@
+//! Test cartridges shared by the integration tests.
+
+// Each test crate only uses part of this module
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use zetaboy::{Emulator, EmulatorConfig};
+
+/// JR -2
+pub const LOOP: &[u8] = &[0x18, 0xFE];
+/// Keeps incrementing the byte at $C000: LD HL,$C000; INC (HL); JR -3
+pub const COUNTER: &[u8] = &[0x21, 0x00, 0xC0, 0x34, 0x18, 0xFD];
+
+/// A file name in the temporary directory, unique to this test process.
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("zetaboy-{}-{}", std::process::id(), name))
+}
+
+/// Writes a 32 KiB ROM-only cartridge that jumps to `code` at 0x0150.
+///
+/// `overrides` are bytes written over the ROM at the given addresses, such as
+/// header fields (title, cartridge type...) or markers checked by the test.
+pub fn write_rom(name: &str, code: &[u8], overrides: &[(usize, &[u8])]) -> PathBuf {
+    let mut rom = vec![0; 0x8000];
+    rom[0x100..0x104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]); // NOP; JP $0150
+    rom[0x150..0x150 + code.len()].copy_from_slice(code);
+    for &(address, bytes) in overrides {
+        rom[address..address + bytes.len()].copy_from_slice(bytes);
+    }
+
+    let path = temp_path(&format!("{}.gb", name));
+    std::fs::write(&path, rom).expect("Failed to write test ROM");
+    path
+}
+
+/// Writes a cartridge like `write_rom` and loads it with `config`, removing the file.
+pub fn load_rom(
+    name: &str,
+    code: &[u8],
+    overrides: &[(usize, &[u8])],
+    config: &EmulatorConfig,
+) -> Emulator {
+    let path = write_rom(name, code, overrides);
+    let emulator = Emulator::with_config(path.to_str().unwrap(), config);
+    let _ = std::fs::remove_file(path);
+    emulator.expect("Failed to load test ROM")
+}
@@ -0,0 +1,88 @@
+//! Colourisation of DMG cartridges on CGB hardware.
+
+mod common;
+
+use zetaboy::emulator::boot::{DEFAULT_COMPAT_PALETTES, PaletteCombo, palettes_for_title};
+use zetaboy::emulator::model::Model;
+use zetaboy::{Emulator, EmulatorConfig};
+
+fn title(name: &str) -> [u8; 16] {
+    let mut title = [0; 16];
+    title[..name.len()].copy_from_slice(name.as_bytes());
+    title
+}
+
+/// Loads a DMG-only ROM with the given title and old licensee code on a CGB.
+fn load(
+    name: &str,
+    game_title: &str,
+    licensee: u8,
+    compat_palette: Option<PaletteCombo>,
+) -> Emulator {
+    let header: &[(usize, &[u8])] = &[(0x134, &title(game_title)), (0x14B, &[licensee])];
+    let config = EmulatorConfig {
+        model: Some(Model::Cgb),
+        compat_palette,
+        ..Default::default()
+    };
+    common::load_rom(name, common::LOOP, header, &config)
+}
+
+fn bg_palette(emulator: &Emulator) -> [u16; 4] {
+    std::array::from_fn(|i| emulator.cpu.bus.ppu.cgb_palette_rgb555(0, i as u8, false))
+}
+
+#[test]
+fn titles_select_boot_rom_palettes() {
+    // Tetris: white, yellow, red and black everywhere
+    let tetris = palettes_for_title(&title("TETRIS"));
+    assert_eq!(tetris.bg, [0x7FFF, 0x03FF, 0x001F, 0x0000]);
+    assert_eq!(tetris.obj0, tetris.bg);
+
+    // Shared checksum, told apart by the 4th letter (same letters, reordered)
+    let mario = palettes_for_title(&title("SUPER MARIOLAND"));
+    assert_eq!(mario.bg, [0x7ED6, 0x4BFF, 0x2175, 0x0000]);
+    // This OBJ palette starts one colour before a palette boundary
+    assert_eq!(mario.obj0, [0x0000, 0x7FFF, 0x421F, 0x1CF2]);
+    assert_eq!(
+        palettes_for_title(&title("SUEPR MARIOLAND")),
+        DEFAULT_COMPAT_PALETTES
+    );
+
+    assert_eq!(
+        palettes_for_title(&title("ZETABOY TEST")),
+        DEFAULT_COMPAT_PALETTES
+    );
+    assert_eq!(PaletteCombo::RightA.palettes(), DEFAULT_COMPAT_PALETTES);
+}
+
+#[test]
+fn only_nintendo_games_are_colourised() {
+    let nintendo_emulator = load("compat-nintendo", "TETRIS", 0x01, None);
+    let other_emulator = load("compat-other", "TETRIS", 0x0A, None);
+
+    assert!(nintendo_emulator.cpu.bus.ppu.dmg_compat);
+    assert_eq!(
+        bg_palette(&nintendo_emulator),
+        [0x7FFF, 0x03FF, 0x001F, 0x0000]
+    );
+    assert_eq!(bg_palette(&other_emulator), DEFAULT_COMPAT_PALETTES.bg);
+}
+
+#[test]
+fn button_combinations_override_the_title() {
+    let mut emulator = load(
+        "compat-combo",
+        "TETRIS",
+        0x01,
+        Some("left+b".parse().unwrap()),
+    );
+
+    assert_eq!(bg_palette(&emulator), [0x7FFF, 0x5294, 0x294A, 0x0000]);
+
+    emulator.set_compat_palette(Some(PaletteCombo::RightB));
+    assert_eq!(bg_palette(&emulator), [0x0000, 0x4200, 0x037F, 0x7FFF]);
+    emulator.set_compat_palette(None);
+    assert_eq!(bg_palette(&emulator), [0x7FFF, 0x03FF, 0x001F, 0x0000]);
+    assert!("up+c".parse::<PaletteCombo>().is_err());
+}
@@ -0,0 +1,196 @@
+//! Conformance tests.
+//!
+//! The synthetic ROM tests always run. The full test-ROM suite from
+//! `tests/test_roms.txt` only runs when `ZETABOY_TEST_ROMS` points at a
+//! directory containing the ROMs, and fails on any regression from the
+//! expected results.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use zetaboy::conformance::{self, Check, ScreenshotReference, Status, TestRom};
+use zetaboy::emulator::model::Model;
+use zetaboy::emulator::ppu::{HEIGHT, WIDTH};
+use zetaboy::screenshot;
+use zetaboy::{Emulator, EmulatorConfig};
+
+const MANIFEST: &str = include_str!("test_roms.txt");
+const README: &str = include_str!("../README.md");
+
+fn load(name: &str, code: &[u8]) -> Emulator {
+    common::load_rom(name, code, &[], &EmulatorConfig::default())
+}
+
+/// Name of a file in the temporary directory, relative to it.
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().into_owned()
+}
+
+/// Loads B, C, D, E, H and L with `values`, then runs `LD B,B` and loops forever.
+fn mooneye_code(values: [u8; 6]) -> Vec<u8> {
+    let mut code = Vec::new();
+    for (opcode, value) in [0x06, 0x0E, 0x16, 0x1E, 0x26, 0x2E].into_iter().zip(values) {
+        code.extend([opcode, value]); // LD r,n
+    }
+    code.extend([0x40, 0x18, 0xFE]); // LD B,B; JR -2
+    code
+}
+
+/// Sends `text` through the serial port with the internal clock, then loops forever.
+fn serial_code(text: &str) -> Vec<u8> {
+    let mut code = Vec::new();
+    for byte in text.bytes() {
+        code.extend([0x3E, byte, 0xE0, 0x01]); // LD A,byte; LDH (SB),A
+        code.extend([0x3E, 0x81, 0xE0, 0x02]); // LD A,$81; LDH (SC),A
+        code.extend([0xF0, 0x02, 0xCB, 0x7F, 0x20, 0xFA]); // wait: LDH A,(SC); BIT 7,A; JR NZ,wait
+    }
+    code.extend([0x18, 0xFE]); // JR -2
+    code
+}
+
+#[test]
+fn mooneye_fibonacci_signature_passes() {
+    let mut emulator = load("mooneye-pass", &mooneye_code([3, 5, 8, 13, 21, 34]));
+    let result = conformance::run_mooneye(&mut emulator, 10);
+
+    assert_eq!(result.status, Status::Pass, "{}", result.detail);
+}
+
+#[test]
+fn mooneye_failure_signature_fails() {
+    let mut emulator = load("mooneye-fail", &mooneye_code([0x42; 6]));
+    let result = conformance::run_mooneye(&mut emulator, 10);
+
+    assert_eq!(result.status, Status::Fail, "{}", result.detail);
+    assert_eq!(result.detail, "B=66 C=66 D=66 E=66 H=66 L=66");
+}
+
+#[test]
+fn blargg_serial_output_is_judged() {
+    let mut emulator = load("blargg-pass", &serial_code("cpu_instrs\n\nPassed\n"));
+    let result = conformance::run_blargg(&mut emulator, 10);
+    assert_eq!(result.status, Status::Pass, "{}", result.detail);
+    assert!(result.detail.starts_with("cpu_instrs"));
+
+    let mut emulator = load("blargg-fail", &serial_code("Failed #2\n"));
+    let result = conformance::run_blargg(&mut emulator, 10);
+    assert_eq!(result.status, Status::Fail, "{}", result.detail);
+}
+
+#[test]
+fn dmg_screenshot_is_compared_in_greys() {
+    // BGP = $1B: colour 0 is black, shown dark green in the default palette
+    let path = common::write_rom("screenshot", &[0x3E, 0x1B, 0xE0, 0x47, 0x18, 0xFE], &[]);
+    let dir = std::env::temp_dir();
+    let mut pngs = Vec::new();
+    let mut reference = |name: &str, pixel: u32| {
+        let png = common::temp_path(&format!("{}.png", name));
+        let pixels = vec![pixel; WIDTH * HEIGHT];
+        screenshot::save_png(&png, &pixels, WIDTH, HEIGHT).unwrap();
+        pngs.push(png.clone());
+        TestRom {
+            section: String::new(),
+            name: name.to_string(),
+            path: file_name(&path),
+            check: Check::Screenshot(ScreenshotReference::Image(file_name(&png))),
+            frames: 5,
+            expected: Status::Pass,
+            note: String::new(),
+        }
+    };
+    // Close to black: reference PNGs are quantised to four greys
+    let black = reference("screenshot-black", 0xFF0A0A0A);
+    let white = reference("screenshot-white", 0xFFFFFFFF);
+
+    let black_result = conformance::run_test(&black, &dir).unwrap();
+    let white_result = conformance::run_test(&white, &dir).unwrap();
+    for file in pngs.iter().chain([&path]) {
+        let _ = std::fs::remove_file(file);
+    }
+
+    assert_eq!(black_result.status, Status::Pass, "{}", black_result.detail);
+    assert_eq!(white_result.status, Status::Fail, "{}", white_result.detail);
+}
+
+#[test]
+fn model_is_picked_from_test_name() {
+    assert_eq!(
+        conformance::model_for_test("boot_regs-dmgABC"),
+        Some(Model::Dmg)
+    );
+    assert_eq!(
+        conformance::model_for_test("boot_regs-mgb"),
+        Some(Model::Mgb)
+    );
+    assert_eq!(conformance::model_for_test("boot_div-S"), Some(Model::Sgb));
+    assert_eq!(
+        conformance::model_for_test("boot_hwio-dmg0"),
+        Some(Model::Dmg0)
+    );
+    assert_eq!(
+        conformance::model_for_test("unused_hwio-GS"),
+        Some(Model::Dmg)
+    );
+    assert_eq!(conformance::model_for_test("instr_timing"), None);
+}
+
+#[test]
+fn manifest_is_valid() {
+    let roms = conformance::parse_manifest(MANIFEST).expect("Invalid manifest");
+    assert!(!roms.is_empty());
+}
+
+#[test]
+fn readme_tables_match_manifest() {
+    let results: Vec<(TestRom, Status)> = conformance::parse_manifest(MANIFEST)
+        .unwrap()
+        .into_iter()
+        .map(|rom| {
+            let status = rom.expected;
+            (rom, status)
+        })
+        .collect();
+    let begin = README.find(conformance::README_BEGIN).unwrap() + conformance::README_BEGIN.len();
+    let end = README.find(conformance::README_END).unwrap();
+
+    assert_eq!(
+        &README[begin..end],
+        format!("\n\n{}\n", conformance::markdown_tables(&results)),
+        "README tables are out of date, regenerate them from tests/test_roms.txt"
+    );
+}
+
+#[test]
+fn test_rom_suite_has_no_regressions() {
+    let Some(roms_dir) = std::env::var_os("ZETABOY_TEST_ROMS") else {
+        eprintln!("ZETABOY_TEST_ROMS not set, skipping test ROM suite");
+        return;
+    };
+    let roms_dir = PathBuf::from(roms_dir);
+
+    let mut regressions = Vec::new();
+    for rom in conformance::parse_manifest(MANIFEST).unwrap() {
+        let result = conformance::run_test(&rom, &roms_dir);
+        match result {
+            Ok(result) if result.status == rom.expected => {}
+            Ok(_) if rom.expected == Status::Fail => {
+                eprintln!("{} now passes, update tests/test_roms.txt", rom.name);
+            }
+            Ok(result) if rom.expected == Status::Untested => {
+                eprintln!(
+                    "{}: {:?}, record it in tests/test_roms.txt",
+                    rom.name, result.status
+                );
+            }
+            Ok(result) => regressions.push(format!("{}: {}", rom.name, result.detail.trim())),
+            Err(e) => regressions.push(format!("{}: {}", rom.name, e)),
+        }
+    }
+
+    assert!(
+        regressions.is_empty(),
+        "Regressions:\n{}",
+        regressions.join("\n")
+    );
+}
@@ -0,0 +1,83 @@
+//! Breakpoint and watchpoint tests.
+
+mod common;
+
+use zetaboy::emulator::debugger::{Access, Breakpoint, DebugHit, Watchpoint};
+use zetaboy::{Emulator, EmulatorConfig};
+
+fn load_counter_rom(name: &str) -> Emulator {
+    common::load_rom(name, common::COUNTER, &[], &EmulatorConfig::default())
+}
+
+#[test]
+fn breakpoints_are_parsed_with_optional_bank() {
+    let plain: Breakpoint = "$4A10".parse().unwrap();
+    let banked: Breakpoint = "03:4A10".parse().unwrap();
+
+    assert_eq!((plain.bank, plain.address), (None, 0x4A10));
+    assert_eq!((banked.bank, banked.address), (Some(3), 0x4A10));
+    assert_eq!(banked.to_string(), "03:4A10");
+    assert!("03:XYZ".parse::<Breakpoint>().is_err());
+    assert!("C100-C000".parse::<Watchpoint>().is_err());
+}
+
+#[test]
+fn breakpoint_stops_before_instruction_and_resumes() {
+    let mut emulator = load_counter_rom("breakpoint");
+    let debugger = &mut emulator.cpu.bus.debugger;
+    debugger.breakpoints.push("0153".parse().unwrap());
+    // Bank 1 is mapped at 4000-7FFF, never at 0153
+    debugger.breakpoints.push("01:0150".parse().unwrap());
+
+    emulator.run_frame();
+    assert_eq!(
+        emulator.cpu.bus.debugger.hit(),
+        Some(DebugHit::Breakpoint { index: 0, pc: 0x0153 })
+    );
+    assert_eq!(emulator.cpu.reg.pc, 0x0153);
+    assert_eq!(emulator.cpu.bus.read_byte(0xC000), 0);
+
+    // Nothing runs until the hit is cleared
+    assert_eq!(emulator.run_cycles(100), 0);
+    emulator.cpu.bus.debugger.resume();
+    emulator.run_frame();
+    assert_eq!(emulator.cpu.reg.pc, 0x0153);
+    assert_eq!(emulator.cpu.bus.read_byte(0xC000), 1);
+}
+
+#[test]
+fn toggling_removes_unbanked_breakpoints() {
+    let mut emulator = load_counter_rom("toggle-breakpoint");
+    let debugger = &mut emulator.cpu.bus.debugger;
+    debugger.breakpoints.push("4A10".parse().unwrap());
+
+    debugger.toggle_breakpoint(0x4A10, Some(3));
+    assert!(debugger.breakpoints.is_empty());
+
+    debugger.toggle_breakpoint(0x4A10, Some(3));
+    assert_eq!(debugger.breakpoints, vec!["03:4A10".parse().unwrap()]);
+    // Another bank mapped at the same address doesn't match
+    debugger.toggle_breakpoint(0x4A10, Some(4));
+    assert_eq!(debugger.breakpoints.len(), 2);
+}
+
+#[test]
+fn write_watchpoint_reports_access() {
+    let mut emulator = load_counter_rom("watchpoint");
+    let mut watchpoint: Watchpoint = "C000-C00F".parse().unwrap();
+    watchpoint.read = false;
+    emulator.cpu.bus.debugger.watchpoints.push(watchpoint);
+
+    emulator.run_frame();
+
+    assert_eq!(
+        emulator.cpu.bus.debugger.hit(),
+        Some(DebugHit::Watchpoint {
+            index: 0,
+            address: 0xC000,
+            access: Access::Write,
+            value: 1,
+            pc: 0x0153,
+        })
+    );
+}
@@ -0,0 +1,86 @@
+//! Disassembler output tests.
+
+use zetaboy::emulator::cpu::disassembler::{disassemble, disassemble_bytes};
+
+fn text(code: &[u8], base: u16) -> Vec<String> {
+    disassemble_bytes(code, base)
+        .into_iter()
+        .map(|instruction| instruction.text)
+        .collect()
+}
+
+#[test]
+fn operands_are_resolved() {
+    let code = [
+        0x2A, // LD A,(HL+)
+        0x20, 0x0F, // JR NZ,+15
+        0x18, 0xFE, // JR -2
+        0xFA, 0x34, 0x12, // LD A,($1234)
+        0xE0, 0x44, // LDH ($FF44),A
+        0xF8, 0xFE, // LD HL,SP-2
+        0xCB, 0x7E, // BIT 7,(HL)
+        0xC4, 0x00, 0x40, // CALL NZ,$4000
+        0xFE, 0x90, // CP $90
+        0x08, 0x00, 0xC0, // LD ($C000),SP
+        0xFF, // RST $38
+    ];
+
+    assert_eq!(
+        text(&code, 0xC000),
+        [
+            "LD A,(HL+)",
+            "JR NZ,$C012",
+            "JR $C003",
+            "LD A,($1234)",
+            "LDH ($FF44),A",
+            "LD HL,SP-$02",
+            "BIT 7,(HL)",
+            "CALL NZ,$4000",
+            "CP $90",
+            "LD ($C000),SP",
+            "RST $38",
+        ]
+    );
+}
+
+#[test]
+fn byte_loads_name_their_registers() {
+    let code = [
+        0x7E, // LD A,(HL)
+        0x70, // LD (HL),B
+        0x36, 0x12, // LD (HL),$12
+        0x0E, 0x34, // LD C,$34
+        0x53, // LD D,E
+    ];
+
+    assert_eq!(
+        text(&code, 0x0000),
+        [
+            "LD A,(HL)",
+            "LD (HL),B",
+            "LD (HL),$12",
+            "LD C,$34",
+            "LD D,E"
+        ]
+    );
+}
+
+#[test]
+fn invalid_and_truncated_instructions_are_data() {
+    // 0xD3 has no instruction, 0xC3 is missing its address
+    let instructions = disassemble_bytes(&[0xD3, 0x00, 0xC3, 0x00], 0x0100);
+    let text: Vec<_> = instructions.iter().map(|i| i.text.as_str()).collect();
+    assert_eq!(text, ["DB $D3", "NOP", "DB $C3", "NOP"]);
+    assert_eq!(instructions[2].address, 0x0102);
+}
+
+#[test]
+fn sizes_follow_immediates() {
+    let memory = [0x01, 0x34, 0x12, 0xCB, 0x37];
+    let read = |address: u16| memory[address as usize];
+
+    let first = disassemble(read, 0);
+    assert_eq!(first.text, "LD BC,$1234");
+    assert_eq!(first.bytes, [0x01, 0x34, 0x12]);
+    assert_eq!(disassemble(read, 3).size(), 2);
+}
@@ -0,0 +1,39 @@
+//! Custom DMG display palettes.
+
+use zetaboy::emulator::ppu::dmg_palette::{DMG_GREEN, DmgLayer, DmgPalettes, PRESETS};
+use zetaboy::emulator::ppu::{PPU, ViewerPalette};
+
+#[test]
+fn palette_files_set_one_or_all_layers() {
+    let uniform = DmgPalettes::parse("#FFFFFF, AAAAAA 0x555555 000000 ; greys").unwrap();
+    assert_eq!(uniform, DmgPalettes::uniform(PRESETS[4].shades));
+
+    let text = "; BG\n000001 000002 000003 000004\n000005 000006 000007 000008\n\
+                000009 00000A 00000B 00000C\n";
+    let layers = DmgPalettes::parse(text).unwrap();
+    assert_eq!(
+        layers.obj0,
+        [0xFF000005, 0xFF000006, 0xFF000007, 0xFF000008]
+    );
+    assert_eq!(
+        DmgPalettes::parse(&layers.to_file_string()).unwrap(),
+        layers
+    );
+
+    assert!(DmgPalettes::parse("FFFFFF AAAAAA 555555 000000 123456").is_err());
+    assert!(DmgPalettes::parse("FFFFFF AAAAAA 555555 00000G").is_err());
+}
+
+#[test]
+fn dmg_colors_use_the_layer_palette() {
+    let mut ppu = PPU::new();
+    ppu.write_register(0xFF47, 0b11_10_01_00); // BGP
+    ppu.write_register(0xFF49, 0b00_01_10_11); // OBP1, reversed
+    let mut palettes = DmgPalettes::default();
+    palettes.set_layer(DmgLayer::Obj1, PRESETS[4].shades);
+    ppu.dmg_palettes = palettes;
+
+    assert_eq!(ppu.palette_color(ViewerPalette::Bgp, 3), DMG_GREEN[3]);
+    assert_eq!(ppu.palette_color(ViewerPalette::Obp1, 3), 0xFFFFFFFF);
+    assert_eq!(ppu.palette_color(ViewerPalette::Obp1, 0), 0xFF000000);
+}
@@ -0,0 +1,174 @@
+//! Serial transfers: the link cable between two emulators and the capture API.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use zetaboy::emulator::serial::{LinkCable, SerialTransfer};
+use zetaboy::{Emulator, EmulatorConfig};
+
+/// Loads `sb` into SB, starts a transfer with `sc` and stores the received
+/// byte at 0xC000 once it completes. 0xC001 counts the polls of SC meanwhile.
+fn load(name: &str, sb: u8, sc: u8) -> Emulator {
+    #[rustfmt::skip]
+    let program = [
+        0x3E, sb,         // LD A, sb
+        0xE0, 0x01,       // LDH (SB), A
+        0x3E, sc,         // LD A, sc
+        0xE0, 0x02,       // LDH (SC), A
+        0x21, 0x01, 0xC0, // LD HL, $C001
+        0x34,             // INC (HL)
+        0xF0, 0x02,       // LDH A, (SC)
+        0xCB, 0x7F,       // BIT 7, A
+        0x20, 0xF9,       // JR NZ, -7
+        0xF0, 0x01,       // LDH A, (SB)
+        0xEA, 0x00, 0xC0, // LD ($C000), A
+        0x18, 0xFE,       // JR -2
+    ];
+    let mut emulator = common::load_rom(name, &program, &[], &EmulatorConfig::default());
+    emulator.cpu.bus.write_byte(0xC000, 0);
+    emulator.cpu.bus.write_byte(0xC001, 0);
+    emulator
+}
+
+fn received(emulator: &Emulator) -> u8 {
+    emulator.cpu.bus.read_byte(0xC000)
+}
+
+/// How long the transfer took, in polls of SC.
+fn polls(emulator: &Emulator) -> u8 {
+    emulator.cpu.bus.read_byte(0xC001)
+}
+
+#[test]
+fn linked_emulators_exchange_bytes() {
+    let mut master = load("link-master", 0x42, 0x81);
+    let mut slave = load("link-slave", 0x24, 0x80);
+    master.link(&mut slave);
+    for _ in 0..3 {
+        master.run_frame_linked(&mut slave);
+    }
+
+    assert_eq!(received(&master), 0x24);
+    assert_eq!(received(&slave), 0x42);
+    assert_eq!(slave.cpu.bus.read_byte(0xFF02) & 0x80, 0);
+}
+
+#[test]
+fn bytes_wait_for_an_external_clock_transfer() {
+    let mut master = load("early-master", 0x42, 0x81);
+    // Not ready: SC is written without the transfer bit
+    let mut slave = load("early-slave", 0x24, 0x00);
+    master.link(&mut slave);
+    for _ in 0..3 {
+        master.run_frame_linked(&mut slave);
+    }
+    assert_eq!(slave.cpu.bus.read_byte(0xFF01), 0x24);
+    assert_eq!(master.cpu.bus.read_byte(0xFF02) & 0x80, 0x80);
+
+    slave.cpu.bus.write_byte(0xFF02, 0x80);
+    master.run_frame_linked(&mut slave);
+    assert_eq!(slave.cpu.bus.read_byte(0xFF01), 0x42);
+    assert_eq!(received(&master), 0x24);
+}
+
+#[test]
+fn transfers_are_captured_both_ways() {
+    let mut master = load("capture-master", 0x42, 0x81);
+    let mut slave = load("capture-slave", 0x24, 0x80);
+    master.link(&mut slave);
+    for _ in 0..3 {
+        master.run_frame_linked(&mut slave);
+    }
+
+    let transfer = SerialTransfer {
+        sent: 0x42,
+        received: 0x24,
+        internal_clock: true,
+    };
+    assert_eq!(master.take_serial_transfers(), [transfer]);
+    assert!(master.take_serial_transfers().is_empty());
+    assert_eq!(master.take_serial_output(), [0x42], "taken separately");
+    assert_eq!(slave.take_serial_output(), [0x24]);
+    assert_eq!(slave.take_serial_transfers().len(), 1);
+}
+
+#[test]
+fn stdout_printing_is_opt_in_and_kept() {
+    let mut emulator = load("print-serial", 0x42, 0x81);
+    assert!(!emulator.config.print_serial);
+
+    emulator.set_print_serial(true);
+    emulator.reset().unwrap();
+    assert!(emulator.config.print_serial);
+    emulator.run_frame();
+    assert_eq!(emulator.take_serial_output(), [0x42]);
+}
+
+#[test]
+fn unplugged_port_reads_ones() {
+    let mut master = load("unplugged-master", 0x42, 0x81);
+    let mut slave = load("unplugged-slave", 0x24, 0x80);
+    for _ in 0..3 {
+        master.run_frame();
+        slave.run_frame();
+    }
+
+    assert_eq!(received(&master), 0xFF);
+    // Nothing drives the clock, so the external-clock transfer never ends
+    assert_eq!(received(&slave), 0);
+    assert_eq!(slave.cpu.bus.read_byte(0xFF02) & 0x80, 0x80);
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_socket_link() {
+    let path = common::temp_path("link.sock");
+    let address = format!("unix:{}", path.display());
+
+    let listen_address = address.clone();
+    let listener = std::thread::spawn(move || LinkCable::listen(&listen_address).unwrap());
+    let mut cable = None;
+    for _ in 0..100 {
+        match LinkCable::connect(&address) {
+            Ok(connected) => {
+                cable = Some(connected);
+                break;
+            }
+            Err(_) => std::thread::sleep(std::time::Duration::from_millis(10)),
+        }
+    }
+    let connected = cable.expect("Failed to connect to the listening emulator");
+    let listening = listener.join().unwrap();
+
+    // Each emulator runs on its own thread like separate processes, the slave
+    // starting late: the master must wait for it
+    let run = |name: &'static str, sb: u8, sc: u8, cable: LinkCable, delay: u64| {
+        std::thread::spawn(move || {
+            let mut emulator = load(name, sb, sc);
+            emulator.set_serial_device(Some(Arc::new(Mutex::new(cable))));
+            std::thread::sleep(std::time::Duration::from_millis(delay));
+            for _ in 0..3 {
+                emulator.run_frame();
+            }
+            emulator
+        })
+    };
+    let master = run("socket-master", 0x5A, 0x81, listening, 0);
+    let slave = run("socket-slave", 0xA5, 0x80, connected, 200);
+    let master = master.join().unwrap();
+    let slave = slave.join().unwrap();
+
+    // Timed exactly as two emulators run in lockstep
+    let mut lockstep_master = load("lockstep-master", 0x5A, 0x81);
+    let mut lockstep_slave = load("lockstep-slave", 0xA5, 0x80);
+    lockstep_master.link(&mut lockstep_slave);
+    for _ in 0..3 {
+        lockstep_master.run_frame_linked(&mut lockstep_slave);
+    }
+
+    assert_eq!(received(&master), 0xA5);
+    assert_eq!(received(&slave), 0x5A);
+    assert_eq!(polls(&master), polls(&lockstep_master));
+    assert_eq!(polls(&slave), polls(&lockstep_slave));
+}
@@ -0,0 +1,59 @@
+//! Memory region views used by the memory viewer.
+
+mod common;
+
+use zetaboy::emulator::cpu::memory_bus::MemoryRegion;
+use zetaboy::{Emulator, EmulatorConfig};
+
+/// Loads a 32 KiB ROM with a marker byte at the start of bank 1.
+fn load_rom(name: &str) -> Emulator {
+    let marker: &[u8] = &[0xA5];
+    common::load_rom(
+        name,
+        common::LOOP,
+        &[(0x4000, marker)],
+        &EmulatorConfig::default(),
+    )
+}
+
+#[test]
+fn regions_cover_rom_and_internal_memory() {
+    let emulator = load_rom("regions-list");
+    let bus = &emulator.cpu.bus;
+    let regions = bus.memory_regions();
+
+    assert_eq!(regions[0], MemoryRegion::Bus);
+    for region in [
+        MemoryRegion::Rom(0),
+        MemoryRegion::Rom(1),
+        MemoryRegion::Oam,
+        MemoryRegion::Hram,
+    ] {
+        assert!(regions.contains(&region), "missing {}", region);
+    }
+    assert!(!regions.contains(&MemoryRegion::Rom(2)));
+
+    assert_eq!(bus.region_len(MemoryRegion::Bus), 0x10000);
+    assert_eq!(bus.region_len(MemoryRegion::Rom(1)), 0x4000);
+    assert_eq!(bus.region_len(MemoryRegion::Wram(1)), 0x1000);
+    assert_eq!(bus.region_len(MemoryRegion::Oam), 160);
+    assert_eq!(bus.region_len(MemoryRegion::Hram), 127);
+    assert_eq!(MemoryRegion::Wram(1).base_address(), 0xD000);
+}
+
+#[test]
+fn regions_read_and_write_behind_the_bus() {
+    let mut emulator = load_rom("regions-access");
+    let bus = &mut emulator.cpu.bus;
+
+    assert_eq!(bus.read_region(MemoryRegion::Rom(1), 0), 0xA5);
+
+    bus.write_region(MemoryRegion::Bus, 0xC000, 0x42);
+    assert_eq!(bus.read_region(MemoryRegion::Wram(0), 0), 0x42);
+
+    bus.write_region(MemoryRegion::Wram(1), 0x10, 0x99);
+    assert_eq!(bus.read_region(MemoryRegion::Bus, 0xD010), 0x99);
+
+    bus.write_region(MemoryRegion::Hram, 0, 0x7E);
+    assert_eq!(bus.peek_byte(0xFF80), 0x7E);
+}
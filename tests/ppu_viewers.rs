@@ -0,0 +1,119 @@
+//! Rendering of VRAM, OAM and palettes for the PPU debug viewers.
+
+use zetaboy::emulator::ppu::{
+    PPU, TILE_MAP_SIZE, TILE_SHEET_WIDTH, TileMap, ViewerPalette, WIDTH, rgb555_to_argb,
+    tile_address,
+};
+
+#[test]
+fn tile_sheet_decodes_2bpp_tiles() {
+    let mut ppu = PPU::new();
+    ppu.write_register(0xFF47, 0b11_10_01_00);
+    // Tile 1, row 0: low bits set (colour 1); row 1: high bits set (colour 2)
+    ppu.vram_bank_mut(0)[0x10..0x14].copy_from_slice(&[0xFF, 0x00, 0x00, 0xFF]);
+    // Tile 383, row 7, leftmost pixel: colour 3
+    ppu.vram_bank_mut(0)[0x17FE..0x1800].copy_from_slice(&[0x80, 0x80]);
+
+    let sheet = ppu.render_tile_sheet(0, ViewerPalette::Bgp);
+    let color = |index| ppu.palette_color(ViewerPalette::Bgp, index);
+
+    assert_eq!(sheet[8], color(1));
+    assert_eq!(sheet[TILE_SHEET_WIDTH + 15], color(2));
+    assert_eq!(sheet[2 * TILE_SHEET_WIDTH + 8], color(0));
+    assert_eq!(sheet[sheet.len() - 8], color(3));
+    assert_eq!(sheet[sheet.len() - 7], color(0));
+    assert_eq!(tile_address(383), 0x97F0);
+}
+
+#[test]
+fn tile_map_matches_rendered_scanline() {
+    let mut ppu = PPU::new();
+    ppu.write_register(0xFF47, 0b11_10_01_00);
+    ppu.write_register(0xFF43, 3); // SCX
+    ppu.write_register(0xFF42, 5); // SCY
+    let vram = ppu.vram_bank_mut(0);
+    for tile in 0..4 {
+        // Each row of tile n mixes colours so that scrolling matters
+        for row in 0..8 {
+            vram[tile * 16 + row * 2] = 0b1010_0101 ^ tile as u8;
+            vram[tile * 16 + row * 2 + 1] = 0b1100_0011 ^ row as u8;
+        }
+    }
+    for entry in 0..32 {
+        vram[0x1800 + entry] = (entry % 4) as u8;
+    }
+    ppu.write_register(0xFF40, 0x91); // LCD and BG on, tiles at $8000, map at $9800
+
+    let mut interrupt_flag = 0;
+    for _ in 0..456 {
+        ppu.tick(&mut interrupt_flag);
+    }
+
+    let map = ppu.render_tile_map(TileMap::Map9800);
+    for x in 0..WIDTH {
+        assert_eq!(ppu.buffer[x], map[5 * TILE_MAP_SIZE + x + 3], "pixel {}", x);
+    }
+    assert_eq!(ppu.map_layout().bg_map, TileMap::Map9800);
+}
+
+#[test]
+fn tile_map_entries_include_cgb_attributes() {
+    let mut ppu = PPU::new();
+    ppu.set_cgb_mode(true);
+    ppu.vram_bank_mut(0)[0x1C21] = 0x80;
+    ppu.vram_bank_mut(1)[0x1C21] = 0b0110_1011;
+
+    let entry = ppu.tile_map_entry(TileMap::Map9C00, 1, 1);
+    assert_eq!(entry.address, 0x9C21);
+    assert_eq!(entry.tile_id, 0x80);
+    // LCDC bit 4 clear: signed addressing from $9000
+    assert_eq!(entry.tile_address, 0x8800);
+    assert_eq!(entry.attributes.palette, 3);
+    assert_eq!(entry.attributes.vram_bank, 1);
+    assert!(entry.attributes.x_flip && entry.attributes.y_flip);
+    assert!(!entry.attributes.bg_priority);
+}
+
+#[test]
+fn sprites_over_the_line_limit_are_reported() {
+    let mut ppu = PPU::new();
+    // 11 sprites at screen Y 0-7, then one further down
+    for index in 0..11 {
+        ppu.oam_mut()[index * 4..index * 4 + 2].copy_from_slice(&[16, 8 + index as u8 * 8]);
+    }
+    ppu.oam_mut()[11 * 4..11 * 4 + 2].copy_from_slice(&[24, 8]);
+
+    let dropped = ppu.dropped_sprite_lines();
+    assert_eq!(dropped.len(), 40);
+    assert_eq!(dropped[10], (0..8).collect::<Vec<u8>>());
+    assert!(dropped[..10].iter().chain(&dropped[11..]).all(Vec::is_empty));
+    assert_eq!(ppu.oam_sprite(11).screen_y, 8);
+}
+
+#[test]
+fn sprite_preview_applies_flips_and_transparency() {
+    let mut ppu = PPU::new();
+    ppu.write_register(0xFF48, 0b11_10_01_00); // OBP0
+    // Tile 2, row 0: leftmost pixel colour 3
+    ppu.vram_bank_mut(0)[0x20..0x22].copy_from_slice(&[0x80, 0x80]);
+    ppu.oam_mut()[0..4].copy_from_slice(&[16, 8, 2, 0b0110_0000]); // X and Y flip
+
+    let pixels = ppu.render_sprite(0);
+    assert_eq!(pixels.len(), 64);
+    assert_eq!(pixels[63], ppu.palette_color(ViewerPalette::Obp0, 3));
+    assert_eq!(pixels[0], 0);
+}
+
+#[test]
+fn cgb_palette_colours_can_be_edited() {
+    let mut ppu = PPU::new();
+    ppu.set_cgb_mode(true);
+    ppu.set_cgb_palette_rgb555(7, 3, true, 0x001F);
+
+    assert_eq!(ppu.cgb_palette_rgb555(7, 3, true), 0x001F);
+    assert_eq!(ppu.palette_color(ViewerPalette::CgbObj(7), 3), 0xFFFF0000);
+    // The BG palette with the same number is untouched
+    assert_ne!(ppu.cgb_palette_rgb555(7, 3, false), 0x001F);
+    assert_eq!(rgb555_to_argb(0x7FFF), 0xFFFFFFFF);
+    assert_eq!(rgb555_to_argb(0x03E0), 0xFF00FF00);
+}
@@ -0,0 +1,130 @@
+//! Game Boy Printer packets, image decoding and saved pages.
+
+mod common;
+
+use std::sync::Arc;
+
+use zetaboy::EmulatorConfig;
+use zetaboy::emulator::serial::{PRINT_WIDTH, Printer, SerialDevice};
+use zetaboy::screenshot;
+
+const INIT: u8 = 0x01;
+const PRINT: u8 = 0x02;
+const DATA: u8 = 0x04;
+
+/// Sends a packet, returning the two bytes answered after it (alive, status).
+fn send(printer: &mut Printer, command: u8, compressed: bool, data: &[u8]) -> (u8, u8) {
+    let length = (data.len() as u16).to_le_bytes();
+    let mut body = vec![command, compressed as u8, length[0], length[1]];
+    body.extend_from_slice(data);
+    let checksum = body.iter().fold(0u16, |sum, &b| sum.wrapping_add(b as u16));
+
+    let mut packet = vec![0x88, 0x33];
+    packet.extend(body);
+    packet.extend(checksum.to_le_bytes());
+    for byte in packet {
+        assert_eq!(printer.transfer(byte), Some(0x00));
+    }
+    (printer.transfer(0).unwrap(), printer.transfer(0).unwrap())
+}
+
+/// One row of 20 tiles: colour 1 on the first line of each tile, colour 3 below.
+fn tile_row() -> Vec<u8> {
+    let tile: Vec<u8> = [0xFF, 0x00].into_iter().chain([0xFF; 14]).collect();
+    tile.repeat(PRINT_WIDTH / 8)
+}
+
+fn print(printer: &mut Printer, data: &[u8], compressed: bool, margins: u8) -> u8 {
+    send(printer, INIT, false, &[]);
+    let (_, status) = send(printer, DATA, compressed, data);
+    send(printer, DATA, false, &[]);
+    let (_, print_status) = send(printer, PRINT, false, &[1, margins, 0xE4, 0x40]);
+    assert_eq!(status & 0x08, 0x08, "DATA leaves unprocessed data");
+    print_status
+}
+
+#[test]
+fn prints_tiles_through_the_palette() {
+    let mut printer = Printer::new();
+    assert_eq!(send(&mut printer, INIT, false, &[]), (0x81, 0x00));
+
+    // No feed after the first print: both go on the same page
+    let status = print(&mut printer, &tile_row(), false, 0x10);
+    assert_eq!(status & 0x02, 0x02, "busy after PRINT");
+    assert!(printer.pages().is_empty());
+    print(&mut printer, &tile_row(), false, 0x03);
+
+    let page = &printer.pages()[0];
+    assert_eq!(page.height(), 16);
+    assert_eq!(page.pixels[0], 0xFFAAAAAA);
+    assert_eq!(page.pixels[PRINT_WIDTH], 0xFF000000);
+    assert_eq!(page.pixels[8 * PRINT_WIDTH + 159], 0xFFAAAAAA);
+    assert!(printer.current_page().pixels.is_empty());
+}
+
+#[test]
+fn compressed_data_matches_uncompressed() {
+    // Runs of 0xFF and literal 0xFF 0x00 pairs, per tile
+    let tile = [0x01, 0xFF, 0x00, 0x8C, 0xFF];
+    let compressed = tile.repeat(PRINT_WIDTH / 8);
+
+    let mut plain = Printer::new();
+    let mut rle = Printer::new();
+    print(&mut plain, &tile_row(), false, 0x03);
+    print(&mut rle, &compressed, true, 0x03);
+    assert_eq!(rle.pages()[0].pixels, plain.pages()[0].pixels);
+}
+
+#[test]
+fn bad_checksum_is_reported_and_ignored() {
+    let mut printer = Printer::new();
+    for byte in [0x88, 0x33, DATA, 0, 1, 0, 0xAA, 0x00, 0x00] {
+        printer.transfer(byte);
+    }
+    assert_eq!(printer.transfer(0), Some(0x81));
+    assert_eq!(printer.transfer(0), Some(0x01));
+
+    let (_, status) = send(&mut printer, PRINT, false, &[1, 0x03, 0xE4, 0x40]);
+    assert_eq!(status & 0x01, 0);
+    assert!(printer.pages().is_empty(), "nothing to print");
+}
+
+#[test]
+fn finished_pages_are_saved() {
+    let base = common::temp_path("printer");
+    let mut printer = Printer::new();
+    printer.save_to(Some(base.clone()));
+    print(&mut printer, &tile_row(), false, 0x00);
+    printer.finish_page();
+
+    let path = printer.pages()[0].path.clone().expect("Page wasn't saved");
+    let loaded = screenshot::load_png(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(
+        path,
+        base.with_file_name(format!("zetaboy-{}-printer-1.png", std::process::id()))
+    );
+    let (pixels, width, height) = loaded.unwrap();
+    assert_eq!((width, height), (PRINT_WIDTH, 8));
+    assert_eq!(pixels, printer.pages()[0].pixels);
+}
+
+#[test]
+fn emulator_keeps_the_printer_plugged_in() {
+    let mut emulator = common::load_rom("printer", common::LOOP, &[], &EmulatorConfig::default());
+    assert!(emulator.printer().is_none());
+
+    let printer = emulator.connect_printer();
+    assert!(Arc::ptr_eq(emulator.printer().unwrap(), &printer));
+    emulator.reset().unwrap();
+    assert!(
+        emulator.printer().is_some(),
+        "still plugged in after a reset"
+    );
+
+    emulator.set_serial_device(None);
+    assert!(emulator.printer().is_none());
+    emulator.plug_printer(printer.clone());
+    assert!(Arc::ptr_eq(emulator.printer().unwrap(), &printer));
+}
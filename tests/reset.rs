@@ -0,0 +1,55 @@
+//! Soft and hard reset tests.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use zetaboy::Emulator;
+
+/// Writes an MBC1+RAM+BATTERY cartridge that keeps incrementing the byte at $C000.
+fn write_rom(name: &str, marker: u8) -> PathBuf {
+    let overrides: &[(usize, &[u8])] = &[
+        (0x147, &[0x03]), // MBC1+RAM+BATTERY
+        (0x149, &[0x02]), // 8 KiB
+        (0x200, &[marker]),
+    ];
+    common::write_rom(name, common::COUNTER, overrides)
+}
+
+fn remove_files(rom_path: &Path) {
+    let _ = std::fs::remove_file(rom_path.with_extension("srm"));
+    let _ = std::fs::remove_file(rom_path);
+}
+
+#[test]
+fn soft_reset_restarts_and_keeps_cartridge_ram() {
+    let rom_path = write_rom("soft-reset", 0);
+    let mut emulator = Emulator::new(rom_path.to_str().unwrap(), None).unwrap();
+    emulator.run_frame();
+    emulator.cpu.bus.cart.ram_data[0x10] = 0x42;
+    assert_ne!(emulator.cpu.bus.read_byte(0xC000), 0);
+
+    emulator.reset().unwrap();
+    remove_files(&rom_path);
+
+    assert_eq!(emulator.cpu.reg.pc, 0x0100);
+    assert_eq!(emulator.cpu.bus.read_byte(0xC000), 0);
+    assert_eq!(emulator.cpu.bus.cart.ram_data[0x10], 0x42);
+}
+
+#[test]
+fn hard_reset_reloads_rom_from_disk() {
+    let rom_path = write_rom("hard-reset", 0x11);
+    let mut emulator = Emulator::new(rom_path.to_str().unwrap(), None).unwrap();
+    emulator.run_frame();
+    emulator.cpu.bus.cart.ram_data[0x10] = 0x42;
+
+    write_rom("hard-reset", 0x22);
+    let result = emulator.hard_reset();
+    remove_files(&rom_path);
+
+    result.unwrap();
+    assert_eq!(emulator.cpu.reg.pc, 0x0100);
+    assert_eq!(emulator.cpu.bus.read_byte(0x0200), 0x22);
+    assert_eq!(emulator.cpu.bus.cart.ram_data[0x10], 0x42);
+}
@@ -0,0 +1,60 @@
+//! Rewind buffer tests.
+
+mod common;
+
+use zetaboy::emulator::rewind::RewindConfig;
+use zetaboy::{Emulator, EmulatorConfig};
+
+fn load_counter_rom(name: &str, rewind: RewindConfig) -> Emulator {
+    let config = EmulatorConfig {
+        rewind: Some(rewind),
+        ..Default::default()
+    };
+    common::load_rom(name, common::COUNTER, &[], &config)
+}
+
+#[test]
+fn rewind_restores_snapshots_in_reverse_order() {
+    let config = RewindConfig {
+        interval: 1,
+        keyframe_interval: 4,
+        ..Default::default()
+    };
+    let mut emulator = load_counter_rom("rewind", config);
+
+    let mut counters = Vec::new();
+    for _ in 0..10 {
+        emulator.run_frame();
+        counters.push(emulator.cpu.bus.read_byte(0xC000));
+    }
+
+    for &expected in counters.iter().rev() {
+        assert!(emulator.rewind());
+        assert_eq!(emulator.cpu.bus.read_byte(0xC000), expected);
+    }
+    assert!(!emulator.rewind());
+}
+
+#[test]
+fn rewind_stays_within_memory_budget() {
+    let config = RewindConfig {
+        interval: 1,
+        keyframe_interval: 2,
+        memory_budget: 4096,
+    };
+    let mut emulator = load_counter_rom("rewind-budget", config);
+
+    let mut peak = 0;
+    for _ in 0..20 {
+        emulator.run_frame();
+        peak = peak.max(emulator.rewind_memory_usage());
+    }
+
+    // Only the newest keyframe group may go over the budget
+    let mut rewound = 0;
+    while emulator.rewind() {
+        rewound += 1;
+    }
+    assert!(rewound <= 2, "{} snapshots kept", rewound);
+    assert!(peak > 0);
+}
@@ -0,0 +1,95 @@
+//! Save-state slot and file format tests.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use zetaboy::Emulator;
+use zetaboy::emulator::save_state::SaveSlot;
+
+/// Writes a ROM-only cartridge titled `title` that loops at 0x0150.
+fn write_rom(name: &str, title: &[u8], global_checksum: [u8; 2]) -> PathBuf {
+    common::write_rom(
+        name,
+        common::LOOP,
+        &[(0x134, title), (0x14E, &global_checksum)],
+    )
+}
+
+fn load(path: &Path) -> Emulator {
+    Emulator::new(path.to_str().unwrap(), None).unwrap()
+}
+
+#[test]
+fn slot_round_trip_keeps_header_and_state() {
+    let rom_path = write_rom("slots", b"SLOT", [0x12, 0x34]);
+    let mut emulator = load(&rom_path);
+    emulator.run_frame();
+    let slot = SaveSlot::Numbered(3);
+    let state_path = emulator.save_state(slot).unwrap();
+    let header = emulator.state_header(slot);
+
+    emulator.cpu.reg.a = 0x42;
+    let loaded = emulator.load_state(slot);
+    let _ = std::fs::remove_file(&state_path);
+    let _ = std::fs::remove_file(&rom_path);
+
+    assert!(state_path.ends_with(".ss3"));
+    let header = header.unwrap();
+    assert_eq!(header.rom_title, "SLOT");
+    assert_eq!(header.rom_checksum, 0x1234);
+    assert_eq!(header.thumbnail.len(), 160 * 144);
+    loaded.unwrap();
+    assert_ne!(emulator.cpu.reg.a, 0x42);
+}
+
+#[test]
+fn state_for_another_rom_is_rejected() {
+    let rom_path = write_rom("state-rom-a", b"GAME A", [0x00, 0x01]);
+    let other_path = write_rom("state-rom-b", b"GAME B", [0x00, 0x02]);
+    let state_path = rom_path.with_extension("state");
+
+    let emulator = load(&rom_path);
+    emulator.save_state_to(state_path.to_str().unwrap()).unwrap();
+    let result = load(&other_path).load_state_from(state_path.to_str().unwrap());
+
+    let _ = std::fs::remove_file(&state_path);
+    let _ = std::fs::remove_file(&rom_path);
+    let _ = std::fs::remove_file(&other_path);
+
+    let Err(error) = result else {
+        panic!("state for another ROM was loaded");
+    };
+    assert!(error.to_string().contains("GAME A"), "{}", error);
+}
+
+#[test]
+fn corrupted_or_newer_states_are_rejected() {
+    let rom_path = write_rom("state-corrupt", b"CORRUPT", [0x00, 0x03]);
+    let state_path = rom_path.with_extension("state");
+    let mut emulator = load(&rom_path);
+    emulator.save_state_to(state_path.to_str().unwrap()).unwrap();
+    let state = std::fs::read(&state_path).unwrap();
+
+    // Flip a byte of the payload
+    let mut corrupted = state.clone();
+    *corrupted.last_mut().unwrap() ^= 0xFF;
+    std::fs::write(&state_path, corrupted).unwrap();
+    let corrupted_result = emulator.load_state_from(state_path.to_str().unwrap());
+
+    // Format version follows the 4-byte magic number
+    let mut newer = state.clone();
+    newer[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    std::fs::write(&state_path, newer).unwrap();
+    let newer_result = emulator.load_state_from(state_path.to_str().unwrap());
+
+    std::fs::write(&state_path, state).unwrap();
+    let valid_result = emulator.load_state_from(state_path.to_str().unwrap());
+
+    let _ = std::fs::remove_file(&state_path);
+    let _ = std::fs::remove_file(&rom_path);
+
+    assert!(corrupted_result.err().unwrap().to_string().contains("checksum"));
+    assert!(newer_result.err().unwrap().to_string().contains("version"));
+    valid_result.unwrap();
+}
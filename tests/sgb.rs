@@ -0,0 +1,153 @@
+//! Super Game Boy command packets and frame composition.
+
+mod common;
+
+use zetaboy::emulator::model::Model;
+use zetaboy::emulator::ppu::{WIDTH, rgb555_to_argb};
+use zetaboy::emulator::sgb::{Mask, SGB_HEIGHT, SGB_WIDTH};
+use zetaboy::{Emulator, EmulatorConfig};
+
+const RED: u16 = 0x001F;
+const BLUE: u16 = 0x7C00;
+
+/// Loads a cartridge that loops forever on an SGB, with SGB support if `sgb`.
+fn load(name: &str, sgb: bool) -> Emulator {
+    // SGB flag and the old licensee code that tells the SGB to check it
+    let sgb_header: &[(usize, &[u8])] = &[(0x146, &[0x03]), (0x14B, &[0x33])];
+    let config = EmulatorConfig {
+        model: Some(Model::Sgb),
+        ..Default::default()
+    };
+    common::load_rom(
+        name,
+        common::LOOP,
+        if sgb { sgb_header } else { &[] },
+        &config,
+    )
+}
+
+/// Sends one packet through P1: reset pulse, 128 data bits and the stop bit.
+fn send_packet(emulator: &mut Emulator, packet: [u8; 16]) {
+    let mut pulse = |value: u8| {
+        emulator.cpu.bus.write_byte(0xFF00, value);
+        emulator.cpu.bus.write_byte(0xFF00, 0x30);
+    };
+    pulse(0x00);
+    for bit in 0..128 {
+        let one = (packet[bit / 8] >> (bit % 8)) & 1 != 0;
+        pulse(if one { 0x10 } else { 0x20 });
+    }
+    pulse(0x20);
+}
+
+fn command(code: u8, bytes: &[u8]) -> [u8; 16] {
+    let mut packet = [0; 16];
+    packet[0] = code << 3 | 1;
+    packet[1..=bytes.len()].copy_from_slice(bytes);
+    packet
+}
+
+/// PAL01 with colour 3 of palette 0 red and colour 3 of palette 1 blue.
+fn pal01() -> [u8; 16] {
+    let colors = [0x7FFF, 0, 0, RED, 0, 0, BLUE];
+    let bytes: Vec<u8> = colors.iter().flat_map(|c: &u16| c.to_le_bytes()).collect();
+    command(0x00, &bytes)
+}
+
+/// Pixel of the Game Boy screen inside the SGB frame.
+fn screen_pixel(emulator: &Emulator, x: usize, y: usize) -> u32 {
+    let left = (SGB_WIDTH - WIDTH) / 2;
+    let top = (SGB_HEIGHT - 144) / 2;
+    emulator.framebuffer()[(top + y) * SGB_WIDTH + left + x]
+}
+
+/// A screen of colour index 0 shown as the darkest shade.
+fn darkest_screen(emulator: &mut Emulator) {
+    emulator.cpu.bus.write_byte(0xFF47, 0xFF);
+}
+
+#[test]
+fn only_sgb_cartridges_get_the_large_frame() {
+    let sgb = load("sgb-frame", true);
+    assert_eq!(sgb.frame_size(), (SGB_WIDTH, SGB_HEIGHT));
+    assert_eq!(sgb.framebuffer().len(), SGB_WIDTH * SGB_HEIGHT);
+
+    let dmg = load("sgb-dmg-frame", false);
+    assert!(dmg.cpu.bus.sgb.is_none());
+    assert_eq!(dmg.frame_size(), (160, 144));
+}
+
+#[test]
+fn palettes_are_assigned_per_block() {
+    let mut emulator = load("sgb-attributes", true);
+    darkest_screen(&mut emulator);
+    send_packet(&mut emulator, pal01());
+    // ATTR_DIV: palette 0 left of column 10, palette 1 on and right of it
+    send_packet(&mut emulator, command(0x06, &[0b01_0001, 10]));
+    emulator.run_frame();
+    emulator.run_frame();
+
+    assert_eq!(screen_pixel(&emulator, 0, 0), rgb555_to_argb(RED));
+    assert_eq!(screen_pixel(&emulator, 79, 143), rgb555_to_argb(RED));
+    assert_eq!(screen_pixel(&emulator, 80, 0), rgb555_to_argb(BLUE));
+
+    // ATTR_BLK: a block inside (and edge) of (0,0)-(1,1) in palette 1
+    send_packet(
+        &mut emulator,
+        command(0x04, &[1, 0b011, 0b0101, 0, 0, 1, 1]),
+    );
+    emulator.run_frame();
+    assert_eq!(screen_pixel(&emulator, 0, 0), rgb555_to_argb(BLUE));
+    assert_eq!(screen_pixel(&emulator, 16, 16), rgb555_to_argb(RED));
+
+    // Backdrop outside the Game Boy screen in colour 0
+    assert_eq!(emulator.framebuffer()[0], rgb555_to_argb(0x7FFF));
+}
+
+#[test]
+fn mask_hides_the_screen() {
+    let mut emulator = load("sgb-mask", true);
+    darkest_screen(&mut emulator);
+    send_packet(&mut emulator, pal01());
+    send_packet(&mut emulator, command(0x17, &[2]));
+    emulator.run_frame();
+
+    assert_eq!(emulator.cpu.bus.sgb.as_ref().unwrap().mask(), Mask::Black);
+    assert_eq!(screen_pixel(&emulator, 0, 0), rgb555_to_argb(0));
+
+    send_packet(&mut emulator, command(0x17, &[0]));
+    emulator.run_frame();
+    assert_eq!(screen_pixel(&emulator, 0, 0), rgb555_to_argb(RED));
+}
+
+#[test]
+fn multiplayer_cycles_controller_ids() {
+    let mut emulator = load("sgb-multiplayer", true);
+    send_packet(&mut emulator, command(0x11, &[0b01]));
+
+    let mut ids = Vec::new();
+    for _ in 0..3 {
+        ids.push(emulator.cpu.bus.read_byte(0xFF00) & 0x0F);
+        emulator.cpu.bus.write_byte(0xFF00, 0x10);
+        emulator.cpu.bus.write_byte(0xFF00, 0x30);
+    }
+    assert_eq!(ids, [0xF, 0xE, 0xF]);
+}
+
+#[test]
+fn save_states_keep_sgb_state() {
+    let mut emulator = load("sgb-state", true);
+    darkest_screen(&mut emulator);
+    send_packet(&mut emulator, pal01());
+    let path = common::temp_path("sgb.ss");
+    let path = path.to_str().unwrap();
+    emulator.save_state_to(path).unwrap();
+
+    let mut loaded = load("sgb-state-load", true);
+    let result = loaded.load_state_from(path);
+    let _ = std::fs::remove_file(path);
+    result.unwrap();
+    loaded.run_frame();
+
+    assert_eq!(screen_pixel(&loaded, 0, 0), rgb555_to_argb(RED));
+}